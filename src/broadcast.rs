@@ -0,0 +1,215 @@
+use std::{path::PathBuf, time::Duration};
+
+use xshell::Shell;
+
+use crate::{
+    cli::{BuildTxCmd, CodeId, Coin, Contract, ReadyTxCmd, TxId},
+    key::Key,
+    network::{
+        gas::{Gas, Price as GasPrice},
+        ChainId, Network,
+    },
+    Error,
+};
+
+/// A hook to modify the CLI command just before it is executed, only honoured by CLI based backends
+pub type PreExecuteBuildHook = Box<dyn for<'a> FnOnce(ReadyTxCmd<'a>) -> ReadyTxCmd<'a>>;
+
+/// A hook registered on a network instance with `Instance::on_tx`
+pub type TxHook = Box<dyn Fn(&TxEvent)>;
+
+/// The events fired to tx hooks over the lifetime of a tx
+pub enum TxEvent<'a> {
+    /// Fired just before the tx is broadcast
+    Broadcasting {
+        chain_id: &'a ChainId,
+        from: &'a Key,
+        kind: &'a TxKind,
+    },
+    /// Fired once the tx has been included in a block
+    Confirmed {
+        chain_id: &'a ChainId,
+        tx_hash: &'a TxId,
+        height: u64,
+        gas_wanted: u128,
+        gas_used: u128,
+        /// The time taken from broadcast to confirmation
+        duration: Duration,
+    },
+}
+
+/// A backend agnostic description of a contract tx, with messages already serialised to JSON
+pub enum TxKind {
+    Store {
+        wasm_path: PathBuf,
+    },
+    Instantiate {
+        code_id: CodeId,
+        label: String,
+        admin: Option<String>,
+        msg: String,
+    },
+    Execute {
+        contract: Contract,
+        msg: String,
+    },
+    Migrate {
+        contract: Contract,
+        code_id: CodeId,
+        msg: String,
+    },
+    UpdateAdmin {
+        contract: Contract,
+        new_admin: String,
+    },
+    ClearAdmin {
+        contract: Contract,
+    },
+}
+
+impl TxKind {
+    /// Build the CLI command for the tx
+    #[must_use]
+    pub fn into_cli_cmd(self, build: BuildTxCmd<'_>) -> ReadyTxCmd<'_> {
+        match self {
+            TxKind::Store { wasm_path } => build.wasm_store(wasm_path),
+            TxKind::Instantiate {
+                code_id,
+                label,
+                admin,
+                msg,
+            } => build.wasm_init(code_id, &label, &msg, admin.as_deref()),
+            TxKind::Execute { contract, msg } => build.wasm_exec(&contract, &msg),
+            TxKind::Migrate {
+                contract,
+                code_id,
+                msg,
+            } => build.wasm_migrate(&contract, code_id, &msg),
+            TxKind::UpdateAdmin {
+                contract,
+                new_admin,
+            } => build.wasm_set_admin(&contract, &new_admin),
+            TxKind::ClearAdmin { contract } => build.wasm_clear_admin(&contract),
+        }
+    }
+}
+
+/// How the gas limit of a tx is determined
+pub enum GasLimit {
+    Fixed(Gas),
+    Simulated { price: GasPrice, adjustment: f64 },
+}
+
+pub struct TxRequest {
+    pub kind: TxKind,
+    pub funds: Vec<Coin>,
+    pub gas: GasLimit,
+    /// A flat fee to pay instead of the gas price
+    pub fees: Option<Coin>,
+    pub pre_execute_hook: Option<PreExecuteBuildHook>,
+}
+
+/// Signs txs outside of the local keyring, e.g. with a KMS or a tmkms-like service, for keys added with `Cmd::add_offline_key`
+pub trait Signer {
+    /// Sign the `unsigned_tx` JSON generated by the CLI for the `from` key, returning the signed tx JSON.
+    /// The signer is responsible for obtaining the account number & sequence, e.g. with `QueryCmd::account`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementation.
+    fn sign(
+        &self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+        unsigned_tx: &str,
+    ) -> Result<String, Error>;
+}
+
+pub trait Broadcaster {
+    /// Sign & broadcast the tx described by `request` from the `from` key, returning the tx ID for querying
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementation.
+    fn broadcast(
+        &self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+        request: TxRequest,
+    ) -> Result<TxId, Error>;
+}
+
+/// Broadcasts txs by shelling out to the network's CLI
+pub struct CliBroadcaster;
+
+impl Broadcaster for CliBroadcaster {
+    fn broadcast(
+        &self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+        request: TxRequest,
+    ) -> Result<TxId, Error> {
+        let TxRequest {
+            kind,
+            funds,
+            gas,
+            fees,
+            pre_execute_hook,
+        } = request;
+
+        let chain_id = network.chain_id();
+
+        let node_uri = network.node_uri(sh)?;
+
+        let cmd = kind.into_cli_cmd(network.cli(sh)?.tx(from, &chain_id, &node_uri));
+
+        let cmd = if funds.is_empty() {
+            cmd
+        } else {
+            cmd.funds(&funds)
+        };
+
+        let cmd = match fees {
+            Some(Coin { amount, denom }) => cmd.fees(amount, &denom),
+            None => cmd,
+        };
+
+        let cmd = match pre_execute_hook {
+            Some(hook) => hook(cmd),
+            None => cmd,
+        };
+
+        if !from.is_offline() {
+            return match gas {
+                GasLimit::Fixed(gas) => cmd.execute(&gas),
+                GasLimit::Simulated { price, adjustment } => {
+                    cmd.execute_simulated(&price, adjustment)
+                }
+            };
+        }
+
+        let signer = network
+            .signer()
+            .ok_or_else(|| Error::MissingSigner(from.name().to_owned()))?;
+
+        let unsigned_tx = match gas {
+            GasLimit::Fixed(gas) => cmd.generate(&gas)?,
+            GasLimit::Simulated { price, adjustment } => {
+                cmd.generate_simulated(&price, adjustment)?
+            }
+        };
+
+        let signed_tx = signer.sign(sh, network, from, &unsigned_tx)?;
+
+        let tmp_dir = sh.create_temp_dir()?;
+
+        let signed_tx_path = tmp_dir.path().join("signed_tx.json");
+
+        sh.write_file(&signed_tx_path, signed_tx)?;
+
+        network.cli(sh)?.broadcast_tx(&node_uri, &signed_tx_path)
+    }
+}
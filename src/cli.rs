@@ -1,15 +1,23 @@
-use std::path::Path;
+use std::{
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
 
 use derive_more::{Display, From, FromStr};
 use log::debug;
 use prost::Message;
 use serde::{de::DeserializeOwned, Deserialize};
 use serde_aux::prelude::*;
-use xshell::{Cmd as ShellCmd, Shell};
+use xshell::{cmd, Cmd as ShellCmd, Shell};
 
 use crate::{
-    key::{Key, KeyringBackend, Raw},
-    network::{gas::Gas, ChainId, Network, NodeUri},
+    key::{Derivation, Key, KeyInfo, KeyringBackend, PublicKey, Raw, KEYRING_PASSPHRASE_ENV},
+    metrics::{self, StepKind},
+    network::{
+        gas::{Gas, Price as GasPrice},
+        ChainId, Network, NodeUri,
+    },
     Error,
 };
 
@@ -34,20 +42,239 @@ pub struct BuildTxCmd<'a> {
 
 pub struct ReadyTxCmd<'a> {
     pub(crate) cmd: ShellCmd<'a>,
+    /// Flat fees have been set, so gas prices must not be
+    has_fees: bool,
+    /// The sequence is set explicitly, so re-running the command on a mismatch would only fail again
+    sequence_pinned: bool,
 }
 
 pub struct QueryCmd<'a> {
     cmd: ShellCmd<'a>,
+    retry: RetryPolicy,
 }
 
 #[derive(From, Display, Debug, Clone)]
 pub struct TxId(String);
 
+/// The tx hash responded with for txs only printed in `XtaskMode::DryRun`
+const DRY_RUN_TX_HASH: &str = "DRY-RUN";
+
 impl TxId {
     #[must_use]
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+
+    /// Whether the tx was only printed in `XtaskMode::DryRun` rather than broadcast
+    #[must_use]
+    pub fn is_dry_run(&self) -> bool {
+        self.0 == DRY_RUN_TX_HASH
+    }
+}
+
+/// How state changing chain CLI commands are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum XtaskMode {
+    #[default]
+    Execute,
+    /// Print the commands which would change chain, keyring or genesis state to stderr rather than executing them.
+    /// Queries & simulations are still executed. Broadcasting a tx responds with a placeholder tx which is "included"
+    /// straight away with a default response, e.g. code ID 0, so a deploy continues & prints its whole plan.
+    /// Other commands whose output is needed to continue, e.g. adding a key, respond with `Error::DryRun` after being printed.
+    DryRun,
+}
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Set the mode for all chain CLI commands, e.g. by the `--dry-run` flag of `cli_app`
+pub fn set_mode(mode: XtaskMode) {
+    DRY_RUN.store(mode == XtaskMode::DryRun, Ordering::Relaxed);
+}
+
+#[must_use]
+pub fn mode() -> XtaskMode {
+    if DRY_RUN.load(Ordering::Relaxed) {
+        XtaskMode::DryRun
+    } else {
+        XtaskMode::Execute
+    }
+}
+
+/// How often to poll the node or a log file by default, see `set_poll_interval`
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[allow(clippy::cast_possible_truncation)]
+static POLL_INTERVAL_MS: AtomicU64 = AtomicU64::new(DEFAULT_POLL_INTERVAL.as_millis() as u64);
+
+/// Set how long to sleep between polls when waiting for txs or blocks, & when following node logs.
+/// Fast localnets can poll aggressively, while public testnets should be polled less often.
+pub fn set_poll_interval(interval: Duration) {
+    let millis = u64::try_from(interval.as_millis()).unwrap_or(u64::MAX);
+
+    POLL_INTERVAL_MS.store(millis.max(1), Ordering::Relaxed);
+}
+
+#[must_use]
+pub fn poll_interval() -> Duration {
+    Duration::from_millis(POLL_INTERVAL_MS.load(Ordering::Relaxed))
+}
+
+/// The fewest consecutive BIP-39 words treated as a mnemonic by `redact`
+const MIN_MNEMONIC_WORDS: usize = 12;
+
+/// Replace anything in `text` that looks like a secret with `<redacted>`, i.e. runs of BIP-39 words, armored private keys
+/// & the keyring passphrase
+#[must_use]
+pub fn redact(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+
+    let mut copied = 0;
+
+    let mut run: Vec<(usize, usize)> = vec![];
+
+    // Replace the run of words with `<redacted>` if it is long enough to be a mnemonic
+    let mut end_run = |run: &mut Vec<(usize, usize)>| {
+        if run.len() >= MIN_MNEMONIC_WORDS {
+            redacted.push_str(&text[copied..run[0].0]);
+            redacted.push_str("<redacted>");
+            copied = run[run.len() - 1].1;
+        }
+
+        run.clear();
+    };
+
+    for token in text.split_whitespace().map(Some).chain([None]) {
+        // A trailing `None` ends the final run
+        let Some(token) = token else {
+            end_run(&mut run);
+            break;
+        };
+
+        // The word without surrounding punctuation, e.g. quotes, with its byte range in `text`
+        let word = token.trim_matches(|c: char| !c.is_ascii_alphabetic());
+
+        if bip39::Language::English.find_word(word).is_none() {
+            end_run(&mut run);
+            continue;
+        }
+
+        // Punctuation bounds a run, e.g. a quoted mnemonic followed by `--output`
+        if token.starts_with(|c: char| !c.is_ascii_alphabetic()) {
+            end_run(&mut run);
+        }
+
+        let start = word.as_ptr() as usize - text.as_ptr() as usize;
+
+        run.push((start, start + word.len()));
+
+        if token.ends_with(|c: char| !c.is_ascii_alphabetic()) {
+            end_run(&mut run);
+        }
+    }
+
+    redacted.push_str(&text[copied..]);
+
+    let redacted = redact_armored(&redacted);
+
+    match std::env::var(KEYRING_PASSPHRASE_ENV) {
+        Ok(passphrase) if !passphrase.is_empty() => redacted.replace(&passphrase, "<redacted>"),
+        _ => redacted,
+    }
+}
+
+/// Replace each armored block in `text`, e.g. the output of `keys export`, with `<redacted>`
+fn redact_armored(text: &str) -> String {
+    const BEGIN: &str = "-----BEGIN";
+    const END: &str = "-----END";
+    const DASHES: &str = "-----";
+
+    let mut redacted = String::with_capacity(text.len());
+
+    let mut rest = text;
+
+    while let Some(start) = rest.find(BEGIN) {
+        let end = rest[start..].find(END).and_then(|end| {
+            let footer = start + end + END.len();
+            rest[footer..]
+                .find(DASHES)
+                .map(|close| footer + close + DASHES.len())
+        });
+
+        // An unterminated block is cut short, redact the rest of it
+        let end = end.unwrap_or(rest.len());
+
+        redacted.push_str(&rest[..start]);
+        redacted.push_str("<redacted>");
+
+        rest = &rest[end..];
+    }
+
+    redacted.push_str(rest);
+
+    redacted
+}
+
+pub(crate) trait ShellCmdExt {
+    /// Answer the keyring passphrase prompts of the `file` backend, see `KeyringBackend::passphrase_input`
+    fn with_passphrase(self, backend: KeyringBackend, prompts: usize) -> Self;
+
+    /// Log the command with any secrets redacted rather than letting `xshell` echo it to stderr
+    fn logged(self) -> Self;
+
+    /// In `XtaskMode::DryRun` print the command & respond with `Error::DryRun` rather than executing it,
+    /// for commands whose output is needed to continue
+    fn unless_dry_run(self) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    /// Run the command, or in `XtaskMode::DryRun` only print it
+    fn run_unless_dry_run(self) -> Result<(), Error>;
+
+    /// In `XtaskMode::DryRun` print the tx broadcast command & respond with a placeholder tx ID rather than executing it
+    fn dry_run_broadcast(&self) -> Option<TxId>;
+}
+
+impl ShellCmdExt for ShellCmd<'_> {
+    fn with_passphrase(self, backend: KeyringBackend, prompts: usize) -> Self {
+        match backend.passphrase_input(prompts) {
+            Some(input) => self.stdin(input),
+            None => self,
+        }
+    }
+
+    fn logged(self) -> Self {
+        debug!("$ {}", redact(&self.to_string()));
+        self.quiet()
+    }
+
+    fn unless_dry_run(self) -> Result<Self, Error> {
+        if mode() == XtaskMode::DryRun {
+            let cmd = redact(&self.to_string());
+            eprintln!("{cmd}");
+            return Err(Error::DryRun(cmd));
+        }
+
+        Ok(self)
+    }
+
+    fn run_unless_dry_run(self) -> Result<(), Error> {
+        if mode() == XtaskMode::DryRun {
+            eprintln!("{}", redact(&self.to_string()));
+            return Ok(());
+        }
+
+        self.logged().run().map_err(Error::from)
+    }
+
+    fn dry_run_broadcast(&self) -> Option<TxId> {
+        if mode() != XtaskMode::DryRun {
+            return None;
+        }
+
+        eprintln!("{}", redact(&self.to_string()));
+
+        Some(TxId::from(DRY_RUN_TX_HASH.to_owned()))
+    }
 }
 
 impl<'a> Cmd<'a> {
@@ -69,6 +296,7 @@ impl<'a> Cmd<'a> {
                 "--output",
                 "json",
             ])
+            .with_passphrase(backend, 2)
             .output()
             .map_err(Error::from)
             .and_then(|out| serde_json::from_slice(&out.stdout).map_err(Error::from))?;
@@ -89,6 +317,22 @@ impl<'a> Cmd<'a> {
     /// - There is an issue with running the command.
     /// - JSON deserialisation fails
     pub fn add_key(self, name: &str, backend: KeyringBackend) -> Result<Key, Error> {
+        self.add_key_derived(name, backend, Derivation::Default)
+    }
+
+    /// Add a key derived with `derivation` to be associated with the given `backend`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    /// - JSON deserialisation fails
+    pub fn add_key_derived(
+        self,
+        name: &str,
+        backend: KeyringBackend,
+        derivation: Derivation,
+    ) -> Result<Key, Error> {
         self.0
             .args([
                 "keys",
@@ -99,180 +343,523 @@ impl<'a> Cmd<'a> {
                 "--output",
                 "json",
             ])
+            .args(derivation.args())
+            .with_passphrase(backend, 2)
+            .unless_dry_run()?
             .read()
             .map_err(Error::from)
             .and_then(|out| {
                 serde_json::from_str::<Raw>(&out)
-                    .map(|raw_key| raw_key.with_backend(backend))
+                    .map(|raw_key| raw_key.with_backend(backend).with_derivation(derivation))
                     .map_err(Error::from)
             })
     }
 
-    /// Recover a key with mnemonic to be associated with the given `backend`.
+    /// Add a reference to a key held on a connected Ledger device, to be associated with the given `backend`.
+    /// Txs from the key are signed on the device.
     ///
     /// # Errors
     ///
     /// This function will return an error if:
-    /// - There is an issue with running the command.
+    /// - There is an issue with running the command, e.g. no device is connected
     /// - JSON deserialisation fails
-    pub fn recover_key(
+    pub fn add_ledger_key(
         self,
         name: &str,
-        mnenomic: &str,
         backend: KeyringBackend,
+        derivation: Derivation,
     ) -> Result<Key, Error> {
-        let cmd = self.0.args([
-            "keys",
-            "add",
-            name,
-            "--keyring-backend",
-            backend.as_str(),
-            "--recover",
-            "--output",
-            "json",
-        ]);
-
-        let out = cmd.stdin(mnenomic).output().map_err(Error::from)?;
-
-        if !out.status.success() {
-            let err = String::from_utf8(out.stdout)?;
-
-            return Err(Error::CmdExecute(err));
-        }
-
-        let combined = [out.stdout, out.stderr].concat();
-
-        serde_json::from_slice::<Raw>(&combined)
-            .map(|raw_key| raw_key.with_backend(backend))
-            .map_err(Error::from)
-    }
-
-    /// Initialise the chain state
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if:
-    /// - There is an issue with running the command.
-    pub fn init_chain(self, moniker: &str, chain_id: &ChainId) -> Result<(), Error> {
         self.0
-            .args(["init", moniker, "--chain-id", chain_id.as_str()])
-            .ignore_stdout()
-            .run()
+            .args([
+                "keys",
+                "add",
+                name,
+                "--ledger",
+                "--keyring-backend",
+                backend.as_str(),
+                "--output",
+                "json",
+            ])
+            .args(derivation.args())
+            .with_passphrase(backend, 2)
+            .unless_dry_run()?
+            .read()
             .map_err(Error::from)
+            .and_then(|out| {
+                serde_json::from_str::<Raw>(&out)
+                    .map(|raw_key| raw_key.with_backend(backend).with_derivation(derivation))
+                    .map_err(Error::from)
+            })
     }
 
-    /// Add a genesis account to be given an `amount` of coins.
+    /// Add an offline key with only the public key `pubkey`, to be associated with the given `backend`.
+    /// Txs from the key are signed by the network's remote signer, see `Instance::set_signer`.
     ///
     /// # Errors
     ///
     /// This function will return an error if:
-    /// - There is an issue with running the command.
-    pub fn add_genesis_account(self, key: &Key, coins: &[(u128, &str)]) -> Result<(), Error> {
-        assert!(!coins.is_empty(), "you must specify at least one coin");
+    /// - There is an issue with running the command
+    /// - JSON (de)serialisation fails
+    pub fn add_offline_key(
+        self,
+        name: &str,
+        pubkey: &PublicKey,
+        backend: KeyringBackend,
+    ) -> Result<Key, Error> {
+        let pubkey = serde_json::to_string(pubkey)?;
 
         self.0
             .args([
-                "add-genesis-account",
-                key.name(),
-                coins
-                    .iter()
-                    .map(|(amount, denom)| format!("{amount}{denom},"))
-                    .collect::<String>()
-                    .strip_suffix(',')
-                    .unwrap(),
+                "keys",
+                "add",
+                name,
+                "--pubkey",
+                pubkey.as_str(),
                 "--keyring-backend",
-                key.backend(),
+                backend.as_str(),
+                "--output",
+                "json",
             ])
-            .run()
+            .with_passphrase(backend, 2)
+            .unless_dry_run()?
+            .read()
             .map_err(Error::from)
+            .and_then(|out| {
+                serde_json::from_str::<Raw>(&out)
+                    .map(|raw_key| raw_key.with_backend(backend))
+                    .map_err(Error::from)
+            })
     }
 
-    /// Add a genesis tx to be made.
+    /// Show the address & public key of the key `name` in the given `backend`.
     ///
     /// # Errors
     ///
     /// This function will return an error if:
-    /// - There is an issue with running the command.
-    pub fn gentx(self, key: &Key, amount: u128, denom: &str, chain_id: &str) -> Result<(), Error> {
+    /// - There is an issue with running the command, e.g. the key does not exist
+    /// - JSON deserialisation fails
+    pub fn show_key(self, name: &str, backend: KeyringBackend) -> Result<KeyInfo, Error> {
         self.0
             .args([
-                "gentx",
-                key.name(),
-                &format!("{amount}{denom}"),
-                "--chain-id",
-                chain_id,
+                "keys",
+                "show",
+                name,
                 "--keyring-backend",
-                key.backend(),
+                backend.as_str(),
+                "--output",
+                "json",
             ])
-            .run()
+            .with_passphrase(backend, 2)
+            .read()
             .map_err(Error::from)
+            .and_then(|out| serde_json::from_str(&out).map_err(Error::from))
     }
 
-    /// Collect all the genesis txs
+    /// Export the private key `name` from the given `backend`, armored & encrypted with `passphrase`.
     ///
     /// # Errors
     ///
     /// This function will return an error if:
     /// - There is an issue with running the command.
-    pub fn collect_gentx(self) -> Result<(), Error> {
-        self.0.arg("collect-gentxs").run().map_err(Error::from)
-    }
+    /// - The armored key is missing from the output
+    pub fn export_key(
+        self,
+        name: &str,
+        backend: KeyringBackend,
+        passphrase: &str,
+    ) -> Result<String, Error> {
+        // The export passphrase is asked for before the keyring passphrase
+        let input = format!(
+            "{passphrase}\n{}",
+            backend.passphrase_input(1).unwrap_or_default()
+        );
 
-    /// Validate the genesis file
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if:
-    /// - There is an issue with running the command.
-    pub fn validate_genesis(self) -> Result<(), Error> {
-        self.0.arg("validate-genesis").run().map_err(Error::from)
+        let out = self
+            .0
+            .args([
+                "keys",
+                "export",
+                name,
+                "--keyring-backend",
+                backend.as_str(),
+            ])
+            .stdin(input)
+            .ignore_status()
+            .output()?;
+
+        let stderr = String::from_utf8(out.stderr)?;
+
+        if !out.status.success() {
+            return Err(Error::CmdExecute(redact(&stderr)));
+        }
+
+        // Depending on the SDK version the armored key is written to stdout or stderr
+        let combined = format!("{}{stderr}", String::from_utf8(out.stdout)?);
+
+        let start = combined
+            .find("-----BEGIN")
+            .ok_or(Error::ExpectedArmoredKey)?;
+
+        let end = combined[start..]
+            .rfind("-----")
+            .map(|end| start + end + "-----".len())
+            .ok_or(Error::ExpectedArmoredKey)?;
+
+        Ok(combined[start..end].to_owned())
     }
 
-    /// Build a predictable address
+    /// Export the unencrypted private key of `name` as hex, only for signing in-process, see `key::sign`
     ///
     /// # Errors
     ///
     /// This function will return an error if:
-    /// - There is an issue with running the command.
-    pub fn build_address(
+    /// - There is an issue with running the command
+    /// - The hex encoded private key is missing from the output
+    pub(crate) fn export_key_hex(
         self,
-        code_hash: &str,
-        from: &'a Key,
-        salt: &str,
+        name: &str,
+        backend: KeyringBackend,
     ) -> Result<String, Error> {
-        let hex_salt = hex::encode(salt);
+        // Confirm the unsafe export before the keyring passphrase is asked for
+        let input = format!("y\n{}", backend.passphrase_input(1).unwrap_or_default());
 
         let out = self
             .0
             .args([
-                "query",
-                "wasm",
-                "build-address",
-                code_hash,
-                from.address(),
-                hex_salt.as_str(),
+                "keys",
+                "export",
+                name,
+                "--unarmored-hex",
+                "--unsafe",
+                "--keyring-backend",
+                backend.as_str(),
             ])
-            .read()?;
-
-        let address = out.split_ascii_whitespace().next().unwrap().to_owned();
+            .stdin(input)
+            .ignore_status()
+            .output()?;
 
-        Ok(address)
-    }
+        let stderr = String::from_utf8(out.stderr)?;
 
-    #[must_use]
-    pub fn tx(self, from: &'a Key, chain_id: &'a ChainId, node: &'a NodeUri) -> BuildTxCmd<'a> {
-        BuildTxCmd {
-            from,
-            chain_id,
-            node,
-            cmd: self.0,
+        if !out.status.success() {
+            return Err(Error::CmdExecute(redact(&stderr)));
         }
-    }
+
+        // Depending on the SDK version the key is written to stdout or stderr
+        let combined = format!("{}{stderr}", String::from_utf8(out.stdout)?);
+
+        combined
+            .split_whitespace()
+            .find(|word| word.len() == 64 && word.chars().all(|c| c.is_ascii_hexdigit()))
+            .map(str::to_owned)
+            .ok_or(Error::ExpectedPrivateKey)
+    }
+
+    /// Import the armored private key at `armored_path`, encrypted with `passphrase`, as `name` into the given `backend`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command, e.g. the passphrase is wrong
+    pub fn import_key(
+        self,
+        name: &str,
+        armored_path: &Path,
+        passphrase: &str,
+        backend: KeyringBackend,
+    ) -> Result<(), Error> {
+        // The decryption passphrase is asked for before the keyring passphrase
+        let input = format!(
+            "{passphrase}\n{}",
+            backend.passphrase_input(1).unwrap_or_default()
+        );
+
+        let out = self
+            .0
+            .args(["keys", "import", name])
+            .arg(armored_path)
+            .args(["--keyring-backend", backend.as_str()])
+            .stdin(input)
+            .ignore_status()
+            .unless_dry_run()?
+            .output()?;
+
+        if !out.status.success() {
+            return Err(Error::CmdExecute(redact(&String::from_utf8(out.stderr)?)));
+        }
+
+        Ok(())
+    }
+
+    /// Recover a key with mnemonic to be associated with the given `backend`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    /// - JSON deserialisation fails
+    pub fn recover_key(
+        self,
+        name: &str,
+        mnenomic: &str,
+        backend: KeyringBackend,
+    ) -> Result<Key, Error> {
+        self.recover_key_derived(name, mnenomic, backend, Derivation::Default)
+    }
+
+    /// Recover a key derived with `derivation` from a mnemonic to be associated with the given `backend`.
+    /// With the `file` backend the keyring must already exist, e.g. by listing keys first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    /// - JSON deserialisation fails
+    pub fn recover_key_derived(
+        self,
+        name: &str,
+        mnenomic: &str,
+        backend: KeyringBackend,
+        derivation: Derivation,
+    ) -> Result<Key, Error> {
+        let cmd = self.0.args([
+            "keys",
+            "add",
+            name,
+            "--keyring-backend",
+            backend.as_str(),
+            "--recover",
+            "--output",
+            "json",
+        ]);
+
+        let cmd = cmd.args(derivation.args());
+
+        // The file backend asks for the keyring passphrase before the mnemonic
+        let input = match backend.passphrase_input(1) {
+            Some(passphrase) => format!("{passphrase}{mnenomic}"),
+            None => mnenomic.to_owned(),
+        };
+
+        let out = cmd.stdin(input).unless_dry_run()?.output()?;
+
+        if !out.status.success() {
+            // Never let the mnemonic leak into errors or logs
+            let err = String::from_utf8(out.stdout)?.replace(mnenomic.trim(), "<redacted>");
+
+            return Err(Error::CmdExecute(redact(&err)));
+        }
+
+        let combined = [out.stdout, out.stderr].concat();
+
+        serde_json::from_slice::<Raw>(&combined)
+            .map(|raw_key| raw_key.with_backend(backend).with_derivation(derivation))
+            .map_err(Error::from)
+    }
+
+    /// Initialise the chain state
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    pub fn init_chain(self, moniker: &str, chain_id: &ChainId) -> Result<(), Error> {
+        self.0
+            .args(["init", moniker, "--chain-id", chain_id.as_str()])
+            .ignore_stdout()
+            .run_unless_dry_run()
+    }
+
+    /// Add a genesis account to be given an `amount` of coins.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    pub fn add_genesis_account(self, key: &Key, coins: &[(u128, &str)]) -> Result<(), Error> {
+        assert!(!coins.is_empty(), "you must specify at least one coin");
+
+        self.0
+            .args([
+                "add-genesis-account",
+                key.name(),
+                coins
+                    .iter()
+                    .map(|(amount, denom)| format!("{amount}{denom},"))
+                    .collect::<String>()
+                    .strip_suffix(',')
+                    .unwrap(),
+                "--keyring-backend",
+                key.backend(),
+            ])
+            .run_unless_dry_run()
+    }
+
+    /// Add a genesis tx to be made.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    pub fn gentx(self, key: &Key, amount: u128, denom: &str, chain_id: &str) -> Result<(), Error> {
+        self.0
+            .args([
+                "gentx",
+                key.name(),
+                &format!("{amount}{denom}"),
+                "--chain-id",
+                chain_id,
+                "--keyring-backend",
+                key.backend(),
+            ])
+            .run_unless_dry_run()
+    }
+
+    /// Collect all the genesis txs
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    pub fn collect_gentx(self) -> Result<(), Error> {
+        self.0.arg("collect-gentxs").run_unless_dry_run()
+    }
+
+    /// Validate the genesis file
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    pub fn validate_genesis(self) -> Result<(), Error> {
+        self.0.arg("validate-genesis").run_unless_dry_run()
+    }
+
+    /// Build a predictable address
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue with running the command.
+    pub fn build_address(
+        self,
+        code_hash: &str,
+        from: &'a Key,
+        salt: &str,
+    ) -> Result<String, Error> {
+        let hex_salt = hex::encode(salt);
+
+        let out = self
+            .0
+            .args([
+                "query",
+                "wasm",
+                "build-address",
+                code_hash,
+                from.address(),
+                hex_salt.as_str(),
+            ])
+            .read()?;
+
+        let address = out.split_ascii_whitespace().next().unwrap().to_owned();
+
+        Ok(address)
+    }
+
+    #[must_use]
+    pub fn tx(self, from: &'a Key, chain_id: &'a ChainId, node: &'a NodeUri) -> BuildTxCmd<'a> {
+        BuildTxCmd {
+            from,
+            chain_id,
+            node,
+            cmd: self.0,
+        }
+    }
+
+    /// Broadcast the signed tx in the JSON file at `path`, returning the tx ID for querying
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The tx is rejected
+    /// - JSON Deserialisation fails
+    pub fn broadcast_tx(self, node: &NodeUri, path: &Path) -> Result<TxId, Error> {
+        let cmd = self
+            .0
+            .args(["tx", "broadcast"])
+            .arg(path)
+            .args(["--node", node.as_str(), "--output", "json"])
+            .ignore_status()
+            .logged();
+
+        if let Some(tx_id) = cmd.dry_run_broadcast() {
+            return Ok(tx_id);
+        }
+
+        parse_tx_id(&cmd.output()?)
+    }
+
+    /// Encode the signed tx in the JSON file at `path` to its protobuf bytes
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - Base64 decoding the output fails
+    pub fn encode_tx(self, path: &Path) -> Result<Vec<u8>, Error> {
+        use base64::Engine;
+
+        let cmd = self.0.args(["tx", "encode"]).arg(path).ignore_status();
+
+        let encoded = read_checked(&cmd)?;
+
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(Error::from)
+    }
 
     #[must_use]
     pub fn query(self, node: &NodeUri) -> QueryCmd<'a> {
         let cmd = self.0.args(["--node", node.as_str()]);
-        QueryCmd { cmd }
+        QueryCmd {
+            cmd,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Run a chain subcommand the crate does not wrap, e.g. `&["debug", "addr", address]`, with the home flags injected,
+    /// responding with its stdout. In `XtaskMode::DryRun` it is only printed, as it may change state.
+    /// See `QueryCmd::raw` to also target a node & `BuildTxCmd::raw` for txs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command fails
+    /// - Parsing UTF-8 from its output fails
+    /// - The mode is `XtaskMode::DryRun`
+    pub fn raw(self, args: &[&str]) -> Result<String, Error> {
+        let cmd = self
+            .0
+            .args(args)
+            .ignore_status()
+            .logged()
+            .unless_dry_run()?;
+
+        read_checked(&cmd)
+    }
+
+    /// Run a chain subcommand the crate does not wrap with `--output json`, deserialising its stdout, see `Cmd::raw`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Running the command fails, see `Cmd::raw`
+    /// - JSON deserialisation fails
+    pub fn raw_json<T: DeserializeOwned>(self, args: &[&str]) -> Result<T, Error> {
+        let out = Cmd(self.0.args(args)).raw(&["--output", "json"])?;
+
+        serde_json::from_str(&out).map_err(Error::from)
     }
 }
 
@@ -290,11 +877,44 @@ macro_rules! ready {
             "--yes",
         ]);
 
-        ReadyTxCmd { cmd }
+        let cmd = cmd.with_passphrase($build_tx_cmd.from.keyring_backend(), 2);
+
+        // Ledger devices only support signing amino JSON
+        let cmd = if $build_tx_cmd.from.is_ledger() {
+            cmd.args(["--ledger", "--sign-mode", "amino-json"])
+        } else {
+            cmd
+        };
+
+        ReadyTxCmd {
+            cmd,
+            has_fees: false,
+            sequence_pinned: false,
+        }
     }};
 }
 
 impl<'a> BuildTxCmd<'a> {
+    /// A tx subcommand the crate does not wrap, e.g. `&["tx", "feerefunder", ...]`, with the sender, keyring, chain & node flags injected
+    #[must_use]
+    pub fn raw(self, args: &[&str]) -> ReadyTxCmd<'a> {
+        let cmd = self.cmd.args(args);
+        ready!(cmd, self)
+    }
+
+    /// Sign the unsigned tx JSON at `unsigned_tx_path` with the sender's key, responding with the signed tx JSON
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue running the command, e.g. the account does not exist yet.
+    pub fn sign(self, unsigned_tx_path: &Path) -> Result<String, Error> {
+        let cmd = self.cmd.args(["tx", "sign"]).arg(unsigned_tx_path);
+
+        let ReadyTxCmd { cmd, .. } = ready!(cmd, self);
+
+        read_checked(&cmd.args(["--output", "json"]).ignore_status().logged())
+    }
+
     pub fn wasm_store<P>(self, path: P) -> ReadyTxCmd<'a>
     where
         P: AsRef<Path>,
@@ -339,17 +959,133 @@ impl<'a> BuildTxCmd<'a> {
     }
 
     #[must_use]
-    pub fn ibc_transfer(
-        self,
-        channel: &str,
-        recipient: &str,
-        tx_amount: u128,
-        tx_denom: &str,
-    ) -> ReadyTxCmd<'a> {
+    pub fn wasm_migrate(self, contract: &Contract, code_id: CodeId, msg: &str) -> ReadyTxCmd<'a> {
         let cmd = self.cmd.args([
             "tx",
-            "ibc-transfer",
-            "transfer",
+            "wasm",
+            "migrate",
+            contract.as_str(),
+            code_id.u64().to_string().as_str(),
+            msg,
+        ]);
+        ready!(cmd, self)
+    }
+
+    #[must_use]
+    pub fn wasm_set_admin(self, contract: &Contract, new_admin: &str) -> ReadyTxCmd<'a> {
+        let cmd = self.cmd.args([
+            "tx",
+            "wasm",
+            "set-contract-admin",
+            contract.as_str(),
+            new_admin,
+        ]);
+        ready!(cmd, self)
+    }
+
+    #[must_use]
+    pub fn wasm_clear_admin(self, contract: &Contract) -> ReadyTxCmd<'a> {
+        let cmd = self
+            .cmd
+            .args(["tx", "wasm", "clear-contract-admin", contract.as_str()]);
+        ready!(cmd, self)
+    }
+
+    /// Submit a governance proposal to store the WASM bytecode at `path`
+    pub fn wasm_store_proposal<P>(
+        self,
+        path: P,
+        title: &str,
+        summary: &str,
+        deposit: &Coin,
+    ) -> ReadyTxCmd<'a>
+    where
+        P: AsRef<Path>,
+    {
+        let cmd = self
+            .cmd
+            .args(["tx", "wasm", "submit-proposal", "wasm-store"])
+            .arg(path.as_ref())
+            .args([
+                "--title",
+                title,
+                "--summary",
+                summary,
+                "--deposit",
+                deposit.to_string().as_str(),
+            ]);
+
+        ready!(cmd, self)
+    }
+
+    /// Submit a governance proposal to upgrade the chain software to the upgrade handler `name` at `height`
+    #[must_use]
+    pub fn software_upgrade_proposal(
+        self,
+        name: &str,
+        height: u64,
+        title: &str,
+        summary: &str,
+        deposit: &Coin,
+    ) -> ReadyTxCmd<'a> {
+        let cmd = self
+            .cmd
+            .args(["tx", "upgrade", "software-upgrade", name])
+            .args([
+                "--upgrade-height",
+                height.to_string().as_str(),
+                "--title",
+                title,
+                "--summary",
+                summary,
+                "--deposit",
+                deposit.to_string().as_str(),
+                "--no-validate",
+            ]);
+
+        ready!(cmd, self)
+    }
+
+    #[must_use]
+    pub fn gov_vote(self, proposal_id: u64, option: VoteOption) -> ReadyTxCmd<'a> {
+        let cmd = self.cmd.args([
+            "tx",
+            "gov",
+            "vote",
+            proposal_id.to_string().as_str(),
+            option.as_str(),
+        ]);
+
+        ready!(cmd, self)
+    }
+
+    #[must_use]
+    pub fn bank_send(self, to: &str, coins: &[Coin]) -> ReadyTxCmd<'a> {
+        let coins = coins
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let cmd = self
+            .cmd
+            .args(["tx", "bank", "send", self.from.name(), to, coins.as_str()]);
+
+        ready!(cmd, self)
+    }
+
+    #[must_use]
+    pub fn ibc_transfer(
+        self,
+        channel: &str,
+        recipient: &str,
+        tx_amount: u128,
+        tx_denom: &str,
+    ) -> ReadyTxCmd<'a> {
+        let cmd = self.cmd.args([
+            "tx",
+            "ibc-transfer",
+            "transfer",
             "transfer",
             channel,
             recipient,
@@ -363,12 +1099,14 @@ impl<'a> BuildTxCmd<'a> {
 #[derive(Debug, Deserialize)]
 pub struct Attribute {
     pub key: String,
+    #[serde(default)]
     pub value: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Event {
     pub r#type: String,
+    #[serde(default)]
     pub attributes: Vec<Attribute>,
 }
 
@@ -377,7 +1115,7 @@ pub struct Log {
     pub events: Vec<Event>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Hex(String);
 
 #[derive(Clone, PartialEq, Message)]
@@ -395,12 +1133,415 @@ impl ProtobufAny {
     }
 }
 
+/// Message result data as emitted by SDK <= 0.45
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgData {
+    #[prost(string, tag = "1")]
+    pub msg_type: String,
+    #[prost(bytes, tag = "2")]
+    pub data: Vec<u8>,
+}
+
 #[derive(Clone, PartialEq, Message)]
 pub struct TxMsgData {
+    /// Populated by SDK <= 0.45
+    #[prost(message, repeated, tag = "1")]
+    pub data: Vec<MsgData>,
+    /// Populated by SDK >= 0.46
     #[prost(message, repeated, tag = "2")]
     pub msg_responses: Vec<ProtobufAny>,
 }
 
+impl TxMsgData {
+    /// The bytes of the first message response, regardless of which SDK version produced them
+    #[must_use]
+    pub fn first_response(&self) -> Option<&[u8]> {
+        self.msg_responses
+            .first()
+            .map(ProtobufAny::as_slice)
+            .or_else(|| self.data.first().map(|msg_data| msg_data.data.as_slice()))
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtobufCoin {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+    #[prost(string, tag = "2")]
+    pub amount: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TxRaw {
+    #[prost(bytes, tag = "1")]
+    pub body_bytes: Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub auth_info_bytes: Vec<u8>,
+    #[prost(bytes, repeated, tag = "3")]
+    pub signatures: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TxBody {
+    #[prost(message, repeated, tag = "1")]
+    pub messages: Vec<ProtobufAny>,
+    #[prost(string, tag = "2")]
+    pub memo: String,
+    #[prost(uint64, tag = "3")]
+    pub timeout_height: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgSend {
+    #[prost(string, tag = "1")]
+    pub from_address: String,
+    #[prost(string, tag = "2")]
+    pub to_address: String,
+    #[prost(message, repeated, tag = "3")]
+    pub amount: Vec<ProtobufCoin>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgStoreCode {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(bytes, tag = "2")]
+    pub wasm_byte_code: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgInstantiateContract {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub admin: String,
+    #[prost(uint64, tag = "3")]
+    pub code_id: u64,
+    #[prost(string, tag = "4")]
+    pub label: String,
+    #[prost(bytes, tag = "5")]
+    pub msg: Vec<u8>,
+    #[prost(message, repeated, tag = "6")]
+    pub funds: Vec<ProtobufCoin>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgExecuteContract {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub contract: String,
+    #[prost(bytes, tag = "3")]
+    pub msg: Vec<u8>,
+    #[prost(message, repeated, tag = "5")]
+    pub funds: Vec<ProtobufCoin>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SimulateRequest {
+    #[prost(bytes, tag = "2")]
+    tx_bytes: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SimulateGasInfo {
+    #[prost(uint64, tag = "1")]
+    gas_wanted: u64,
+    #[prost(uint64, tag = "2")]
+    gas_used: u64,
+}
+
+/// Keys & values are bytes before `CometBFT` 0.37 & strings after, both have the same wire format
+#[derive(Clone, PartialEq, Message)]
+struct AbciEventAttribute {
+    #[prost(bytes, tag = "1")]
+    key: Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct AbciEvent {
+    #[prost(string, tag = "1")]
+    r#type: String,
+    #[prost(message, repeated, tag = "2")]
+    attributes: Vec<AbciEventAttribute>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SimulateResult {
+    #[prost(message, repeated, tag = "3")]
+    events: Vec<AbciEvent>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SimulateResponse {
+    #[prost(message, optional, tag = "1")]
+    gas_info: Option<SimulateGasInfo>,
+    #[prost(message, optional, tag = "2")]
+    result: Option<SimulateResult>,
+}
+
+impl From<AbciEvent> for Event {
+    fn from(event: AbciEvent) -> Self {
+        Event {
+            r#type: event.r#type,
+            attributes: event
+                .attributes
+                .into_iter()
+                .map(|attribute| Attribute {
+                    key: String::from_utf8_lossy(&attribute.key).into_owned(),
+                    value: String::from_utf8_lossy(&attribute.value).into_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgMigrateContract {
+    #[prost(string, tag = "1")]
+    pub sender: String,
+    #[prost(string, tag = "2")]
+    pub contract: String,
+    #[prost(uint64, tag = "3")]
+    pub code_id: u64,
+    #[prost(bytes, tag = "4")]
+    pub msg: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxMsg {
+    Send(MsgSend),
+    StoreCode(MsgStoreCode),
+    InstantiateContract(MsgInstantiateContract),
+    ExecuteContract(MsgExecuteContract),
+    MigrateContract(MsgMigrateContract),
+    Unknown(ProtobufAny),
+}
+
+impl TxMsg {
+    /// Decode a known message type from the `any`, falling back to `TxMsg::Unknown`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if protobuf decoding of a known message type fails.
+    pub fn decode_any(any: ProtobufAny) -> Result<Self, Error> {
+        let bytes = any.as_slice();
+
+        let msg = match any.type_url.as_str() {
+            "/cosmos.bank.v1beta1.MsgSend" => Self::Send(MsgSend::decode(bytes)?),
+            "/cosmwasm.wasm.v1.MsgStoreCode" => Self::StoreCode(MsgStoreCode::decode(bytes)?),
+            "/cosmwasm.wasm.v1.MsgInstantiateContract" => {
+                Self::InstantiateContract(MsgInstantiateContract::decode(bytes)?)
+            }
+            "/cosmwasm.wasm.v1.MsgExecuteContract" => {
+                Self::ExecuteContract(MsgExecuteContract::decode(bytes)?)
+            }
+            "/cosmwasm.wasm.v1.MsgMigrateContract" => {
+                Self::MigrateContract(MsgMigrateContract::decode(bytes)?)
+            }
+            _ => Self::Unknown(any),
+        };
+
+        Ok(msg)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedTx {
+    pub messages: Vec<TxMsg>,
+    pub memo: String,
+    pub timeout_height: u64,
+    pub signatures: Vec<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct AbciQuery {
+    result: AbciQueryResult,
+}
+
+#[derive(Deserialize)]
+struct AbciQueryResult {
+    response: AbciQueryResponse,
+}
+
+#[derive(Deserialize)]
+struct AbciQueryResponse {
+    #[serde(default)]
+    code: u32,
+    #[serde(default)]
+    codespace: String,
+    #[serde(default)]
+    log: String,
+    #[serde(default)]
+    value: Option<String>,
+}
+
+/// The HTTP URL of the `CometBFT` RPC at `node`, which the CLI addresses as `tcp://`
+fn rpc_url(node: &NodeUri) -> String {
+    node.as_str().replacen("tcp://", "http://", 1)
+}
+
+/// Simulate the signed `tx_bytes` with the tx service of the `node`, queried through `abci_query` with `curl`.
+/// Responds with the gas used & the events emitted.
+///
+/// The data is posted hex encoded, so a store tx for a large WASM file may exceed the node's RPC `max_body_bytes`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Running `curl` fails, e.g. the node is unreachable
+/// - The simulation fails, e.g. the contract returns an error
+/// - Decoding the response fails
+pub(crate) fn simulate_tx(
+    sh: &Shell,
+    node: &NodeUri,
+    tx_bytes: &[u8],
+) -> Result<(u128, Vec<Event>), Error> {
+    use base64::Engine;
+
+    let request = SimulateRequest {
+        tx_bytes: tx_bytes.to_vec(),
+    };
+
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "abci_query",
+        "params": {
+            "path": "/cosmos.tx.v1beta1.Service/Simulate",
+            "data": hex::encode(request.encode_to_vec()),
+            "prove": false,
+        },
+    });
+
+    let tmp_dir = sh.create_temp_dir()?;
+
+    let body_path = tmp_dir.path().join("simulate.json");
+
+    sh.write_file(&body_path, body.to_string())?;
+
+    let cmd = cmd!(
+        sh,
+        "curl --silent --show-error --fail --request POST --header"
+    )
+    .arg("Content-Type: application/json")
+    .arg("--data-binary")
+    .arg(format!("@{}", body_path.display()))
+    .arg(rpc_url(node))
+    .ignore_status();
+
+    let AbciQuery {
+        result: AbciQueryResult { response },
+    } = serde_json::from_str(&read_checked(&cmd)?)?;
+
+    if response.code > 0 {
+        return Err(tx_error(Metadata {
+            txhash: String::new(),
+            height: 0,
+            codespace: response.codespace,
+            code: response.code,
+            gas_wanted: 0,
+            gas_used: 0,
+            raw_log: response.log,
+            logs: vec![],
+            events: vec![],
+        }));
+    }
+
+    let value =
+        base64::engine::general_purpose::STANDARD.decode(response.value.unwrap_or_default())?;
+
+    let SimulateResponse { gas_info, result } = SimulateResponse::decode(value.as_slice())?;
+
+    let gas_used = gas_info.map_or(0, |gas_info| u128::from(gas_info.gas_used));
+
+    let events = result
+        .map(|result| result.events.into_iter().map(Event::from).collect())
+        .unwrap_or_default();
+
+    Ok((gas_used, events))
+}
+
+/// The encoding of a raw Cosmos tx, see `decode_tx_as`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxEncoding {
+    Hex,
+    /// What `<chaind> tx encode` produces from the output of `--generate-only`
+    Base64,
+}
+
+/// Decode a raw Cosmos tx encoded as either hex or base64 into its typed messages.
+///
+/// An even length string of hex digits may also be valid base64, so it is only taken as hex when that decodes to a tx
+/// with at least one message, otherwise it is decoded as base64. Use `decode_tx_as` when the encoding is known.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Base64 decoding fails
+/// - Protobuf decoding fails
+pub fn decode_tx(base64_or_hex: &str) -> Result<DecodedTx, Error> {
+    let encoded = base64_or_hex.trim();
+
+    let looks_hex = encoded.len() % 2 == 0 && encoded.chars().all(|c| c.is_ascii_hexdigit());
+
+    let hex_tx = if looks_hex {
+        decode_tx_as(encoded, TxEncoding::Hex).ok()
+    } else {
+        None
+    };
+
+    if let Some(tx) = hex_tx.filter(|tx| !tx.messages.is_empty()) {
+        return Ok(tx);
+    }
+
+    decode_tx_as(encoded, TxEncoding::Base64)
+}
+
+/// Decode a raw Cosmos tx in the given `encoding` into its typed messages.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Hex or base64 decoding fails
+/// - Protobuf decoding fails
+pub fn decode_tx_as(encoded: &str, encoding: TxEncoding) -> Result<DecodedTx, Error> {
+    use base64::Engine;
+
+    let encoded = encoded.trim();
+
+    let bytes = match encoding {
+        TxEncoding::Hex => hex::decode(encoded)?,
+        TxEncoding::Base64 => base64::engine::general_purpose::STANDARD.decode(encoded)?,
+    };
+
+    let TxRaw {
+        body_bytes,
+        signatures,
+        ..
+    } = TxRaw::decode(bytes.as_slice())?;
+
+    let TxBody {
+        messages,
+        memo,
+        timeout_height,
+    } = TxBody::decode(body_bytes.as_slice())?;
+
+    let messages = messages
+        .into_iter()
+        .map(TxMsg::decode_any)
+        .collect::<Result<_, _>>()?;
+
+    Ok(DecodedTx {
+        messages,
+        memo,
+        timeout_height,
+        signatures,
+    })
+}
+
 #[derive(Display, Clone, Copy, Message)]
 pub struct CodeId {
     #[prost(uint64, tag = "1")]
@@ -431,6 +1572,28 @@ impl Contract {
         Self { address }
     }
 
+    /// Validate that `address` is a bech32 address with the `expected_prefix`, e.g. `neutron`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Bech32 decoding fails, e.g. the checksum is invalid
+    /// - The address prefix does not match `expected_prefix`
+    pub fn checked(address: &str, expected_prefix: &str) -> Result<Self, Error> {
+        let (prefix, _, _) = bech32::decode(address)?;
+
+        if prefix != expected_prefix {
+            return Err(Error::UnexpectedAddressPrefix {
+                expected: expected_prefix.to_owned(),
+                found: prefix,
+            });
+        }
+
+        Ok(Self {
+            address: address.to_owned(),
+        })
+    }
+
     #[must_use]
     pub fn as_str(&self) -> &str {
         self.address.as_str()
@@ -467,18 +1630,67 @@ impl CwExecuteResponse {
     }
 }
 
+/// `MsgMigrateContractResponse` has the same shape as `MsgExecuteContractResponse`
+pub type CwMigrateResponse = CwExecuteResponse;
+
 #[derive(Debug, Deserialize)]
 pub struct Metadata {
     pub txhash: String,
+    #[serde(default, deserialize_with = "deserialize_number_from_string")]
+    pub height: u64,
+    #[serde(default)]
+    pub codespace: String,
+    #[serde(default)]
     pub code: u32,
+    #[serde(default, deserialize_with = "deserialize_number_from_string")]
+    pub gas_wanted: u128,
+    #[serde(default, deserialize_with = "deserialize_number_from_string")]
+    pub gas_used: u128,
+    #[serde(default)]
     pub raw_log: String,
+    /// Populated by SDK <= 0.47, empty or missing after
+    #[serde(default)]
     pub logs: Vec<Log>,
+    /// Populated by SDK >= 0.47, missing before
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+impl Metadata {
+    /// The events emitted by the tx, taken from the logs when present and the top-level events otherwise
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        let use_top_level = self.logs.is_empty();
+
+        self.logs
+            .iter()
+            .flat_map(|l| l.events.as_slice())
+            .chain(self.events.iter().filter(move |_| use_top_level))
+    }
+}
+
+/// A tx found by a search, with its body for inspecting the msgs it contained
+#[derive(Debug, Deserialize)]
+pub struct SearchedTx {
+    #[serde(flatten)]
+    pub meta: Metadata,
+    /// The signed tx as rendered by the CLI, the msgs are at `/body/messages`
+    #[serde(default)]
+    pub tx: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TxSearchPage {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub total_count: u64,
+    #[serde(default)]
+    pub txs: Vec<SearchedTx>,
 }
 
 #[derive(Deserialize)]
 pub struct TxData<D> {
     #[serde(flatten)]
     pub meta: Metadata,
+    #[serde(default)]
     pub data: D,
 }
 
@@ -486,11 +1698,7 @@ pub type RawTxData = TxData<Hex>;
 
 impl<Data> TxData<Data> {
     pub fn attributes(&self) -> impl Iterator<Item = &Attribute> {
-        self.meta
-            .logs
-            .iter()
-            .flat_map(|l| l.events.as_slice())
-            .flat_map(|ev| ev.attributes.as_slice())
+        self.meta.events().flat_map(|ev| ev.attributes.as_slice())
     }
 
     pub fn into_data(self) -> Data {
@@ -498,7 +1706,65 @@ impl<Data> TxData<Data> {
     }
 }
 
+/// The outcome of a tx included in a block, along with its decoded response
+#[derive(Debug)]
+pub struct TxResult<Response> {
+    pub tx_hash: TxId,
+    pub height: u64,
+    pub gas_wanted: u128,
+    pub gas_used: u128,
+    pub events: Vec<Event>,
+    pub data: Response,
+}
+
+impl<Response> From<TxData<Response>> for TxResult<Response> {
+    fn from(tx_data: TxData<Response>) -> Self {
+        let TxData { meta, data } = tx_data;
+
+        let events = if meta.logs.is_empty() {
+            meta.events
+        } else {
+            meta.logs.into_iter().flat_map(|log| log.events).collect()
+        };
+
+        Self {
+            tx_hash: TxId::from(meta.txhash),
+            height: meta.height,
+            gas_wanted: meta.gas_wanted,
+            gas_used: meta.gas_used,
+            events,
+            data,
+        }
+    }
+}
+
 impl RawTxData {
+    /// The placeholder for a tx only printed in `XtaskMode::DryRun`, its single msg response decodes to the default
+    fn dry_run(tx_id: &TxId) -> Self {
+        let data = TxMsgData {
+            data: vec![],
+            msg_responses: vec![ProtobufAny {
+                type_url: String::new(),
+                value: vec![],
+            }],
+        };
+
+        Self {
+            meta: Metadata {
+                txhash: tx_id.to_string(),
+                height: 0,
+                codespace: String::new(),
+                code: 0,
+                gas_wanted: 0,
+                gas_used: 0,
+                raw_log: String::new(),
+                logs: vec![],
+                events: vec![],
+            },
+            data: Hex(hex::encode(data.encode_to_vec())),
+        }
+    }
+
     /// Decode the raw data hex string into the `Msg` type
     ///
     /// # Errors
@@ -516,10 +1782,8 @@ impl RawTxData {
         let bytes = hex::decode(data.0)?;
 
         TxMsgData::decode(bytes.as_slice())?
-            .msg_responses
-            .first()
+            .first_response()
             .ok_or(Error::ExpectedAtLeastOneMsgResponse)
-            .map(ProtobufAny::as_slice)
             .and_then(|data| Msg::decode(data).map_err(Error::from))
             .map(|data| TxData { meta, data })
     }
@@ -529,7 +1793,7 @@ impl<'a> ReadyTxCmd<'a> {
     #[must_use]
     pub fn amount(self, amount: u128, denom: &str) -> Self {
         let cmd = self.cmd.args(["--amount", &format!("{amount}{denom}")]);
-        Self { cmd }
+        Self { cmd, ..self }
     }
 
     #[must_use]
@@ -542,266 +1806,1598 @@ impl<'a> ReadyTxCmd<'a> {
                     coins.push_str(&amount.to_string());
                     coins.push_str(denom.as_ref());
 
-                    if idx < amounts.len() - 1 {
-                        coins.push(',');
-                    }
+                    if idx < amounts.len() - 1 {
+                        coins.push(',');
+                    }
+
+                    coins
+                });
+
+        let cmd = self.cmd.args(["--amount", &coins]);
+
+        Self { cmd, ..self }
+    }
+
+    #[must_use]
+    pub fn funds(self, funds: &[Coin]) -> Self {
+        let coins = funds
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let cmd = self.cmd.args(["--amount", &coins]);
+
+        Self { cmd, ..self }
+    }
+
+    /// Sign the tx with an explicit `account_number` & `sequence` rather than querying them,
+    /// allowing several txs from the same account to be broadcast before any are included in a block
+    #[must_use]
+    pub fn sequence(self, account_number: u64, sequence: u64) -> Self {
+        let cmd = self.cmd.args([
+            "--account-number",
+            account_number.to_string().as_str(),
+            "--sequence",
+            sequence.to_string().as_str(),
+        ]);
+
+        Self {
+            cmd,
+            sequence_pinned: true,
+            ..self
+        }
+    }
+
+    /// Pay a flat fee of `amount` of `denom` instead of paying per unit of gas, the gas price is ignored when executing
+    #[must_use]
+    pub fn fees(self, amount: u128, denom: &str) -> Self {
+        let cmd = self
+            .cmd
+            .args(["--fees", format!("{amount}{denom}").as_str()]);
+
+        Self {
+            cmd,
+            has_fees: true,
+            ..self
+        }
+    }
+
+    /// Set the gas `price` unless flat fees have been set, the CLI rejects both together
+    fn gas_prices(self, price: &GasPrice) -> Self {
+        if self.has_fees {
+            return self;
+        }
+
+        let cmd = self.cmd.args(["--gas-prices", price.to_string().as_str()]);
+
+        Self { cmd, ..self }
+    }
+
+    /// Execute the `TxCmd`, returning the tx ID for querying
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - JSON Deserialisation fails
+    pub fn execute(self, gas: &Gas) -> Result<TxId, Error> {
+        let cmd = self.cmd.args(["--gas", gas.units.to_string().as_str()]);
+
+        Self { cmd, ..self }.gas_prices(&gas.price).broadcast()
+    }
+
+    /// Execute the `TxCmd` with a gas limit estimated by simulating the tx & scaling by `adjustment`, returning the tx ID for querying
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - Simulating the tx fails
+    /// - JSON Deserialisation fails
+    pub fn execute_simulated(self, price: &GasPrice, adjustment: f64) -> Result<TxId, Error> {
+        let cmd = self.cmd.args([
+            "--gas",
+            "auto",
+            "--gas-adjustment",
+            adjustment.to_string().as_str(),
+        ]);
+
+        Self { cmd, ..self }.gas_prices(price).broadcast()
+    }
+
+    /// Simulate the `TxCmd` without broadcasting it, returning the estimated gas units scaled by `adjustment`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - Simulating the tx fails
+    /// - The gas estimate is missing from the output
+    pub fn simulate(self, price: &GasPrice, adjustment: f64) -> Result<u128, Error> {
+        let cmd = self.cmd.args([
+            "--gas",
+            "auto",
+            "--gas-adjustment",
+            adjustment.to_string().as_str(),
+            "--dry-run",
+        ]);
+
+        let cmd = Self { cmd, ..self }
+            .gas_prices(price)
+            .cmd
+            .ignore_status()
+            .logged();
+
+        let out = cmd.output()?;
+
+        let stderr = String::from_utf8(out.stderr)?;
+
+        if !out.status.success() {
+            return Err(Error::CmdExecute(redact(&stderr)));
+        }
+
+        let stdout = String::from_utf8(out.stdout)?;
+
+        // The estimate is written as `gas estimate: <units>`, to stderr or stdout depending on the SDK version
+        stderr
+            .lines()
+            .chain(stdout.lines())
+            .find_map(|line| line.trim().strip_prefix("gas estimate:"))
+            .ok_or(Error::ExpectedGasEstimate)?
+            .trim()
+            .parse()
+            .map_err(Error::from)
+    }
+
+    /// Broadcast the tx, re-running the command on an account sequence mismatch so the sequence is re-queried,
+    /// unless it was pinned with `sequence`
+    fn broadcast(self) -> Result<TxId, Error> {
+        let retry = if self.sequence_pinned {
+            RetryPolicy::none()
+        } else {
+            RetryPolicy::sequence_mismatch()
+        };
+
+        let cmd = self.cmd.args(["--output", "json"]).ignore_status().logged();
+
+        if let Some(tx_id) = cmd.dry_run_broadcast() {
+            return Ok(tx_id);
+        }
+
+        retry.run(|| parse_tx_id(&cmd.output()?))
+    }
+
+    /// Generate the unsigned tx JSON with a gas limit of `gas`, for signing elsewhere
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is an issue running the command.
+    pub fn generate(self, gas: &Gas) -> Result<String, Error> {
+        let cmd = self.cmd.args(["--gas", gas.units.to_string().as_str()]);
+
+        Self { cmd, ..self }.gas_prices(&gas.price).generate_only()
+    }
+
+    /// Generate the unsigned tx JSON with a gas limit estimated by simulating the tx & scaling by `adjustment`, for signing elsewhere
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - Simulating the tx fails
+    pub fn generate_simulated(self, price: &GasPrice, adjustment: f64) -> Result<String, Error> {
+        let cmd = self.cmd.args([
+            "--gas",
+            "auto",
+            "--gas-adjustment",
+            adjustment.to_string().as_str(),
+        ]);
+
+        Self { cmd, ..self }.gas_prices(price).generate_only()
+    }
+
+    fn generate_only(self) -> Result<String, Error> {
+        let cmd = self
+            .cmd
+            .args(["--generate-only", "--output", "json"])
+            .ignore_status()
+            .logged();
+
+        let out = cmd.output()?;
+
+        if !out.status.success() {
+            return Err(Error::CmdExecute(redact(&String::from_utf8(out.stderr)?)));
+        }
+
+        Ok(String::from_utf8(out.stdout)?)
+    }
+}
+
+/// Parse the tx ID from the output of a broadcast command, failing if the tx was rejected
+fn parse_tx_id(out: &std::process::Output) -> Result<TxId, Error> {
+    if !out.status.success() {
+        let stderr = String::from_utf8(out.stderr.clone())?;
+
+        return Err(Error::CmdExecute(redact(&stderr)));
+    }
+
+    let tx_exec: RawTxData = serde_json::from_slice(&out.stdout)?;
+
+    if tx_exec.meta.code > 0 {
+        return Err(tx_error(tx_exec.meta));
+    }
+
+    Ok(TxId::from(tx_exec.meta.txhash))
+}
+
+/// The failure mode of a tx rejected by the chain, see `Error::chain_error`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    OutOfGas,
+    InsufficientFunds,
+    Unauthorized,
+    CodeNotFound,
+    AccountSequenceMismatch,
+    /// Any other error registered by a module, e.g. a contract error is `wasm` code 5
+    Custom {
+        codespace: String,
+        code: u32,
+    },
+    /// A failure reported without a code which is none of the above
+    Unknown,
+}
+
+impl ChainError {
+    /// Classify the error registered as `code` in `codespace`, falling back to the `log` for codes which are not specific enough
+    #[must_use]
+    pub fn from_code(codespace: &str, code: u32, log: &str) -> Self {
+        match (codespace, code) {
+            ("sdk", 4) => Self::Unauthorized,
+            ("sdk", 5) => Self::InsufficientFunds,
+            ("sdk", 11) => Self::OutOfGas,
+            ("sdk", 32) => Self::AccountSequenceMismatch,
+            ("wasm", 28) => Self::CodeNotFound,
+            _ => match Self::from_log(log) {
+                Self::Unknown => Self::Custom {
+                    codespace: codespace.to_owned(),
+                    code,
+                },
+                known => known,
+            },
+        }
+    }
+
+    /// Classify a failure reported without a code, e.g. by a rejected simulation, from its `log`
+    #[must_use]
+    pub fn from_log(log: &str) -> Self {
+        let log = log.to_lowercase();
+
+        if log.contains("out of gas") {
+            Self::OutOfGas
+        } else if log.contains("insufficient funds") || log.contains("insufficient fee") {
+            Self::InsufficientFunds
+        } else if log.contains(SEQUENCE_MISMATCH) {
+            Self::AccountSequenceMismatch
+        } else if log.contains("no such code") || log.contains("code not found") {
+            Self::CodeNotFound
+        } else if log.contains("unauthorized") {
+            Self::Unauthorized
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+/// The details of a tx rejected by the chain, carried by `Error::TxExecute`.
+/// Failures reported before the tx reached a block, e.g. by `CheckTx`, have no hash, height or gas usage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxFailure {
+    pub kind: ChainError,
+    pub tx_hash: Option<String>,
+    pub height: Option<u64>,
+    pub codespace: String,
+    pub code: u32,
+    pub gas_wanted: Option<u128>,
+    pub gas_used: Option<u128>,
+    pub raw_log: String,
+}
+
+impl std::fmt::Display for TxFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(tx_hash) = &self.tx_hash {
+            write!(f, "tx {tx_hash}")?;
+
+            if let Some(height) = self.height {
+                write!(f, " at height {height}")?;
+            }
+
+            write!(f, " failed")?;
+
+            if !self.codespace.is_empty() {
+                write!(f, " with {} code {}", self.codespace, self.code)?;
+            }
+
+            if let (Some(used), Some(wanted)) = (self.gas_used, self.gas_wanted) {
+                write!(f, " (gas used {used} of {wanted})")?;
+            }
+
+            write!(f, ": ")?;
+        }
+
+        f.write_str(&self.raw_log)
+    }
+}
+
+/// The error for a tx the chain rejected, as described by its `meta`
+fn tx_error(meta: Metadata) -> Error {
+    let included = meta.height > 0;
+
+    Error::TxExecute(Box::new(TxFailure {
+        kind: ChainError::from_code(&meta.codespace, meta.code, &meta.raw_log),
+        tx_hash: (!meta.txhash.is_empty()).then_some(meta.txhash),
+        height: included.then_some(meta.height),
+        codespace: meta.codespace,
+        code: meta.code,
+        gas_wanted: included.then_some(meta.gas_wanted),
+        gas_used: included.then_some(meta.gas_used),
+        raw_log: meta.raw_log,
+    }))
+}
+
+/// The error for a chain command which failed with the `log` on stderr
+fn log_error(log: &str) -> Error {
+    let raw_log = redact(log);
+
+    Error::TxExecute(Box::new(TxFailure {
+        kind: ChainError::from_log(&raw_log),
+        tx_hash: None,
+        height: None,
+        codespace: String::new(),
+        code: 0,
+        gas_wanted: None,
+        gas_used: None,
+        raw_log,
+    }))
+}
+
+#[derive(Debug, Display, Deserialize, FromStr, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BlockHeight(u64);
+
+impl BlockHeight {
+    #[must_use]
+    pub const fn u64(self) -> u64 {
+        self.0
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct SyncInfo {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub latest_block_height: BlockHeight,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Status {
+    #[serde(rename = "SyncInfo")]
+    pub sync_info: SyncInfo,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StoredCode {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub code_id: u64,
+    pub creator: String,
+    pub data_hash: String,
+}
+
+/// The estimated gas usage of a simulated tx
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasReport {
+    /// The gas units consumed by the simulation
+    pub estimate: u128,
+    /// The gas limit that would be used when sending, i.e. `estimate` scaled by the gas adjustment
+    pub limit: u128,
+}
+
+/// The outcome of a simulated tx, see `Tx::simulate`
+#[derive(Debug)]
+pub struct Simulation {
+    pub gas: GasReport,
+    /// The events the tx would emit if it were sent now
+    pub events: Vec<Event>,
+}
+
+/// The signing state of an account, used to sign txs with explicit sequence numbers
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Account {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub account_number: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub sequence: u64,
+}
+
+/// A Neutron interchain query, as registered by its owner contract
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct RegisteredQuery {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub id: u64,
+    pub owner: String,
+    pub query_type: String,
+    pub connection_id: String,
+    /// The local height of the last result submitted by the relayer, zero until the first result
+    #[serde(default, deserialize_with = "deserialize_number_from_string")]
+    pub last_submitted_result_local_height: u64,
+}
+
+/// The handshake state of an IBC channel
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelState {
+    #[serde(rename = "STATE_UNINITIALIZED_UNSPECIFIED")]
+    Uninitialized,
+    #[serde(rename = "STATE_INIT")]
+    Init,
+    #[serde(rename = "STATE_TRYOPEN")]
+    TryOpen,
+    #[serde(rename = "STATE_OPEN")]
+    Open,
+    #[serde(rename = "STATE_CLOSED")]
+    Closed,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct ChannelEnd {
+    pub port_id: String,
+    pub channel_id: String,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct IbcChannel {
+    pub port_id: String,
+    pub channel_id: String,
+    pub state: ChannelState,
+    pub counterparty: ChannelEnd,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CodeInfo {
+    pub creator: String,
+    pub data_hash: String,
+}
+
+#[derive(Debug, Display, Deserialize, Clone, PartialEq, Eq)]
+#[display(fmt = "{amount}{denom}")]
+pub struct Coin {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub amount: u128,
+    pub denom: String,
+}
+
+impl Coin {
+    pub fn new(amount: u128, denom: impl Into<String>) -> Self {
+        Self {
+            amount,
+            denom: denom.into(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BondStatus {
+    #[serde(rename = "BOND_STATUS_UNSPECIFIED")]
+    Unspecified,
+    #[serde(rename = "BOND_STATUS_UNBONDED")]
+    Unbonded,
+    #[serde(rename = "BOND_STATUS_UNBONDING")]
+    Unbonding,
+    #[serde(rename = "BOND_STATUS_BONDED")]
+    Bonded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteOption {
+    Yes,
+    No,
+    Abstain,
+    NoWithVeto,
+}
+
+impl VoteOption {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VoteOption::Yes => "yes",
+            VoteOption::No => "no",
+            VoteOption::Abstain => "abstain",
+            VoteOption::NoWithVeto => "no_with_veto",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    #[serde(rename = "PROPOSAL_STATUS_UNSPECIFIED")]
+    Unspecified,
+    #[serde(rename = "PROPOSAL_STATUS_DEPOSIT_PERIOD")]
+    DepositPeriod,
+    #[serde(rename = "PROPOSAL_STATUS_VOTING_PERIOD")]
+    VotingPeriod,
+    #[serde(rename = "PROPOSAL_STATUS_PASSED")]
+    Passed,
+    #[serde(rename = "PROPOSAL_STATUS_REJECTED")]
+    Rejected,
+    #[serde(rename = "PROPOSAL_STATUS_FAILED")]
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Proposal {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub id: u64,
+    pub status: ProposalStatus,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ValidatorDescription {
+    pub moniker: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Validator {
+    pub operator_address: String,
+    #[serde(default)]
+    pub jailed: bool,
+    pub status: BondStatus,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub tokens: u128,
+    pub delegator_shares: String,
+    pub description: ValidatorDescription,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Delegation {
+    pub delegator_address: String,
+    pub validator_address: String,
+    pub shares: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DelegationResponse {
+    pub delegation: Delegation,
+    pub balance: Coin,
+}
+
+/// Error classes which are considered transient by default, matched case-insensitively against stderr
+pub const DEFAULT_RETRY_ON: &[&str] = &[
+    "connection refused",
+    "connection reset",
+    "post failed",
+    "timed out",
+    "EOF",
+    "broken pipe",
+    "bad gateway",
+    "service unavailable",
+    "gateway timeout",
+    "too many requests",
+];
+
+/// How long to wait for a broadcast tx to be included in a block before giving up
+pub const DEFAULT_TX_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Policy for waiting on a broadcast tx to be included in a block, see `wait_for_tx_with_policy`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitPolicy {
+    poll_interval: Duration,
+    max_wait: Duration,
+    max_not_found: Option<u32>,
+}
+
+impl Default for WaitPolicy {
+    fn default() -> Self {
+        Self {
+            poll_interval: poll_interval(),
+            max_wait: DEFAULT_TX_TIMEOUT,
+            max_not_found: None,
+        }
+    }
+}
+
+impl WaitPolicy {
+    /// How long to sleep between queries for the tx, defaults to the crate wide `poll_interval`
+    #[must_use]
+    pub fn poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            ..self
+        }
+    }
+
+    /// Give up with `Error::TxTimeout` if the tx is not found within `max_wait`
+    #[must_use]
+    pub fn max_wait(self, max_wait: Duration) -> Self {
+        Self { max_wait, ..self }
+    }
+
+    /// Give up with `Error::TxNotFound` after `max_not_found` queries which did not find the tx, regardless of `max_wait`
+    #[must_use]
+    pub fn max_not_found(self, max_not_found: u32) -> Self {
+        Self {
+            max_not_found: Some(max_not_found.max(1)),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn max_wait_duration(&self) -> Duration {
+        self.max_wait
+    }
+}
+
+/// The error class returned when a tx is signed with a stale account sequence
+pub const SEQUENCE_MISMATCH: &str = "account sequence mismatch";
+
+/// Policy for retrying commands which fail with a transient error
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    retry_on: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+            retry_on: DEFAULT_RETRY_ON.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy which never retries
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the `initial` backoff, doubled after each failed attempt up to `max`
+    #[must_use]
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Add an error class to retry on
+    #[must_use]
+    pub fn retry_on(mut self, class: &str) -> Self {
+        self.retry_on.push(class.to_owned());
+        self
+    }
+
+    /// A policy which only retries when the account sequence used to sign a tx is stale,
+    /// waiting long enough for the pending tx to be included in a block
+    #[must_use]
+    pub fn sequence_mismatch() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(8),
+            retry_on: vec![SEQUENCE_MISMATCH.to_owned()],
+        }
+    }
+
+    fn is_retryable(&self, err: &Error) -> bool {
+        let msg = match err {
+            Error::CmdExecute(msg) => msg,
+            // A tx included in a block failed for good, however its log reads
+            Error::TxExecute(failure) if failure.height.is_none() => &failure.raw_log,
+            _ => return false,
+        };
+
+        let msg = msg.to_lowercase();
+
+        self.retry_on
+            .iter()
+            .any(|class| msg.contains(&class.to_lowercase()))
+    }
+
+    fn backoff_for(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+
+    /// Run `f` until it succeeds, fails with a non-retryable error, or the attempts are exhausted
+    ///
+    /// # Errors
+    ///
+    /// This function will return the last error returned by `f`.
+    pub fn run<T, F>(&self, mut f: F) -> Result<T, Error>
+    where
+        F: FnMut() -> Result<T, Error>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            match f() {
+                Err(err) if attempt + 1 < self.max_attempts && self.is_retryable(&err) => {
+                    let backoff = self.backoff_for(attempt);
+
+                    debug!("transient failure, retrying in {backoff:?}: {err}");
+
+                    std::thread::sleep(backoff);
+
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Join `key=value` tx search conditions as expected by `query txs --events`
+fn events_query(events: &[(&str, &str)]) -> String {
+    events
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Run the `cmd`, which must ignore its status, responding with its stdout or its stderr as the error if it fails
+fn read_checked(cmd: &ShellCmd) -> Result<String, Error> {
+    let out = cmd.output()?;
+
+    if !out.status.success() {
+        let stderr = String::from_utf8(out.stderr)?;
+
+        return Err(Error::CmdExecute(redact(&stderr)));
+    }
+
+    String::from_utf8(out.stdout).map_err(Error::from)
+}
+
+/// Read the stdout of `cmd`, retrying transient failures according to the `retry` policy
+fn read_with_retry(cmd: &ShellCmd, retry: &RetryPolicy) -> Result<String, Error> {
+    retry.run(|| read_checked(cmd))
+}
+
+impl<'a> QueryCmd<'a> {
+    /// Use the given `retry` policy for transient failures
+    #[must_use]
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Query the tx ID returning `None` if it cannot yet be found. Transient RPC failures, e.g. a 502 from a public gateway,
+    /// are retried according to the retry policy while a tx rejected by the chain is not.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The node is still unreachable after exhausting the retry policy
+    /// - The response contains an error
+    /// - Parsing UTF-8 fails from stderr fails
+    /// - JSON deserialisation fails
+    pub fn tx(self, tx_id: &TxId) -> Result<Option<RawTxData>, Error> {
+        let cmd = self
+            .cmd
+            .args(["query", "tx", tx_id.as_str(), "--output", "json"])
+            .ignore_status();
+
+        let Some(stdout) = self.retry.run(|| {
+            let output = cmd.output()?;
+
+            if output.status.success() {
+                return Ok(Some(output.stdout));
+            }
+
+            let stderr = String::from_utf8(output.stderr)?;
+
+            if stderr.contains("not found") {
+                return Ok(None);
+            }
+
+            Err(log_error(&stderr))
+        })?
+        else {
+            return Ok(None);
+        };
+
+        let tx_data: RawTxData = serde_json::from_slice(&stdout)?;
+
+        if tx_data.meta.code > 0 {
+            return Err(tx_error(tx_data.meta));
+        }
+
+        Ok(Some(tx_data))
+    }
+
+    /// Query the node status returning `None` if it cannot yet be found.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The node is still unreachable after exhausting the retry policy
+    /// - The response contains an error
+    /// - Parsing UTF-8 fails from stderr fails
+    /// - JSON deserialisation fails
+    pub fn status(self) -> Result<Option<Status>, Error> {
+        let cmd = self.cmd.arg("status").ignore_status();
+
+        self.retry.run(|| {
+            let out = cmd.output()?;
+
+            if !out.status.success() {
+                let stderr = String::from_utf8(out.stderr)?;
+
+                // The node is not up yet rather than unreachable
+                if stderr.contains("connection refused") {
+                    return Ok(None);
+                }
+
+                return Err(log_error(&stderr));
+            }
+
+            let combined = [out.stdout, out.stderr].concat();
+
+            serde_json::from_slice(&combined)
+                .map(Some)
+                .map_err(Error::from)
+        })
+    }
+
+    /// Query the `contract` with the query `msg`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    pub fn wasm_smart(self, contract: &Contract, msg: &str) -> Result<String, Error> {
+        let cmd = self
+            .cmd
+            .args([
+                "query",
+                "wasm",
+                "contract-state",
+                "smart",
+                contract.as_str(),
+                msg,
+                "--output",
+                "json",
+            ])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+    }
+
+    /// Run a query subcommand the crate does not wrap with the node flag injected, e.g. `&["query", "feeburner", "params"]`,
+    /// responding with its stdout
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    pub fn raw(self, args: &[&str]) -> Result<String, Error> {
+        let cmd = self.cmd.args(args).ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+    }
+
+    /// Run a query subcommand the crate does not wrap with `--output json`, deserialising its stdout, see `QueryCmd::raw`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Running the query fails, see `QueryCmd::raw`
+    /// - JSON deserialisation fails
+    pub fn raw_json<T: DeserializeOwned>(self, args: &[&str]) -> Result<T, Error> {
+        let retry = self.retry;
+
+        let out = QueryCmd {
+            cmd: self.cmd.args(args),
+            retry,
+        }
+        .raw(&["--output", "json"])?;
+
+        serde_json::from_str(&out).map_err(Error::from)
+    }
+
+    /// Query the raw value stored under the base64 encoded `key` in the `contract`'s storage
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    pub fn wasm_raw(self, contract: &Contract, key: &str) -> Result<String, Error> {
+        let cmd = self
+            .cmd
+            .args([
+                "query",
+                "wasm",
+                "contract-state",
+                "raw",
+                contract.as_str(),
+                key,
+                "--b64",
+                "--output",
+                "json",
+            ])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+    }
+
+    /// Query the code info for the stored `code_id`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    pub fn code_info(self, code_id: CodeId) -> Result<CodeInfo, Error> {
+        let cmd = self
+            .cmd
+            .args([
+                "query",
+                "wasm",
+                "code-info",
+                code_id.to_string().as_str(),
+                "--output",
+                "json",
+            ])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str(&json).map_err(Error::from))
+    }
+
+    /// Download the WASM bytecode of the stored `code_id` to `out_path`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    pub fn download_code(self, code_id: CodeId, out_path: &Path) -> Result<(), Error> {
+        let cmd = self
+            .cmd
+            .args(["query", "wasm", "code", code_id.to_string().as_str()])
+            .arg(out_path)
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry).map(|_| ())
+    }
+
+    /// Query a `page` (starting from 1) of up to `limit` stored codes, in ascending code id order
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn list_code(self, page: u64, limit: u64) -> Result<Vec<StoredCode>, Error> {
+        #[derive(Deserialize)]
+        struct CodeInfos {
+            #[serde(default)]
+            code_infos: Vec<StoredCode>,
+        }
+
+        let cmd = self
+            .cmd
+            .args([
+                "query",
+                "wasm",
+                "list-code",
+                "--page",
+                page.to_string().as_str(),
+                "--limit",
+                limit.to_string().as_str(),
+                "--output",
+                "json",
+            ])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<CodeInfos>(&json).map_err(Error::from))
+            .map(|res| res.code_infos)
+    }
+
+    /// Query the balance of the `account` for the `denom`
+    ///
+    /// # Errors
+    ///
+    /// this function will return an error if:
+    /// - there is an issue running the command
+    /// - the command still fails after exhausting the retry policy
+    pub fn balance(self, account: &str, denom: &str) -> Result<u128, Error> {
+        #[derive(Deserialize)]
+        struct RawCoin {
+            amount: String,
+            denom: String,
+        }
+
+        #[derive(Deserialize)]
+        struct Balances {
+            balances: Vec<RawCoin>,
+        }
+
+        let cmd = self
+            .cmd
+            .args(["query", "bank", "balances", account, "--output", "json"])
+            .ignore_status();
+
+        let balances: Balances = read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str(&json).map_err(Error::from))?;
+
+        let balance = balances
+            .balances
+            .into_iter()
+            .find_map(|rc| rc.denom.eq(denom).then(|| rc.amount.parse::<u128>()))
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(balance)
+    }
+
+    /// Search for the txs which emitted events matching all the `events` conditions, e.g. `("message.action", "/cosmos.bank.v1beta1.MsgSend")`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn txs(self, events: &[(&str, &str)]) -> Result<Vec<RawTxData>, Error> {
+        #[derive(Deserialize)]
+        struct SearchTxsResult {
+            #[serde(default)]
+            txs: Vec<RawTxData>,
+        }
+
+        let cmd = self
+            .cmd
+            .args(["query", "txs", "--events", &events_query(events)])
+            .args(["--output", "json"])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<SearchTxsResult>(&json).map_err(Error::from))
+            .map(|res| res.txs)
+    }
+
+    /// Search for txs like `txs`, responding with the 1-based `page` of `limit` txs, oldest first, along with the total number of matches
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn txs_page(
+        self,
+        events: &[(&str, &str)],
+        page: u64,
+        limit: u64,
+    ) -> Result<TxSearchPage, Error> {
+        let cmd = self
+            .cmd
+            .args(["query", "txs", "--events", &events_query(events)])
+            .args(["--page", page.to_string().as_str()])
+            .args(["--limit", limit.to_string().as_str()])
+            .args(["--output", "json"])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str(&json).map_err(Error::from))
+    }
+
+    /// Query the address of the Neutron interchain account `interchain_account_id` of the `owner` contract on `connection_id`,
+    /// returning `None` if it is not registered yet
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn interchain_account(
+        self,
+        owner: &str,
+        connection_id: &str,
+        interchain_account_id: &str,
+    ) -> Result<Option<String>, Error> {
+        #[derive(Deserialize)]
+        struct InterchainAccount {
+            interchain_account_address: String,
+        }
+
+        let cmd = self
+            .cmd
+            .args([
+                "query",
+                "interchaintxs",
+                "interchain-account",
+                owner,
+                connection_id,
+                interchain_account_id,
+                "--output",
+                "json",
+            ])
+            .ignore_status();
+
+        let Some(stdout) = self.retry.run(|| {
+            let output = cmd.output()?;
+
+            if output.status.success() {
+                return Ok(Some(output.stdout));
+            }
 
-                    coins
-                });
+            let stderr = String::from_utf8(output.stderr)?;
 
-        let cmd = self.cmd.args(["--amount", &coins]);
+            if stderr.contains("no interchain account") || stderr.contains("not found") {
+                return Ok(None);
+            }
+
+            Err(Error::CmdExecute(redact(&stderr)))
+        })?
+        else {
+            return Ok(None);
+        };
 
-        Self { cmd }
+        let account: InterchainAccount = serde_json::from_slice(&stdout)?;
+
+        Ok(Some(account.interchain_account_address))
     }
 
-    /// Execute the `TxCmd`, returning the tx ID for querying
+    /// Query the Neutron interchain query with the given `query_id`
     ///
     /// # Errors
     ///
     /// This function will return an error if:
     /// - There is an issue running the command
-    /// - JSON Deserialisation fails
-    pub fn execute(self, gas: &Gas) -> Result<TxId, Error> {
-        let cmd = self.cmd.args([
-            "--gas",
-            gas.units.to_string().as_str(),
-            "--gas-prices",
-            gas.price.to_string().as_str(),
-            "--output",
-            "json",
-        ]);
-
-        debug!("{cmd}");
-
-        let tx_exec_str = cmd.read()?;
-
-        let tx_exec: RawTxData = serde_json::from_str(&tx_exec_str)?;
-
-        if tx_exec.meta.code > 0 {
-            return Err(Error::TxExecute(tx_exec.meta.raw_log));
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn registered_query(self, query_id: u64) -> Result<RegisteredQuery, Error> {
+        #[derive(Deserialize)]
+        struct Response {
+            registered_query: RegisteredQuery,
         }
 
-        Ok(TxId::from(tx_exec.meta.txhash))
-    }
-}
-
-#[derive(Debug, Display, Deserialize, FromStr, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct BlockHeight(u64);
-
-#[derive(Debug, Deserialize, Clone, Copy)]
-pub struct SyncInfo {
-    #[serde(deserialize_with = "deserialize_number_from_string")]
-    pub latest_block_height: BlockHeight,
-}
-
-#[derive(Debug, Deserialize, Clone, Copy)]
-pub struct Status {
-    #[serde(rename = "SyncInfo")]
-    pub sync_info: SyncInfo,
-}
+        let cmd = self
+            .cmd
+            .args([
+                "query",
+                "interchainqueries",
+                "registered-query",
+                query_id.to_string().as_str(),
+                "--output",
+                "json",
+            ])
+            .ignore_status();
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct CodeInfo {
-    pub creator: String,
-    pub data_hash: String,
-}
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<Response>(&json).map_err(Error::from))
+            .map(|res| res.registered_query)
+    }
 
-impl<'a> QueryCmd<'a> {
-    /// Query the tx ID returning `None` if it cannot yet be found.
+    /// Query the trace of the IBC voucher denom with the given `hash`, e.g. `transfer/channel-0/uatom`
     ///
     /// # Errors
     ///
     /// This function will return an error if:
     /// - There is an issue running the command
-    /// - The response contains an error
-    /// - Parsing UTF-8 fails from stderr fails
+    /// - The command still fails after exhausting the retry policy
     /// - JSON deserialisation fails
-    pub fn tx(self, tx_id: &TxId) -> Result<Option<RawTxData>, Error> {
-        let output = self
-            .cmd
-            .args(["query", "tx", tx_id.as_str(), "--output", "json"])
-            .ignore_status()
-            .output()?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8(output.stderr)?;
-
-            if stderr.contains("not found") {
-                return Ok(None);
-            }
+    pub fn denom_trace(self, hash: &str) -> Result<String, Error> {
+        #[derive(Deserialize)]
+        struct DenomTrace {
+            path: String,
+            base_denom: String,
+        }
 
-            return Err(Error::TxExecute(stderr));
+        #[derive(Deserialize)]
+        struct Response {
+            denom_trace: DenomTrace,
         }
 
-        let tx_data: RawTxData = serde_json::from_slice(&output.stdout)?;
+        let cmd = self
+            .cmd
+            .args([
+                "query",
+                "ibc-transfer",
+                "denom-trace",
+                hash,
+                "--output",
+                "json",
+            ])
+            .ignore_status();
 
-        if tx_data.meta.code > 0 {
-            return Err(Error::TxExecute(tx_data.meta.raw_log));
-        }
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<Response>(&json).map_err(Error::from))
+            .map(|res| {
+                let DenomTrace { path, base_denom } = res.denom_trace;
 
-        Ok(Some(tx_data))
+                if path.is_empty() {
+                    base_denom
+                } else {
+                    format!("{path}/{base_denom}")
+                }
+            })
     }
 
-    /// Query the node status returning `None` if it cannot yet be found.
+    /// Query the IBC channels of the chain, in every state
     ///
     /// # Errors
     ///
     /// This function will return an error if:
     /// - There is an issue running the command
-    /// - The response contains an error
-    /// - Parsing UTF-8 fails from stderr fails
-    /// - JSON deserialisation fails
-    pub fn status(self) -> Result<Option<Status>, Error> {
-        let out = self.cmd.arg("status").ignore_status().output()?;
-
-        if !out.status.success() {
-            let stderr = String::from_utf8(out.stderr)?;
-
-            if stderr.contains("connection refused") {
-                return Ok(None);
-            }
-
-            return Err(Error::TxExecute(stderr));
+    /// - The command still fails after exhausting the retry policy
+    pub fn ibc_channels(self) -> Result<Vec<IbcChannel>, Error> {
+        #[derive(Deserialize)]
+        struct Channels {
+            channels: Vec<IbcChannel>,
         }
 
-        let combined = [out.stdout, out.stderr].concat();
+        let cmd = self
+            .cmd
+            .args(["query", "ibc", "channel", "channels", "--output", "json"])
+            .ignore_status();
 
-        serde_json::from_slice(&combined)
-            .map(Some)
-            .map_err(Error::from)
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<Channels>(&json).map_err(Error::from))
+            .map(|res| res.channels)
     }
 
-    /// Query the `contract` with the query `msg`
+    /// Query the chain ID of the counterparty of the channel `channel_id` on `port`, as tracked by its light client
     ///
     /// # Errors
     ///
     /// This function will return an error if:
     /// - There is an issue running the command
-    pub fn wasm_smart(self, contract: &Contract, msg: &str) -> Result<String, Error> {
-        self.cmd
+    /// - The command still fails after exhausting the retry policy
+    pub fn ibc_channel_chain_id(self, port: &str, channel_id: &str) -> Result<ChainId, Error> {
+        #[derive(Deserialize)]
+        struct ClientState {
+            chain_id: String,
+        }
+
+        #[derive(Deserialize)]
+        struct IdentifiedClientState {
+            client_state: ClientState,
+        }
+
+        #[derive(Deserialize)]
+        struct ChannelClientState {
+            identified_client_state: IdentifiedClientState,
+        }
+
+        let cmd = self
+            .cmd
             .args([
                 "query",
-                "wasm",
-                "contract-state",
-                "smart",
-                contract.as_str(),
-                msg,
+                "ibc",
+                "channel",
+                "client-state",
+                port,
+                channel_id,
                 "--output",
                 "json",
             ])
-            .read()
-            .map_err(Error::from)
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<ChannelClientState>(&json).map_err(Error::from))
+            .map(|res| ChainId::from(res.identified_client_state.client_state.chain_id))
     }
 
-    /// Query the code info for the stored `code_id`
+    /// Query the account number & current sequence of the `address`
     ///
     /// # Errors
     ///
     /// This function will return an error if:
     /// - There is an issue running the command
-    pub fn code_info(self, code_id: CodeId) -> Result<CodeInfo, Error> {
-        self.cmd
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn account(self, address: &str) -> Result<Account, Error> {
+        // Newer SDK versions wrap the account in an `account` field
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawAccount {
+            Wrapped { account: Account },
+            Bare(Account),
+        }
+
+        let cmd = self
+            .cmd
+            .args(["query", "auth", "account", address, "--output", "json"])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<RawAccount>(&json).map_err(Error::from))
+            .map(|raw| match raw {
+                RawAccount::Wrapped { account } | RawAccount::Bare(account) => account,
+            })
+    }
+
+    /// Query the governance proposal with the given `proposal_id`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn gov_proposal(self, proposal_id: u64) -> Result<Proposal, Error> {
+        // Newer SDK versions wrap the proposal in a `proposal` field
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawProposal {
+            Wrapped { proposal: Proposal },
+            Bare(Proposal),
+        }
+
+        let cmd = self
+            .cmd
             .args([
                 "query",
-                "wasm",
-                "code-info",
-                code_id.to_string().as_str(),
+                "gov",
+                "proposal",
+                proposal_id.to_string().as_str(),
                 "--output",
                 "json",
             ])
-            .read()
-            .map_err(Error::from)
-            .and_then(|json| serde_json::from_str(&json).map_err(Error::from))
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<RawProposal>(&json).map_err(Error::from))
+            .map(|raw| match raw {
+                RawProposal::Wrapped { proposal } | RawProposal::Bare(proposal) => proposal,
+            })
     }
 
-    /// Query the balance of the `account` for the `denom`
+    /// Query the validator set
     ///
     /// # Errors
     ///
-    /// this function will return an error if:
-    /// - there is an issue running the command
-    pub fn balance(self, account: &str, denom: &str) -> Result<u128, Error> {
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn validators(self) -> Result<Vec<Validator>, Error> {
         #[derive(Deserialize)]
-        struct RawCoin {
-            amount: String,
-            denom: String,
+        struct Validators {
+            validators: Vec<Validator>,
         }
 
+        let cmd = self
+            .cmd
+            .args(["query", "staking", "validators", "--output", "json"])
+            .ignore_status();
+
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<Validators>(&json).map_err(Error::from))
+            .map(|res| res.validators)
+    }
+
+    /// Query the delegations made by the `delegator`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - There is an issue running the command
+    /// - The command still fails after exhausting the retry policy
+    /// - JSON deserialisation fails
+    pub fn delegations(self, delegator: &str) -> Result<Vec<DelegationResponse>, Error> {
         #[derive(Deserialize)]
-        struct Balances {
-            balances: Vec<RawCoin>,
+        struct Delegations {
+            delegation_responses: Vec<DelegationResponse>,
         }
 
-        let balances: Balances = self
+        let cmd = self
             .cmd
-            .args(["query", "bank", "balances", account, "--output", "json"])
-            .read()
-            .map_err(Error::from)
-            .and_then(|json| serde_json::from_str(&json).map_err(Error::from))?;
-
-        let balance = balances
-            .balances
-            .into_iter()
-            .find_map(|rc| rc.denom.eq(denom).then(|| rc.amount.parse::<u128>()))
-            .transpose()?
-            .unwrap_or_default();
+            .args([
+                "query",
+                "staking",
+                "delegations",
+                delegator,
+                "--output",
+                "json",
+            ])
+            .ignore_status();
 
-        Ok(balance)
+        read_with_retry(&cmd, &self.retry)
+            .and_then(|json| serde_json::from_str::<Delegations>(&json).map_err(Error::from))
+            .map(|res| res.delegation_responses)
     }
 }
 
-/// Keep querying the tx ID until it is found
+/// Keep querying the tx ID until it is found, giving up after `DEFAULT_TX_TIMEOUT`
 ///
 /// # Errors
 ///
-/// This function will return an error if `QueryCmd::tx` returns an error.
+/// This function will return an error if:
+/// - `QueryCmd::tx` returns an error
+/// - The tx is not found within `DEFAULT_TX_TIMEOUT`
 pub fn wait_for_tx(sh: &Shell, network: &dyn Network, tx_id: &TxId) -> Result<RawTxData, Error> {
+    wait_for_tx_with_timeout(sh, network, tx_id, DEFAULT_TX_TIMEOUT)
+}
+
+/// Keep querying the network for a tx until it is found or `timeout` elapses
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Querying the tx fails
+/// - The tx is not found within `timeout`, e.g. it was dropped from the mempool
+pub fn wait_for_tx_with_timeout(
+    sh: &Shell,
+    network: &dyn Network,
+    tx_id: &TxId,
+    timeout: Duration,
+) -> Result<RawTxData, Error> {
+    wait_for_tx_with_policy(sh, network, tx_id, &WaitPolicy::default().max_wait(timeout))
+}
+
+/// Keep querying the network for a tx according to the wait `policy`, a tx only printed in `XtaskMode::DryRun`
+/// responds with its placeholder straight away
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Querying the tx fails
+/// - The tx is not found within the policy's `max_wait`
+/// - The tx is not found after the policy's `max_not_found` queries
+pub fn wait_for_tx_with_policy(
+    sh: &Shell,
+    network: &dyn Network,
+    tx_id: &TxId,
+    policy: &WaitPolicy,
+) -> Result<RawTxData, Error> {
+    if tx_id.is_dry_run() {
+        return Ok(RawTxData::dry_run(tx_id));
+    }
+
     let node_uri = network.node_uri(sh)?;
 
+    let deadline = Instant::now() + policy.max_wait;
+
+    let mut not_found = 0;
+
     loop {
         if let Some(tx_data) = network.cli(sh)?.query(&node_uri).tx(tx_id)? {
             return Ok(tx_data);
         }
 
-        std::thread::sleep(std::time::Duration::from_millis(250));
+        not_found += 1;
+
+        if policy.max_not_found.is_some_and(|max| not_found >= max) {
+            return Err(Error::TxNotFound(tx_id.clone(), not_found));
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::TxTimeout(tx_id.clone()));
+        }
+
+        std::thread::sleep(policy.poll_interval);
     }
 }
 
-pub(crate) fn wait_for_blocks_fn<'a, F>(cli_fn: F, node_uri: &NodeUri) -> Result<BlockHeight, Error>
+/// Keep querying the node status until it is available, responding with the latest block height
+fn latest_height_fn<'a, F>(cli_fn: &F, node_uri: &NodeUri) -> Result<BlockHeight, Error>
 where
     F: Fn() -> Result<Cmd<'a>, Error>,
 {
     loop {
         if let Some(status) = cli_fn()?.query(node_uri).status()? {
-            let start_height = status.sync_info.latest_block_height;
-
-            loop {
-                std::thread::sleep(std::time::Duration::from_millis(500));
+            return Ok(status.sync_info.latest_block_height);
+        }
 
-                let status = cli_fn()?
-                    .query(node_uri)
-                    .status()?
-                    .expect("status already found once");
+        std::thread::sleep(poll_interval());
+    }
+}
 
-                let current_height = status.sync_info.latest_block_height;
+pub(crate) fn wait_for_height_fn<'a, F>(
+    cli_fn: &F,
+    node_uri: &NodeUri,
+    height: u64,
+) -> Result<BlockHeight, Error>
+where
+    F: Fn() -> Result<Cmd<'a>, Error>,
+{
+    loop {
+        let current_height = latest_height_fn(cli_fn, node_uri)?;
 
-                if current_height > start_height {
-                    return Ok(status.sync_info.latest_block_height);
-                }
-            }
+        if current_height.u64() >= height {
+            return Ok(current_height);
         }
 
-        std::thread::sleep(std::time::Duration::from_millis(250));
+        std::thread::sleep(poll_interval());
     }
 }
 
+pub(crate) fn wait_for_blocks_fn<'a, F>(cli_fn: F, node_uri: &NodeUri) -> Result<BlockHeight, Error>
+where
+    F: Fn() -> Result<Cmd<'a>, Error>,
+{
+    let start_height = latest_height_fn(&cli_fn, node_uri)?;
+
+    wait_for_height_fn(&cli_fn, node_uri, start_height.u64() + 1)
+}
+
 /// Keep querying the network for block height until it is found
 ///
 /// # Errors
 ///
 /// This function will return an error if `QueryCmd::tx` returns an error.
-#[allow(clippy::missing_panics_doc)]
 pub fn wait_for_blocks(sh: &Shell, network: &dyn Network) -> Result<BlockHeight, Error> {
     let node_uri = network.node_uri(sh)?;
-    wait_for_blocks_fn(|| network.cli(sh), &node_uri)
+    metrics::timed(StepKind::Wait, "blocks", || {
+        wait_for_blocks_fn(|| network.cli(sh), &node_uri)
+    })
+}
+
+/// Query the latest block height, waiting for the node to respond
+///
+/// # Errors
+///
+/// This function will return an error if querying the node status fails.
+pub fn latest_height(sh: &Shell, network: &dyn Network) -> Result<BlockHeight, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    latest_height_fn(&|| network.cli(sh), &node_uri)
+}
+
+/// Wait for `n` new blocks after the latest one, responding with the height reached
+///
+/// # Errors
+///
+/// This function will return an error if querying the node status fails.
+pub fn wait_for_n_blocks(sh: &Shell, network: &dyn Network, n: u64) -> Result<BlockHeight, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    let cli_fn = || network.cli(sh);
+
+    metrics::timed(StepKind::Wait, &format!("{n} blocks"), || {
+        let start_height = latest_height_fn(&cli_fn, &node_uri)?;
+
+        wait_for_height_fn(&cli_fn, &node_uri, start_height.u64() + n)
+    })
+}
+
+/// Wait until the chain reaches `height`, responding immediately if it already has
+///
+/// # Errors
+///
+/// This function will return an error if querying the node status fails.
+pub fn wait_for_height(
+    sh: &Shell,
+    network: &dyn Network,
+    height: u64,
+) -> Result<BlockHeight, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    metrics::timed(StepKind::Wait, &format!("height {height}"), || {
+        wait_for_height_fn(&|| network.cli(sh), &node_uri, height)
+    })
 }
@@ -0,0 +1,385 @@
+//! A ready-made xtask command line, enabled by the `cli-app` feature.
+//!
+//! ```ignore
+//! fn deploy(sh: &Shell, network: &dyn Network) -> anyhow::Result<()> {
+//!     // store & instantiate contracts
+//! }
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     env_logger::init();
+//!
+//!     cosmwasm_xtask::cli_app::run(deploy)
+//! }
+//! ```
+//!
+//! Projects can add their own subcommands by implementing `XtaskCommand` for a clap `Subcommand` & using `run_with`:
+//!
+//! ```ignore
+//! #[derive(Subcommand)]
+//! enum Custom {
+//!     #[command(about = "migrate all contracts")]
+//!     MigrateAll,
+//! }
+//!
+//! impl XtaskCommand for Custom {
+//!     type Error = anyhow::Error;
+//!
+//!     fn run(self, sh: &Shell, network: &dyn Network) -> anyhow::Result<()> {
+//!         match self {
+//!             Custom::MigrateAll => migrate_all(sh, network),
+//!         }
+//!     }
+//! }
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     cosmwasm_xtask::cli_app::run_with::<Custom, _, _>(deploy)
+//! }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use clap::{ArgMatches, FromArgMatches, Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use xshell::{cmd, Shell};
+
+use crate::{
+    cli::{self, ShellCmdExt, XtaskMode},
+    config::Config,
+    metrics,
+    network::{Clean, Instance, Network, Node},
+    ops, output, ArchwayLocalnet, Error, Initialize, IntoForeground, NeutronLocalnet,
+    NeutronTestnet, StartLocal,
+};
+
+/// The command re-running the xtask within WSL when the Neutron localnet is selected on a Windows host,
+/// `cargo xtask` by default. The arguments of the current command line are appended.
+pub const WSL_COMMAND_ENV: &str = "COSMWASM_WSL_COMMAND";
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkOption {
+    ArchwayLocal,
+    NeutronLocal,
+    NeutronTestnet,
+}
+
+/// A project specific subcommand, run with the resolved `Shell` & selected network
+pub trait XtaskCommand: Subcommand {
+    type Error;
+
+    /// Run the subcommand
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementation.
+    fn run(self, sh: &Shell, network: &dyn Network) -> Result<(), Self::Error>;
+}
+
+/// No project specific subcommands, the default for `Cli`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoCommand {}
+
+impl FromArgMatches for NoCommand {
+    fn from_arg_matches(_: &ArgMatches) -> Result<Self, clap::Error> {
+        Err(clap::Error::new(clap::error::ErrorKind::InvalidSubcommand))
+    }
+
+    fn update_from_arg_matches(&mut self, _: &ArgMatches) -> Result<(), clap::Error> {
+        match *self {}
+    }
+}
+
+impl Subcommand for NoCommand {
+    fn augment_subcommands(cmd: clap::Command) -> clap::Command {
+        cmd
+    }
+
+    fn augment_subcommands_for_update(cmd: clap::Command) -> clap::Command {
+        cmd
+    }
+
+    fn has_subcommand(_: &str) -> bool {
+        false
+    }
+}
+
+impl XtaskCommand for NoCommand {
+    type Error = Error;
+
+    fn run(self, _: &Shell, _: &dyn Network) -> Result<(), Error> {
+        match self {}
+    }
+}
+
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command<C: XtaskCommand = NoCommand> {
+    #[command(about = "init local network")]
+    InitLocal,
+    #[command(about = "start local network")]
+    StartLocal,
+    #[command(about = "clean network state")]
+    Clean,
+    #[command(about = "clean all network artifacts")]
+    CleanAll,
+    #[command(about = "deploy contracts to the network")]
+    Deploy,
+    #[command(about = "list the keys")]
+    Keys,
+    #[command(about = "check the tools, disk space & ports the network needs")]
+    Doctor,
+    #[cfg(feature = "wizard")]
+    #[command(about = "interactively deploy a contract")]
+    Wizard,
+    #[command(flatten)]
+    Custom(C),
+}
+
+#[derive(Parser, Debug, Clone, Copy, PartialEq, Eq)]
+#[command(author, version, about, long_about = None)]
+#[command(propagate_version = true)]
+pub struct Cli<C: XtaskCommand = NoCommand> {
+    #[command(subcommand)]
+    pub command: Command<C>,
+    /// Defaults to `network` in `xtask.toml`
+    pub network: Option<NetworkOption>,
+    /// Print results as a single JSON object on stdout, see `output::json`
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Print the chain CLI commands which would change state rather than executing them, see `XtaskMode::DryRun`
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+}
+
+/// The `network` given on the command line, otherwise the default network in `xtask.toml`
+fn resolve_network(sh: &Shell, network: Option<NetworkOption>) -> Result<NetworkOption, Error> {
+    if let Some(network) = network {
+        return Ok(network);
+    }
+
+    let network = Config::load(sh)?.network.ok_or(Error::MissingNetwork)?;
+
+    NetworkOption::from_str(&network, true).map_err(|_| Error::UnknownNetwork(network))
+}
+
+/// The path of `wsl.exe` on a Windows host with WSL installed
+fn wsl_exe() -> Option<PathBuf> {
+    if !cfg!(windows) {
+        return None;
+    }
+
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join("wsl.exe"))
+        .find(|path| path.is_file())
+}
+
+/// Run the current command line again within WSL from the same directory, see `WSL_COMMAND_ENV`
+fn run_in_wsl(sh: &Shell, wsl: &Path) -> Result<(), Error> {
+    let command = std::env::var(WSL_COMMAND_ENV).unwrap_or_else(|_| "cargo xtask".to_owned());
+
+    let cwd = sh.current_dir();
+
+    cmd!(sh, "{wsl} --cd {cwd} --")
+        .args(command.split_whitespace())
+        .args(std::env::args().skip(1))
+        .logged()
+        .run()?;
+
+    Ok(())
+}
+
+/// Initialize the selected network & pass it to `f` as a `&dyn Network`
+fn with_network<F, E>(sh: &Shell, network: NetworkOption, f: F) -> Result<(), E>
+where
+    F: FnOnce(&dyn Network) -> Result<(), E>,
+    E: From<Error>,
+{
+    match network {
+        NetworkOption::ArchwayLocal => f(&ArchwayLocalnet::initialize(sh)?),
+        NetworkOption::NeutronLocal => f(&NeutronLocalnet::initialize(sh)?),
+        NetworkOption::NeutronTestnet => f(&NeutronTestnet::initialize(sh)?),
+    }
+}
+
+/// Initialize the Neutron testnet for deploying. A fresh checkout has no keys & the demo mnemonics are public,
+/// so rather than deploying from a demo account anyone can sweep, a deployer must be configured or recovered first.
+fn deploy_testnet(sh: &Shell) -> Result<Instance<NeutronTestnet>, Error> {
+    let network = NeutronTestnet::initialize(sh)?;
+
+    if network.keys.is_empty() && Config::load(sh)?.deployer.is_none() {
+        return Err(Error::NoDeployer(network.chain_id().to_string()));
+    }
+
+    Ok(network)
+}
+
+impl<C: XtaskCommand> Cli<C> {
+    /// Run the parsed command, calling `deploy` with the selected network for the `deploy` subcommand
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - No network is given & there is no default network in `xtask.toml`
+    /// - Initializing, starting or cleaning the network fails
+    /// - `start-local` is used with a network that is not local
+    /// - Running the command again within WSL fails, for the Neutron localnet on a Windows host
+    /// - `deploy` targets the Neutron testnet without any key or a configured deployer
+    /// - `deploy` or a custom subcommand fails
+    pub fn run<F, E>(self, sh: &Shell, deploy: F) -> Result<(), E>
+    where
+        F: FnOnce(&Shell, &dyn Network) -> Result<(), E>,
+        E: From<Error> + From<C::Error>,
+    {
+        // The Neutron localnet builds & supervises native Unix services, on Windows the whole command runs within WSL
+        if let Some(wsl) = wsl_exe() {
+            if resolve_network(sh, self.network).is_ok_and(|n| n == NetworkOption::NeutronLocal) {
+                return run_in_wsl(sh, &wsl).map_err(E::from);
+            }
+        }
+
+        output::set_json(self.json);
+
+        cli::set_mode(if self.dry_run {
+            XtaskMode::DryRun
+        } else {
+            XtaskMode::Execute
+        });
+
+        let network = || resolve_network(sh, self.network);
+
+        match self.command {
+            Command::InitLocal => with_network(sh, network()?, |network| {
+                if output::json() {
+                    output::emit(&json!({ "chain_id": network.chain_id().to_string() }))?;
+                }
+
+                Ok(())
+            }),
+
+            Command::StartLocal => match network()? {
+                NetworkOption::ArchwayLocal => ArchwayLocalnet::initialize(sh)?
+                    .start_local(sh)?
+                    .into_foreground()
+                    .map_err(E::from),
+
+                NetworkOption::NeutronLocal => NeutronLocalnet::initialize(sh)?
+                    .start_local(sh)?
+                    .into_foreground()
+                    .map_err(E::from),
+
+                NetworkOption::NeutronTestnet => {
+                    Err(Error::NotLocalNetwork("neutron-testnet".to_owned()).into())
+                }
+            },
+
+            Command::Clean => match network()? {
+                NetworkOption::ArchwayLocal => ArchwayLocalnet::clean_state(sh),
+                NetworkOption::NeutronLocal => NeutronLocalnet::clean_state(sh),
+                NetworkOption::NeutronTestnet => NeutronTestnet::clean_state(sh),
+            }
+            .map_err(E::from),
+
+            Command::CleanAll => match network()? {
+                NetworkOption::ArchwayLocal => ArchwayLocalnet::clean_all(sh),
+                NetworkOption::NeutronLocal => NeutronLocalnet::clean_all(sh),
+                NetworkOption::NeutronTestnet => NeutronTestnet::clean_all(sh),
+            }
+            .map_err(E::from),
+
+            Command::Deploy => match network()? {
+                NetworkOption::NeutronTestnet => deploy(sh, &deploy_testnet(sh)?),
+                network => with_network(sh, network, |network| deploy(sh, network)),
+            },
+
+            Command::Keys => with_network(sh, network()?, |network| {
+                if output::json() {
+                    let keys: Vec<_> = network
+                        .keys()
+                        .iter()
+                        .map(|key| json!({ "name": key.name(), "address": key.address() }))
+                        .collect();
+
+                    output::emit(&json!({ "keys": keys }))?;
+                } else {
+                    network.keys().iter().for_each(|key| println!("{key}"));
+                }
+
+                Ok(())
+            }),
+
+            Command::Doctor => {
+                // Without a network every network is checked
+                let network = match network() {
+                    Ok(network) => Some(network),
+                    Err(Error::MissingNetwork) => None,
+                    Err(err) => return Err(err.into()),
+                };
+
+                let name = network
+                    .and_then(|network| network.to_possible_value())
+                    .map(|value| value.get_name().to_owned());
+
+                let report = ops::doctor_network(sh, name.as_deref());
+
+                if output::json() {
+                    output::emit(&json!({ "healthy": report.is_healthy() }))?;
+                } else {
+                    print!("{report}");
+                }
+
+                if report.is_healthy() {
+                    Ok(())
+                } else {
+                    Err(Error::UnhealthyEnvironment.into())
+                }
+            }
+
+            #[cfg(feature = "wizard")]
+            Command::Wizard => crate::wizard::deploy(sh, self.network).map_err(E::from),
+
+            Command::Custom(command) => with_network(sh, network()?, |network| {
+                command.run(sh, network).map_err(E::from)
+            }),
+        }
+    }
+}
+
+/// Parse the command line arguments & run the command in a new shell, see `Cli::run`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Creating the shell fails
+/// - Running the command fails
+pub fn run<F, E>(deploy: F) -> Result<(), E>
+where
+    F: FnOnce(&Shell, &dyn Network) -> Result<(), E>,
+    E: From<Error>,
+{
+    run_with::<NoCommand, F, E>(deploy)
+}
+
+/// Like `run` but with the project specific subcommands `C`.
+/// Once the command finishes a timing `metrics::Summary` is printed to stderr, if anything was timed.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Creating the shell fails
+/// - Running the command fails
+pub fn run_with<C, F, E>(deploy: F) -> Result<(), E>
+where
+    C: XtaskCommand,
+    F: FnOnce(&Shell, &dyn Network) -> Result<(), E>,
+    E: From<Error> + From<C::Error>,
+{
+    let sh = Shell::new().map_err(Error::from)?;
+
+    let res = Cli::<C>::parse().run(&sh, deploy);
+
+    let summary = metrics::summary();
+
+    if !summary.is_empty() {
+        eprint!("{summary}");
+    }
+
+    res
+}
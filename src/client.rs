@@ -0,0 +1,111 @@
+/// Generate a typed client for a contract from its message types, wrapping `contract::instantiate`, `contract::execute` & `contract::query`.
+///
+/// ```ignore
+/// cosmwasm_xtask::contract_client! {
+///     pub struct Cw20Client;
+///
+///     instantiate = cw20_base::msg::InstantiateMsg;
+///
+///     execute = cw20::Cw20ExecuteMsg {
+///         fn mint => Mint { recipient: String, amount: cosmwasm_std::Uint128 };
+///         fn burn => Burn { amount: cosmwasm_std::Uint128 };
+///     }
+///
+///     query = cw20::Cw20QueryMsg {
+///         fn balance => Balance { address: String } -> cw20::BalanceResponse;
+///         fn token_info => TokenInfo {} -> cw20::TokenInfoResponse;
+///     }
+/// }
+///
+/// let contract = Cw20Client::instantiate(code_id, "demo_cw20", msg).send(&sh, &network, key)?;
+///
+/// let client = Cw20Client::new(contract);
+///
+/// client.mint(key.address().to_owned(), 1_000u128.into()).send(&sh, &network, key)?;
+///
+/// let balance = client.balance(&sh, &network, key.address().to_owned())?;
+/// ```
+#[macro_export]
+macro_rules! contract_client {
+    (
+        $vis:vis struct $client:ident;
+
+        $(instantiate = $init_msg:path;)?
+
+        execute = $exec_msg:path {
+            $(fn $exec_fn:ident => $exec_variant:ident { $($exec_field:ident : $exec_ty:ty),* $(,)? };)*
+        }
+
+        query = $query_msg:path {
+            $(fn $query_fn:ident => $query_variant:ident { $($query_field:ident : $query_ty:ty),* $(,)? } -> $query_res:ty;)*
+        }
+    ) => {
+        $vis struct $client {
+            contract: $crate::cli::Contract,
+        }
+
+        impl $client {
+            #[must_use]
+            pub fn new(contract: $crate::cli::Contract) -> Self {
+                Self { contract }
+            }
+
+            #[must_use]
+            pub fn contract(&self) -> &$crate::cli::Contract {
+                &self.contract
+            }
+
+            $(
+                #[must_use]
+                pub fn instantiate(
+                    code_id: $crate::cli::CodeId,
+                    label: &str,
+                    msg: $init_msg,
+                ) -> $crate::contract::Tx<
+                    $crate::contract::Instantiate,
+                    $init_msg,
+                    $crate::cli::Contract,
+                > {
+                    $crate::contract::instantiate(code_id, label, msg)
+                }
+            )?
+
+            $(
+                #[must_use]
+                pub fn $exec_fn(
+                    &self,
+                    $($exec_field: $exec_ty),*
+                ) -> $crate::contract::Tx<
+                    $crate::contract::Execute,
+                    $exec_msg,
+                    $crate::cli::CwExecuteResponse,
+                > {
+                    use $exec_msg as Msg;
+
+                    $crate::contract::execute(&self.contract, Msg::$exec_variant { $($exec_field),* })
+                }
+            )*
+
+            $(
+                /// # Errors
+                ///
+                /// This function will return an error if the query fails, see `contract::query`.
+                pub fn $query_fn(
+                    &self,
+                    sh: &$crate::__private::Shell,
+                    network: &dyn $crate::network::Network,
+                    $($query_field: $query_ty),*
+                ) -> Result<$query_res, $crate::Error> {
+                    use $query_msg as Msg;
+
+                    $crate::contract::query(
+                        sh,
+                        network,
+                        &self.contract,
+                        &Msg::$query_variant { $($query_field),* },
+                    )
+                }
+            )*
+        }
+    };
+}
@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use xshell::Shell;
+
+use crate::{
+    deploy::DEPLOYMENTS_DIR,
+    key::Key,
+    network::{gas, Network},
+    ops::DistOptions,
+    Error,
+};
+
+/// The name of the optional project config file at the workspace root
+pub const CONFIG_FILE: &str = "xtask.toml";
+
+/// The default artifacts directory, relative to the workspace root
+pub const ARTIFACTS_DIR: &str = "artifacts";
+
+/// Which of the network's gas prices to pay
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GasTier {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+/// How gas is paid for when it is simulated
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GasPolicy {
+    pub price: GasTier,
+    /// The factor a simulated gas estimate is scaled by
+    pub adjustment: f64,
+}
+
+impl Default for GasPolicy {
+    fn default() -> Self {
+        Self {
+            price: GasTier::default(),
+            adjustment: gas::DEFAULT_ADJUSTMENT,
+        }
+    }
+}
+
+impl GasPolicy {
+    /// The gas price of the chosen tier on the `network`
+    #[must_use]
+    pub fn price(&self, network: &dyn Network) -> gas::Price {
+        match self.price {
+            GasTier::Low => network.low_gas_price(),
+            GasTier::Medium => network.medium_gas_price(),
+            GasTier::High => network.high_gas_price(),
+        }
+    }
+}
+
+/// Project defaults loaded from `xtask.toml`, every field is optional:
+///
+/// ```toml
+/// network = "neutron-local"
+/// artifacts-dir = "artifacts"
+/// deployments-dir = "deployments"
+/// contracts = ["cw20-base"]
+/// deployer = "demo"
+///
+/// [gas]
+/// price = "medium"
+/// adjustment = 1.3
+/// ```
+///
+/// The `COSMWASM_ARTIFACTS_DIR` & `COSMWASM_DEPLOYMENTS_DIR` environment variables take precedence over the config file.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// The network used when none is given on the command line
+    pub network: Option<String>,
+    pub artifacts_dir: Option<PathBuf>,
+    pub deployments_dir: Option<PathBuf>,
+    /// The contract packages to build, all of them if empty
+    pub contracts: Vec<String>,
+    /// The name or address of the key to deploy with
+    pub deployer: Option<String>,
+    pub gas: GasPolicy,
+}
+
+impl Config {
+    /// Load `<workspace-root>/xtask.toml`, or the default config if there isn't one
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Reading the config file fails
+    /// - TOML deserialisation fails
+    pub fn load(sh: &Shell) -> Result<Self, Error> {
+        let path = sh.current_dir().join(CONFIG_FILE);
+
+        if !sh.path_exists(&path) {
+            return Ok(Self::default());
+        }
+
+        Ok(toml::from_str(&sh.read_file(path)?)?)
+    }
+
+    /// Resolve a workspace relative directory, preferring the environment variable `env`, then `configured`, then `default`
+    fn dir(sh: &Shell, env: &str, configured: Option<&PathBuf>, default: &str) -> PathBuf {
+        let dir = std::env::var_os(env)
+            .map(PathBuf::from)
+            .or_else(|| configured.cloned())
+            .unwrap_or_else(|| PathBuf::from(default));
+
+        sh.current_dir().join(dir)
+    }
+
+    /// The artifacts directory, `<workspace-root>/artifacts` by default
+    #[must_use]
+    pub fn artifacts_dir(&self, sh: &Shell) -> PathBuf {
+        Self::dir(
+            sh,
+            "COSMWASM_ARTIFACTS_DIR",
+            self.artifacts_dir.as_ref(),
+            ARTIFACTS_DIR,
+        )
+    }
+
+    /// The directory deployment manifests are kept in, `<workspace-root>/deployments` by default
+    #[must_use]
+    pub fn deployments_dir(&self, sh: &Shell) -> PathBuf {
+        Self::dir(
+            sh,
+            "COSMWASM_DEPLOYMENTS_DIR",
+            self.deployments_dir.as_ref(),
+            DEPLOYMENTS_DIR,
+        )
+    }
+
+    /// The configured deployer key on the `network`, or its first key if none is configured
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key is not found.
+    pub fn deployer<'a>(&self, network: &'a dyn Network) -> Result<&'a Key, Error> {
+        match &self.deployer {
+            Some(deployer) => network.require(deployer),
+            None => network
+                .keys()
+                .first()
+                .ok_or_else(|| Error::KeyNotFound("deployer".to_owned())),
+        }
+    }
+
+    /// Options for `ops::dist_workspace` building only the configured contracts into the configured artifacts directory
+    #[must_use]
+    pub fn dist_options(&self, sh: &Shell) -> DistOptions {
+        let contracts: Vec<_> = self.contracts.iter().map(String::as_str).collect();
+
+        DistOptions::default()
+            .artifacts_dir(self.artifacts_dir(sh))
+            .only(&contracts)
+    }
+}
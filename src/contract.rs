@@ -1,50 +1,120 @@
 use std::{
     marker::PhantomData,
     path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
 };
 
-use log::debug;
+use log::{debug, error};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use xshell::Shell;
+use sha2::{Digest, Sha256};
+use xshell::{cmd, Shell};
 
 use crate::{
-    cli::{wait_for_tx, CodeId, Contract, CwExecuteResponse, ReadyTxCmd, TxData},
+    broadcast::{GasLimit, PreExecuteBuildHook, TxEvent, TxKind, TxRequest},
+    cli::{
+        simulate_tx, wait_for_blocks, wait_for_tx_with_policy, wait_for_tx_with_timeout,
+        ChainError, CodeId, Coin, Contract, CwExecuteResponse, CwMigrateResponse, Event, GasReport,
+        Metadata, ProposalStatus, ReadyTxCmd, SearchedTx, Simulation, TxId, TxResult, VoteOption,
+        WaitPolicy, DEFAULT_TX_TIMEOUT,
+    },
+    deploy::{ContractRecord, Manifest, Pending, Provenance},
     key::Key,
-    network::Network,
+    metrics::{self, Step, StepKind},
+    network::{gas, Network},
     Error,
 };
 
+/// The gas limit used when a tx is not simulated & no explicit limit is set
+pub const DEFAULT_GAS_UNITS: u128 = 100_000_000;
+
 pub struct Store {
     path: PathBuf,
+    check: bool,
 }
 
 pub struct Instantiate {
     code_id: CodeId,
     label: String,
     admin: Option<String>,
+    verify_wasm: Option<PathBuf>,
 }
 
 pub struct Execute {
     contract: Contract,
 }
 
+pub struct Migrate {
+    contract: Contract,
+    code_id: CodeId,
+}
+
+pub struct UpdateAdmin {
+    contract: Contract,
+    new_admin: String,
+}
+
+pub struct ClearAdmin {
+    contract: Contract,
+}
+
 pub enum Cmd<Msg> {
     Store(Store),
     Instantiate { opts: Instantiate, msg: Msg },
     Execute { opts: Execute, msg: Msg },
+    Migrate { opts: Migrate, msg: Msg },
+    UpdateAdmin(UpdateAdmin),
+    ClearAdmin(ClearAdmin),
 }
 
-type PreExecuteBuildHook = Box<dyn for<'a> FnOnce(ReadyTxCmd<'a>) -> ReadyTxCmd<'a>>;
-
 pub struct Tx<Opts, Msg, Response> {
     cmd: Cmd<Msg>,
-    gas_units: u128,
-    amount: Vec<(u128, String)>,
+    gas_units: Option<u128>,
+    gas_adjustment: f64,
+    funds: Vec<Coin>,
+    fees: Option<Coin>,
     pre_execute_hook: Option<PreExecuteBuildHook>,
+    record_as: Option<String>,
+    wait: WaitPolicy,
     _r: PhantomData<Response>,
     _opts: PhantomData<Opts>,
 }
 
+impl<Msg, Response> Tx<Store, Msg, Response> {
+    /// Validate the WASM bytecode with `cosmwasm-check` against the network's capabilities before storing it, see `check_wasm`
+    #[must_use]
+    pub fn checked(mut self) -> Self {
+        if let Cmd::Store(opts) = &mut self.cmd {
+            opts.check = true;
+        }
+        self
+    }
+}
+
+impl Tx<Store, (), CodeId> {
+    /// Send the tx like `send`, unless identical bytecode has already been stored, in which case the existing code ID is returned.
+    /// See `find_stored_code`. The deployment manifest is not updated when existing code is reused.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if searching for existing code fails, or in the same cases as `send`.
+    pub fn send_or_reuse(
+        self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+    ) -> Result<CodeId, Error> {
+        let Cmd::Store(Store { path, .. }) = &self.cmd else {
+            unreachable!()
+        };
+
+        match find_stored_code(sh, network, path)? {
+            Some(code_id) => Ok(code_id),
+            None => self.send(sh, network, from),
+        }
+    }
+}
+
 impl<Msg, Response> Tx<Instantiate, Msg, Response> {
     fn opts_mut(&mut self) -> &mut Instantiate {
         match &mut self.cmd {
@@ -58,21 +128,86 @@ impl<Msg, Response> Tx<Instantiate, Msg, Response> {
         self.opts_mut().admin = Some(admin.to_owned());
         self
     }
+
+    /// Refuse to instantiate unless the on-chain code matches the WASM bytecode at `wasm_path`
+    #[must_use]
+    pub fn verified<P>(mut self, wasm_path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.opts_mut().verify_wasm = Some(wasm_path.as_ref().to_path_buf());
+        self
+    }
 }
 
 impl<Opts, Msg, Response> Tx<Opts, Msg, Response> {
+    fn new(cmd: Cmd<Msg>) -> Self {
+        Self {
+            cmd,
+            gas_units: None,
+            gas_adjustment: gas::DEFAULT_ADJUSTMENT,
+            funds: vec![],
+            fees: None,
+            pre_execute_hook: None,
+            record_as: None,
+            wait: WaitPolicy::default(),
+            _r: PhantomData,
+            _opts: PhantomData,
+        }
+    }
+
+    /// Use a fixed gas limit of `units` rather than simulating the tx
     #[must_use]
     pub fn gas(mut self, units: u128) -> Self {
-        self.gas_units = units;
+        self.gas_units = Some(units);
+        self
+    }
+
+    /// Scale the simulated gas estimate by `adjustment`
+    #[must_use]
+    pub fn gas_adjustment(mut self, adjustment: f64) -> Self {
+        self.gas_adjustment = adjustment;
+        self
+    }
+
+    /// Give up with `Error::TxTimeout` if the tx is not included in a block within `timeout` of calling `send`
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.wait = self.wait.max_wait(timeout);
+        self
+    }
+
+    /// Wait for the tx to be included in a block according to the `wait` policy, replacing any `timeout`
+    #[must_use]
+    pub fn wait_policy(mut self, wait: WaitPolicy) -> Self {
+        self.wait = wait;
         self
     }
 
+    /// Pay a flat fee of `amount` of `denom` instead of the network's gas price
     #[must_use]
-    pub fn amount(mut self, amount: u128, denom: &str) -> Self {
-        self.amount.push((amount, denom.to_owned()));
+    pub fn fees(mut self, amount: u128, denom: &str) -> Self {
+        self.fees = Some(Coin::new(amount, denom));
         self
     }
 
+    /// Attach `amount` of `denom` to the tx, see `add_funds`
+    #[must_use]
+    pub fn amount(self, amount: u128, denom: &str) -> Self {
+        self.add_funds(amount, denom)
+    }
+
+    /// Attach `amount` of `denom` to the tx, adding to any funds of the same denom already attached
+    #[must_use]
+    pub fn add_funds(mut self, amount: u128, denom: &str) -> Self {
+        match self.funds.iter_mut().find(|coin| coin.denom == denom) {
+            Some(coin) => coin.amount += amount,
+            None => self.funds.push(Coin::new(amount, denom)),
+        }
+        self
+    }
+
+    /// Modify the CLI command just before it is executed, e.g. to add chain specific flags
     #[must_use]
     pub fn pre_execute_hook<F>(mut self, f: F) -> Self
     where
@@ -81,6 +216,49 @@ impl<Opts, Msg, Response> Tx<Opts, Msg, Response> {
         self.pre_execute_hook = Some(Box::new(f));
         self
     }
+
+    /// Record the outcome of a store or instantiate tx in the deployment manifest as `name`.
+    ///
+    /// Migrate & admin txs update any manifest entry for the contract automatically.
+    #[must_use]
+    pub fn record_as(mut self, name: &str) -> Self {
+        self.record_as = Some(name.to_owned());
+        self
+    }
+
+    fn pending_record(&self) -> Result<Option<Pending>, Error> {
+        let pending = match (&self.cmd, self.record_as.clone()) {
+            (Cmd::Store(Store { path, .. }), Some(name)) => {
+                let checksum = checksum(path)?;
+
+                Some(Pending::Store {
+                    name,
+                    provenance: Provenance::for_artifact(path, &checksum),
+                    checksum,
+                })
+            }
+            (Cmd::Instantiate { opts, .. }, Some(name)) => Some(Pending::Instantiate {
+                name,
+                label: opts.label.clone(),
+                admin: opts.admin.clone(),
+            }),
+            (Cmd::Migrate { opts, .. }, _) => Some(Pending::Migrate {
+                contract: opts.contract.clone(),
+                code_id: opts.code_id,
+            }),
+            (Cmd::UpdateAdmin(opts), _) => Some(Pending::UpdateAdmin {
+                contract: opts.contract.clone(),
+                admin: Some(opts.new_admin.clone()),
+            }),
+            (Cmd::ClearAdmin(opts), _) => Some(Pending::UpdateAdmin {
+                contract: opts.contract.clone(),
+                admin: None,
+            }),
+            _ => None,
+        };
+
+        Ok(pending)
+    }
 }
 
 impl<Opts, Msg, Response> Tx<Opts, Msg, Response>
@@ -88,27 +266,16 @@ where
     Response: prost::Message + Default,
     Msg: Serialize,
 {
-    /// Send the tx, wait for it to be included in a block, then return the decoded `Response`
-    ///
-    /// # Errors
-    ///
-    /// This function will return an error if:
-    /// - Command execution fails
-    /// - The response from the node contains an error
-    /// - Decoding the `TxData` fails
-    pub fn send(self, sh: &Shell, network: &dyn Network, from: &Key) -> Result<Response, Error> {
-        let gas = network.medium_gas_price().units(self.gas_units);
-
-        let chain_id = network.chain_id();
-
-        let node_uri = network.node_uri(sh)?;
-
-        let cmd = network.cli(sh)?.tx(from, &chain_id, &node_uri);
+    /// Describe `cmd` as a backend agnostic `TxKind`, checking or verifying the code first if requested
+    fn tx_kind(cmd: Cmd<Msg>, sh: &Shell, network: &dyn Network) -> Result<TxKind, Error> {
+        let kind = match cmd {
+            Cmd::Store(Store { path, check }) => {
+                if check {
+                    check_wasm(sh, network, &path)?;
+                }
 
-        let cmd = match self.cmd {
-            Cmd::Store(Store { path }) => {
                 debug!("Storing contract bytecode: {}", path.as_path().display());
-                cmd.wasm_store(path)
+                TxKind::Store { wasm_path: path }
             }
             Cmd::Instantiate {
                 opts:
@@ -116,37 +283,269 @@ where
                         code_id,
                         label,
                         admin,
+                        verify_wasm,
                     },
                 msg,
             } => {
-                let msg_json = serde_json::to_string_pretty(&msg)?;
-                debug!("Initialising {label} with code id {code_id} with message:\n{msg_json}");
+                if let Some(wasm_path) = verify_wasm {
+                    verify_code(sh, network, code_id, wasm_path)?;
+                }
 
-                cmd.wasm_init(code_id, &label, &msg_json, admin.as_deref())
+                let msg = serde_json::to_string_pretty(&msg)?;
+                debug!("Initialising {label} with code id {code_id} with message:\n{msg}");
+
+                TxKind::Instantiate {
+                    code_id,
+                    label,
+                    admin,
+                    msg,
+                }
             }
             Cmd::Execute {
                 opts: Execute { contract },
                 msg,
             } => {
-                let msg_json = serde_json::to_string_pretty(&msg)?;
-                debug!("Executing {contract} with message:\n{msg_json}",);
-                cmd.wasm_exec(&contract, &msg_json)
+                let msg = serde_json::to_string_pretty(&msg)?;
+                debug!("Executing {contract} with message:\n{msg}",);
+                TxKind::Execute { contract, msg }
+            }
+            Cmd::Migrate {
+                opts: Migrate { contract, code_id },
+                msg,
+            } => {
+                let msg = serde_json::to_string_pretty(&msg)?;
+                debug!("Migrating {contract} to code id {code_id} with message:\n{msg}");
+                TxKind::Migrate {
+                    contract,
+                    code_id,
+                    msg,
+                }
+            }
+            Cmd::UpdateAdmin(UpdateAdmin {
+                contract,
+                new_admin,
+            }) => {
+                debug!("Updating admin of {contract} to {new_admin}");
+                TxKind::UpdateAdmin {
+                    contract,
+                    new_admin,
+                }
+            }
+            Cmd::ClearAdmin(ClearAdmin { contract }) => {
+                debug!("Clearing admin of {contract}");
+                TxKind::ClearAdmin { contract }
             }
         };
 
-        let cmd = if self.amount.is_empty() {
+        Ok(kind)
+    }
+
+    /// Simulate the tx without broadcasting it, returning the estimated gas usage & the events it would emit.
+    ///
+    /// The CLI dry-run only reports the gas estimate, so the tx is signed & simulated with the node's tx service
+    /// through its RPC with `curl`, see `Node::node_uri`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Command execution fails
+    /// - Signing the tx fails, e.g. `from` is offline & the network has no remote signer
+    /// - Simulating the tx fails, e.g. the contract returns an error
+    pub fn simulate(
+        self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+    ) -> Result<Simulation, Error> {
+        let chain_id = network.chain_id();
+
+        let node_uri = network.node_uri(sh)?;
+
+        let kind = Self::tx_kind(self.cmd, sh, network)?;
+
+        let cmd = kind.into_cli_cmd(network.cli(sh)?.tx(from, &chain_id, &node_uri));
+
+        let cmd = if self.funds.is_empty() {
             cmd
         } else {
-            cmd.amounts(self.amount.as_slice())
+            cmd.funds(&self.funds)
+        };
+
+        let cmd = match self.fees {
+            Some(Coin { amount, denom }) => cmd.fees(amount, &denom),
+            None => cmd,
+        };
+
+        let cmd = match self.pre_execute_hook {
+            Some(hook) => hook(cmd),
+            None => cmd,
+        };
+
+        // Simulations run with an unlimited gas meter, so a zero limit only keeps the fee from being deducted
+        let unsigned_tx = cmd.generate(&network.medium_gas_price().units(0))?;
+
+        let tmp_dir = sh.create_temp_dir()?;
+
+        let unsigned_tx_path = tmp_dir.path().join("unsigned_tx.json");
+
+        sh.write_file(&unsigned_tx_path, &unsigned_tx)?;
+
+        let signed_tx = if from.is_offline() {
+            network
+                .signer()
+                .ok_or_else(|| Error::MissingSigner(from.name().to_owned()))?
+                .sign(sh, network, from, &unsigned_tx)?
+        } else {
+            network
+                .cli(sh)?
+                .tx(from, &chain_id, &node_uri)
+                .sign(&unsigned_tx_path)?
+        };
+
+        let signed_tx_path = tmp_dir.path().join("signed_tx.json");
+
+        sh.write_file(&signed_tx_path, signed_tx)?;
+
+        let tx_bytes = network.cli(sh)?.encode_tx(&signed_tx_path)?;
+
+        let (estimate, events) = simulate_tx(sh, &node_uri, &tx_bytes)?;
+
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let limit = (estimate as f64 * self.gas_adjustment).ceil() as u128;
+
+        debug!("Simulated gas: {estimate}, limit: {limit}");
+
+        Ok(Simulation {
+            gas: GasReport { estimate, limit },
+            events,
+        })
+    }
+
+    /// Send the tx, wait for it to be included in a block, then return the decoded `Response`
+    ///
+    /// Unless a fixed limit is set with `gas`, the gas limit is estimated by simulating the tx when the network supports it,
+    /// otherwise `DEFAULT_GAS_UNITS` is used.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Command execution fails
+    /// - The response from the node contains an error
+    /// - Decoding the `TxData` fails
+    /// - The tx is not included in a block before the timeout, see `Tx::timeout` & `Tx::wait_policy`
+    pub fn send(self, sh: &Shell, network: &dyn Network, from: &Key) -> Result<Response, Error> {
+        self.send_full(sh, network, from).map(|res| res.data)
+    }
+
+    /// Send the tx like `send`, returning the tx hash, height, gas usage & events alongside the decoded `Response`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error in the same cases as `send`.
+    pub fn send_full(
+        self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+    ) -> Result<TxResult<Response>, Error> {
+        let started = Instant::now();
+
+        let deadline = started + self.wait.max_wait_duration();
+
+        let chain_id = network.chain_id();
+
+        let pending = self.pending_record()?;
+
+        let gas = gas_limit(network, self.gas_units, self.gas_adjustment);
+
+        let request = TxRequest {
+            kind: Self::tx_kind(self.cmd, sh, network)?,
+            funds: self.funds,
+            gas,
+            fees: self.fees,
+            pre_execute_hook: self.pre_execute_hook,
         };
 
-        let tx_id = cmd.execute(&gas)?;
+        network.fire_tx_event(&TxEvent::Broadcasting {
+            chain_id: &chain_id,
+            from,
+            kind: &request.kind,
+        });
+
+        let (step_kind, step_label) = step_of(&request.kind);
+
+        let broadcast_at = Instant::now();
+
+        let tx_id = network
+            .broadcaster()
+            .broadcast(sh, network, from, request)
+            .inspect_err(|err| log_failure_hint(network, err))?;
 
         debug!("TX: {tx_id}");
 
-        wait_for_tx(sh, network, &tx_id)?
-            .decode()
-            .map(TxData::into_data)
+        let remaining = deadline.saturating_duration_since(Instant::now());
+
+        let tx_data = wait_for_tx_with_policy(sh, network, &tx_id, &self.wait.max_wait(remaining))
+            .inspect_err(|err| log_failure_hint(network, err))?
+            .decode()?;
+
+        network.fire_tx_event(&TxEvent::Confirmed {
+            chain_id: &chain_id,
+            tx_hash: &tx_id,
+            height: tx_data.meta.height,
+            gas_wanted: tx_data.meta.gas_wanted,
+            gas_used: tx_data.meta.gas_used,
+            duration: broadcast_at.elapsed(),
+        });
+
+        metrics::record(Step {
+            kind: step_kind,
+            label: step_label,
+            duration: started.elapsed(),
+            gas_used: Some(tx_data.meta.gas_used),
+        });
+
+        // A dry run tx changed nothing worth recording
+        if let Some(pending) = pending.filter(|_| !tx_id.is_dry_run()) {
+            let mut manifest = Manifest::load(sh, &chain_id)?;
+
+            let managed = match &pending {
+                Pending::Store { .. } | Pending::Instantiate { .. } => true,
+                Pending::Migrate { contract, .. } | Pending::UpdateAdmin { contract, .. } => {
+                    manifest.contract_by_address(contract.as_str()).is_some()
+                }
+            };
+
+            if managed {
+                manifest.apply(pending, &tx_data)?;
+                manifest.save(sh)?;
+            }
+        }
+
+        Ok(TxResult::from(tx_data))
+    }
+}
+
+/// Point at the node's logs when the chain rejected a tx, localnets only
+fn log_failure_hint(network: &dyn Network, err: &Error) {
+    if let (Error::TxExecute(failure), Some(hint)) = (err, network.logs_hint()) {
+        error!("{failure}, {hint} for details");
+    }
+}
+
+/// The kind & label of the timing metrics step recorded for a tx
+fn step_of(kind: &TxKind) -> (StepKind, String) {
+    match kind {
+        TxKind::Store { wasm_path } => (StepKind::Store, wasm_path.display().to_string()),
+        TxKind::Instantiate { label, .. } => (StepKind::Instantiate, label.clone()),
+        TxKind::Execute { contract, .. } => (StepKind::Tx, format!("execute {contract}")),
+        TxKind::Migrate { contract, .. } => (StepKind::Tx, format!("migrate {contract}")),
+        TxKind::UpdateAdmin { contract, .. } => (StepKind::Tx, format!("update admin {contract}")),
+        TxKind::ClearAdmin { contract } => (StepKind::Tx, format!("clear admin {contract}")),
     }
 }
 
@@ -155,109 +554,1236 @@ pub fn store<P>(wasm_path: P) -> Tx<Store, (), CodeId>
 where
     P: AsRef<Path>,
 {
-    Tx {
-        cmd: Cmd::Store(Store {
-            path: wasm_path.as_ref().to_path_buf(),
-        }),
-        gas_units: 100_000_000,
-        amount: vec![],
-        pre_execute_hook: None,
-        _r: PhantomData,
-        _opts: PhantomData,
-    }
+    Tx::new(Cmd::Store(Store {
+        path: wasm_path.as_ref().to_path_buf(),
+        check: false,
+    }))
 }
 
-/// Get a predictable address for an instantiated `code_id` on the `network` with the given `creator` & `salt`
+/// The number of codes requested per page when searching the chain for a matching checksum
+const LIST_CODE_PAGE_LIMIT: u64 = 100;
+
+/// Find code already stored on the `network` matching the WASM bytecode at `wasm_path`.
+/// Codes recorded in the deployment manifest are checked first, falling back to searching every code on chain.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
+/// - Reading the local WASM file fails
+/// - Loading the deployment manifest fails
 /// - Command execution fails
-pub fn predict_adddress(
+pub fn find_stored_code<P>(
     sh: &Shell,
     network: &dyn Network,
-    code_id: CodeId,
-    creator: &Key,
-    salt: &str,
-) -> Result<String, Error> {
+    wasm_path: P,
+) -> Result<Option<CodeId>, Error>
+where
+    P: AsRef<Path>,
+{
+    let local = checksum(wasm_path)?;
+
     let node_uri = network.node_uri(sh)?;
-    let code_info = network.cli(sh)?.query(&node_uri).code_info(code_id)?;
-    network
-        .cli(sh)?
-        .build_address(&code_info.data_hash, creator, salt)
-}
 
-/// Construct a tx to instantiate a contract with the given `code_id` on the `network` with `msg`, responds with the contract address.
-pub fn instantiate<Msg>(code_id: CodeId, label: &str, msg: Msg) -> Tx<Instantiate, Msg, Contract> {
-    Tx {
-        cmd: Cmd::Instantiate {
-            opts: Instantiate {
-                code_id,
-                label: label.to_owned(),
-                admin: None,
-            },
-            msg,
-        },
-        gas_units: 100_000_000,
-        amount: vec![],
-        pre_execute_hook: None,
-        _r: PhantomData,
-        _opts: PhantomData,
+    let manifest = Manifest::load(sh, &network.chain_id())?;
+
+    if let Some((name, record)) = manifest.code_by_checksum(&local) {
+        // The chain may have been reset since the manifest was written
+        let on_chain = network
+            .cli(sh)?
+            .query(&node_uri)
+            .code_info(record.code_id())
+            .ok();
+
+        if on_chain.is_some_and(|info| info.data_hash.eq_ignore_ascii_case(&local)) {
+            debug!("Found {name} with checksum {local} in the deployment manifest");
+            return Ok(Some(record.code_id()));
+        }
     }
-}
 
-/// Construct a command to tx a `contract` with a `msg`, responding with the response bytes.
-pub fn execute<Msg>(contract: &Contract, msg: Msg) -> Tx<Execute, Msg, CwExecuteResponse> {
-    Tx {
-        cmd: Cmd::Execute {
-            opts: Execute {
-                contract: contract.clone(),
-            },
-            msg,
-        },
-        gas_units: 100_000_000,
-        amount: vec![],
-        pre_execute_hook: None,
-        _r: PhantomData,
-        _opts: PhantomData,
+    for page in 1.. {
+        let codes = network
+            .cli(sh)?
+            .query(&node_uri)
+            .list_code(page, LIST_CODE_PAGE_LIMIT)?;
+
+        if let Some(code) = codes
+            .iter()
+            .find(|code| code.data_hash.eq_ignore_ascii_case(&local))
+        {
+            debug!("Found code id {} with checksum {local}", code.code_id);
+            return Ok(Some(CodeId::unchecked(code.code_id)));
+        }
+
+        if (codes.len() as u64) < LIST_CODE_PAGE_LIMIT {
+            break;
+        }
     }
+
+    Ok(None)
 }
 
-/// Query a `contract` on the `network` with `msg`, returning the response.
+/// Validate the WASM bytecode at `wasm_path` with `cosmwasm-check` against the `network`'s capabilities
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// - Command execution fails
-/// - The response from the node contains an error
-/// - JSON deserialisation fails
-pub fn query<Msg, Response>(
+/// - Running `cosmwasm-check` fails, e.g. it is not installed
+/// - The bytecode fails validation
+pub fn check_wasm<P>(sh: &Shell, network: &dyn Network, wasm_path: P) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    check_wasm_with_capabilities(sh, network.wasm_capabilities(), wasm_path)
+}
+
+/// Validate the WASM bytecode at `wasm_path` with `cosmwasm-check` against the given `capabilities`, e.g. `NTRN_WASM_CAPABILITIES`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Running `cosmwasm-check` fails, e.g. it is not installed
+/// - The bytecode fails validation
+pub fn check_wasm_with_capabilities<P>(
     sh: &Shell,
-    network: &dyn Network,
-    contract: &Contract,
-    msg: &Msg,
-) -> Result<Response, Error>
+    capabilities: &[&str],
+    wasm_path: P,
+) -> Result<(), Error>
 where
-    Msg: Serialize,
-    Response: DeserializeOwned,
+    P: AsRef<Path>,
 {
-    #[derive(Deserialize)]
-    struct QueryData<T> {
-        data: T,
+    let path = wasm_path.as_ref();
+
+    let capabilities = capabilities.join(",");
+
+    let out = cmd!(
+        sh,
+        "cosmwasm-check --available-capabilities {capabilities} {path}"
+    )
+    .ignore_status()
+    .output()?;
+
+    if !out.status.success() {
+        let stdout = String::from_utf8(out.stdout)?;
+        let stderr = String::from_utf8(out.stderr)?;
+
+        return Err(Error::WasmCheck(
+            format!("{stdout}{stderr}").trim().to_owned(),
+        ));
     }
 
-    let node_uri = network.node_uri(sh)?;
+    debug!(
+        "Checked {} with capabilities {capabilities}",
+        path.display()
+    );
 
-    let msg_json = serde_json::to_string_pretty(msg)?;
+    Ok(())
+}
 
-    debug!("Querying {contract} with message:\n{msg_json}",);
+/// Read an unsigned LEB128 integer from the front of `bytes`, advancing past it
+fn read_leb128(bytes: &mut &[u8]) -> Result<u32, Error> {
+    let mut value = 0u32;
 
-    let res_json = network
-        .cli(sh)?
-        .query(&node_uri)
-        .wasm_smart(contract, &msg_json)?;
+    for shift in (0..35).step_by(7) {
+        let (&byte, rest) = bytes
+            .split_first()
+            .ok_or_else(|| Error::InvalidWasm("unexpected end of bytecode".to_owned()))?;
 
-    serde_json::from_str::<QueryData<Response>>(&res_json)
-        .map(|res| res.data)
-        .map_err(Error::from)
+        *bytes = rest;
+
+        value |= u32::from(byte & 0x7f) << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(Error::InvalidWasm("integer too long".to_owned()))
+}
+
+/// Split `len` bytes from the front of `bytes`
+fn take<'a>(bytes: &mut &'a [u8], len: u32) -> Result<&'a [u8], Error> {
+    let len = len as usize;
+
+    if bytes.len() < len {
+        return Err(Error::InvalidWasm("unexpected end of bytecode".to_owned()));
+    }
+
+    let (taken, rest) = bytes.split_at(len);
+
+    *bytes = rest;
+
+    Ok(taken)
+}
+
+/// The capabilities required by the WASM bytecode at `wasm_path`, declared by the contract as `requires_<capability>` exports
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Reading the file fails
+/// - The file is not valid WASM bytecode
+pub fn required_capabilities<P>(wasm_path: P) -> Result<Vec<String>, Error>
+where
+    P: AsRef<Path>,
+{
+    const EXPORT_SECTION: u8 = 7;
+
+    let bytecode = std::fs::read(wasm_path)?;
+
+    let mut bytes = bytecode
+        .strip_prefix(b"\0asm\x01\0\0\0")
+        .ok_or_else(|| Error::InvalidWasm("missing wasm header".to_owned()))?;
+
+    let mut required = vec![];
+
+    while let Some((&id, rest)) = bytes.split_first() {
+        bytes = rest;
+
+        let len = read_leb128(&mut bytes)?;
+
+        let mut section = take(&mut bytes, len)?;
+
+        if id != EXPORT_SECTION {
+            continue;
+        }
+
+        for _ in 0..read_leb128(&mut section)? {
+            let name_len = read_leb128(&mut section)?;
+
+            let name = std::str::from_utf8(take(&mut section, name_len)?)
+                .map_err(|err| Error::InvalidWasm(err.to_string()))?;
+
+            // The export kind & index
+            take(&mut section, 1)?;
+            read_leb128(&mut section)?;
+
+            if let Some(capability) = name.strip_prefix("requires_") {
+                required.push(capability.to_owned());
+            }
+        }
+    }
+
+    required.sort();
+
+    Ok(required)
+}
+
+/// Compute the hex encoded sha256 checksum of the WASM bytecode at `wasm_path`
+///
+/// # Errors
+///
+/// This function will return an error if reading the file fails.
+pub fn checksum<P>(wasm_path: P) -> Result<String, Error>
+where
+    P: AsRef<Path>,
+{
+    let bytecode = std::fs::read(wasm_path)?;
+
+    Ok(hex::encode(Sha256::digest(bytecode)))
+}
+
+/// Verify that the code stored at `code_id` on the `network` matches the WASM bytecode at `wasm_path`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Reading the local WASM file fails
+/// - Command execution fails
+/// - The on-chain checksum does not match the local checksum
+pub fn verify_code<P>(
+    sh: &Shell,
+    network: &dyn Network,
+    code_id: CodeId,
+    wasm_path: P,
+) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    let local = checksum(wasm_path)?;
+
+    let node_uri = network.node_uri(sh)?;
+
+    let on_chain = network
+        .cli(sh)?
+        .query(&node_uri)
+        .code_info(code_id)?
+        .data_hash;
+
+    if !local.eq_ignore_ascii_case(&on_chain) {
+        return Err(Error::CodeChecksumMismatch {
+            code_id: code_id.u64(),
+            local,
+            on_chain,
+        });
+    }
+
+    debug!("Verified code id {code_id} matches checksum {local}");
+
+    Ok(())
+}
+
+/// The differences between the code stored at a code id & a local WASM artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeDiff {
+    pub code_id: u64,
+    pub local_checksum: String,
+    pub on_chain_checksum: String,
+    /// Sizes in bytes
+    pub local_size: u64,
+    pub on_chain_size: u64,
+}
+
+impl CodeDiff {
+    /// Whether the local artifact differs from the stored code, so must be stored before migrating to it
+    #[must_use]
+    pub fn needs_redeploy(&self) -> bool {
+        !self
+            .local_checksum
+            .eq_ignore_ascii_case(&self.on_chain_checksum)
+    }
+}
+
+impl std::fmt::Display for CodeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.needs_redeploy() {
+            return write!(
+                f,
+                "code id {} matches the local artifact ({})",
+                self.code_id, self.local_checksum
+            );
+        }
+
+        write!(
+            f,
+            "code id {} differs from the local artifact: checksum {} -> {}, size {} -> {} bytes",
+            self.code_id,
+            self.on_chain_checksum,
+            self.local_checksum,
+            self.on_chain_size,
+            self.local_size
+        )
+    }
+}
+
+/// Download the code stored at `code_id` on the `network` & diff its checksum & size against the WASM bytecode at `wasm_path`,
+/// e.g. to decide whether a new code must be stored before a migration, see `CodeDiff::needs_redeploy`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Reading the local WASM file fails
+/// - Creating the temporary download directory fails
+/// - Command execution fails
+pub fn diff_code<P>(
+    sh: &Shell,
+    network: &dyn Network,
+    code_id: CodeId,
+    wasm_path: P,
+) -> Result<CodeDiff, Error>
+where
+    P: AsRef<Path>,
+{
+    let local = std::fs::read(wasm_path)?;
+
+    let node_uri = network.node_uri(sh)?;
+
+    let tmp = sh.create_temp_dir()?;
+
+    let download_path = tmp.path().join(format!("{code_id}.wasm"));
+
+    network
+        .cli(sh)?
+        .query(&node_uri)
+        .download_code(code_id, &download_path)?;
+
+    let on_chain = std::fs::read(&download_path)?;
+
+    let diff = CodeDiff {
+        code_id: code_id.u64(),
+        local_checksum: hex::encode(Sha256::digest(&local)),
+        on_chain_checksum: hex::encode(Sha256::digest(&on_chain)),
+        local_size: local.len() as u64,
+        on_chain_size: on_chain.len() as u64,
+    };
+
+    debug!("{diff}");
+
+    Ok(diff)
+}
+
+/// Get a predictable address for an instantiated `code_id` on the `network` with the given `creator` & `salt`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Command execution fails
+pub fn predict_adddress(
+    sh: &Shell,
+    network: &dyn Network,
+    code_id: CodeId,
+    creator: &Key,
+    salt: &str,
+) -> Result<String, Error> {
+    let node_uri = network.node_uri(sh)?;
+    let code_info = network.cli(sh)?.query(&node_uri).code_info(code_id)?;
+    network
+        .cli(sh)?
+        .build_address(&code_info.data_hash, creator, salt)
+}
+
+/// Resolve a contract recorded in the `network`'s deployment manifest as `name`, see `Tx::record_as`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Loading the deployment manifest fails
+/// - No contract named `name` has been recorded
+pub fn by_name(sh: &Shell, network: &dyn Network, name: &str) -> Result<Contract, Error> {
+    Manifest::load(sh, &network.chain_id())?
+        .contract(name)
+        .map(ContractRecord::contract)
+        .ok_or_else(|| Error::UnknownContract(name.to_owned()))
+}
+
+/// Construct a tx to instantiate a contract with the given `code_id` on the `network` with `msg`, responds with the contract address.
+pub fn instantiate<Msg>(code_id: CodeId, label: &str, msg: Msg) -> Tx<Instantiate, Msg, Contract> {
+    Tx::new(Cmd::Instantiate {
+        opts: Instantiate {
+            code_id,
+            label: label.to_owned(),
+            admin: None,
+            verify_wasm: None,
+        },
+        msg,
+    })
+}
+
+/// Construct a command to tx a `contract` with a `msg`, responding with the response bytes.
+pub fn execute<Msg>(contract: &Contract, msg: Msg) -> Tx<Execute, Msg, CwExecuteResponse> {
+    Tx::new(Cmd::Execute {
+        opts: Execute {
+            contract: contract.clone(),
+        },
+        msg,
+    })
+}
+
+pub struct StoreAndInstantiate<Msg> {
+    store: Tx<Store, (), CodeId>,
+    label: String,
+    admin: Option<String>,
+    record_as: Option<String>,
+    msg: Msg,
+}
+
+impl<Msg> StoreAndInstantiate<Msg> {
+    #[must_use]
+    pub fn admin(mut self, admin: &str) -> Self {
+        self.admin = Some(admin.to_owned());
+        self
+    }
+
+    /// Record both the code & the contract in the deployment manifest as `name`
+    #[must_use]
+    pub fn record_as(mut self, name: &str) -> Self {
+        self.store = self.store.record_as(name);
+        self.record_as = Some(name.to_owned());
+        self
+    }
+}
+
+impl<Msg> StoreAndInstantiate<Msg>
+where
+    Msg: Serialize,
+{
+    /// Send the store tx, wait for it to be included in a block, then do the same for the instantiate tx
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if sending either tx fails, see `Tx::send`.
+    pub fn send(
+        self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+    ) -> Result<(CodeId, Contract), Error> {
+        let code_id = self.store.send(sh, network, from)?;
+
+        let init = instantiate(code_id, &self.label, self.msg);
+
+        let init = match self.admin {
+            Some(admin) => init.admin(&admin),
+            None => init,
+        };
+
+        let init = match self.record_as {
+            Some(name) => init.record_as(&name),
+            None => init,
+        };
+
+        let contract = init.send(sh, network, from)?;
+
+        Ok((code_id, contract))
+    }
+}
+
+/// Construct a pair of txs to store the WASM bytecode at `wasm_path` then instantiate it with `msg`, responds with the code ID & contract address.
+pub fn store_and_instantiate<P, Msg>(
+    wasm_path: P,
+    label: &str,
+    msg: Msg,
+) -> StoreAndInstantiate<Msg>
+where
+    P: AsRef<Path>,
+{
+    StoreAndInstantiate {
+        store: store(wasm_path),
+        label: label.to_owned(),
+        admin: None,
+        record_as: None,
+        msg,
+    }
+}
+
+/// Execute a `cmd` built outside of a `Tx`, simulating the gas limit when the `network` supports it
+pub(crate) fn execute_with_network_gas(
+    cmd: ReadyTxCmd<'_>,
+    network: &dyn Network,
+    adjustment: f64,
+) -> Result<TxId, Error> {
+    let gas_price = network.medium_gas_price();
+
+    if network.supports_gas_simulation() {
+        cmd.execute_simulated(&gas_price, adjustment)
+    } else {
+        cmd.execute(&gas_price.units(DEFAULT_GAS_UNITS))
+    }
+}
+
+/// The gas limit of a tx: `gas_units` if set, otherwise simulated & scaled by `adjustment` when the `network` supports it
+fn gas_limit(network: &dyn Network, gas_units: Option<u128>, adjustment: f64) -> GasLimit {
+    let gas_price = network.medium_gas_price();
+
+    match gas_units {
+        Some(units) => GasLimit::Fixed(gas_price.units(units)),
+        None if network.supports_gas_simulation() => GasLimit::Simulated {
+            price: gas_price,
+            adjustment,
+        },
+        None => GasLimit::Fixed(gas_price.units(DEFAULT_GAS_UNITS)),
+    }
+}
+
+/// A `pre_execute_hook` applied to each tx of a batch
+type SharedPreExecuteHook = Rc<dyn for<'a> Fn(ReadyTxCmd<'a>) -> ReadyTxCmd<'a>>;
+
+pub struct StoreMany {
+    paths: Vec<PathBuf>,
+    gas_adjustment: f64,
+    fees: Option<Coin>,
+    pre_execute_hook: Option<SharedPreExecuteHook>,
+    wait: WaitPolicy,
+}
+
+impl StoreMany {
+    /// Scale the simulated gas estimate of each store tx by `adjustment`
+    #[must_use]
+    pub fn gas_adjustment(mut self, adjustment: f64) -> Self {
+        self.gas_adjustment = adjustment;
+        self
+    }
+
+    /// Pay a flat fee of `amount` of `denom` for each store tx instead of paying per unit of gas
+    #[must_use]
+    pub fn fees(mut self, amount: u128, denom: &str) -> Self {
+        self.fees = Some(Coin::new(amount, denom));
+        self
+    }
+
+    /// Modify the CLI command of each store tx just before it is executed, see `Tx::pre_execute_hook`
+    #[must_use]
+    pub fn pre_execute_hook<F>(mut self, f: F) -> Self
+    where
+        F: for<'a> Fn(ReadyTxCmd<'a>) -> ReadyTxCmd<'a> + 'static,
+    {
+        self.pre_execute_hook = Some(Rc::new(f));
+        self
+    }
+
+    /// Give up with `Error::TxTimeout` if the txs are not all included in a block within `timeout` of the last broadcast
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.wait = self.wait.max_wait(timeout);
+        self
+    }
+
+    /// Wait for the txs according to the `wait` policy, its `max_wait` covering all of them
+    #[must_use]
+    pub fn wait_policy(mut self, wait: WaitPolicy) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// Broadcast the store tx for `wasm_path` with the network's broadcaster, signed with an explicit `sequence`
+    fn broadcast(
+        &self,
+        sh: &Shell,
+        network: &dyn Network,
+        from: &Key,
+        wasm_path: &Path,
+        account_number: u64,
+        sequence: u64,
+    ) -> Result<TxId, Error> {
+        debug!(
+            "Storing contract bytecode with sequence {sequence}: {}",
+            wasm_path.display()
+        );
+
+        let hook = self.pre_execute_hook.clone();
+
+        let request = TxRequest {
+            kind: TxKind::Store {
+                wasm_path: wasm_path.to_path_buf(),
+            },
+            funds: vec![],
+            gas: gas_limit(network, None, self.gas_adjustment),
+            fees: self.fees.clone(),
+            pre_execute_hook: Some(Box::new(move |cmd| {
+                let cmd = match &hook {
+                    Some(hook) => hook(cmd),
+                    None => cmd,
+                };
+
+                cmd.sequence(account_number, sequence)
+            })),
+        };
+
+        network.fire_tx_event(&TxEvent::Broadcasting {
+            chain_id: &network.chain_id(),
+            from,
+            kind: &request.kind,
+        });
+
+        network
+            .broadcaster()
+            .broadcast(sh, network, from, request)
+            .inspect_err(|err| log_failure_hint(network, err))
+    }
+
+    /// Broadcast a store tx for every WASM file with consecutive sequence numbers, then wait for them all to be included.
+    /// Responds with the code IDs in the same order as the paths.
+    ///
+    /// Txs go through the network's broadcaster & fire its tx hooks like any other. The sequence is pinned with a
+    /// `pre_execute_hook`, so only CLI based broadcasters broadcast without waiting a block between each.
+    /// On an account sequence mismatch, e.g. another tx was sent from `from` meanwhile, the account is queried again
+    /// once the pending txs are included & the remaining sequences are rebased on it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Querying the account of `from` fails
+    /// - Broadcasting any of the txs fails, or fails with a sequence mismatch again after rebasing
+    /// - Any of the txs is not included in a block before the timeout
+    /// - Decoding the `TxData` fails
+    pub fn send(self, sh: &Shell, network: &dyn Network, from: &Key) -> Result<Vec<CodeId>, Error> {
+        let chain_id = network.chain_id();
+
+        let node_uri = network.node_uri(sh)?;
+
+        let account = network.cli(sh)?.query(&node_uri).account(from.address())?;
+
+        let mut sequence = account.sequence;
+
+        let started = Instant::now();
+
+        let mut tx_ids = Vec::with_capacity(self.paths.len());
+
+        let mut paths = self.paths.iter().peekable();
+
+        let mut rebased = false;
+
+        while let Some(path) = paths.peek() {
+            let broadcast_at = Instant::now();
+
+            match self.broadcast(sh, network, from, path, account.account_number, sequence) {
+                Ok(tx_id) => {
+                    debug!("TX: {tx_id}");
+
+                    tx_ids.push((tx_id, *path, broadcast_at));
+
+                    sequence += 1;
+
+                    rebased = false;
+
+                    paths.next();
+                }
+                Err(err)
+                    if !rebased
+                        && err.chain_error() == Some(ChainError::AccountSequenceMismatch) =>
+                {
+                    // The mempool may still hold txs of this batch, which the queried sequence only covers once included
+                    wait_for_blocks(sh, network)?;
+
+                    let account = network.cli(sh)?.query(&node_uri).account(from.address())?;
+
+                    debug!(
+                        "Rebasing the remaining store txs from sequence {sequence} to {}",
+                        account.sequence
+                    );
+
+                    sequence = account.sequence;
+
+                    rebased = true;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let deadline = Instant::now() + self.wait.max_wait_duration();
+
+        tx_ids
+            .iter()
+            .map(|(tx_id, path, broadcast_at)| {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+
+                let tx_data =
+                    wait_for_tx_with_policy(sh, network, tx_id, &self.wait.max_wait(remaining))
+                        .inspect_err(|err| log_failure_hint(network, err))?
+                        .decode::<CodeId>()?;
+
+                network.fire_tx_event(&TxEvent::Confirmed {
+                    chain_id: &chain_id,
+                    tx_hash: tx_id,
+                    height: tx_data.meta.height,
+                    gas_wanted: tx_data.meta.gas_wanted,
+                    gas_used: tx_data.meta.gas_used,
+                    duration: broadcast_at.elapsed(),
+                });
+
+                metrics::record(Step {
+                    kind: StepKind::Store,
+                    label: path.display().to_string(),
+                    duration: started.elapsed(),
+                    gas_used: Some(tx_data.meta.gas_used),
+                });
+
+                Ok(tx_data.into_data())
+            })
+            .collect()
+    }
+}
+
+/// Construct a batch of txs to store the WASM bytecode at each of the `wasm_paths` without waiting a block between each, responds with the code IDs.
+pub fn store_many<P>(wasm_paths: &[P]) -> StoreMany
+where
+    P: AsRef<Path>,
+{
+    StoreMany {
+        paths: wasm_paths
+            .iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .collect(),
+        gas_adjustment: gas::DEFAULT_ADJUSTMENT,
+        fees: None,
+        pre_execute_hook: None,
+        wait: WaitPolicy::default(),
+    }
+}
+
+/// How long to wait for a store code proposal to pass by default
+pub const DEFAULT_PROPOSAL_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub struct StoreViaGov {
+    path: PathBuf,
+    title: String,
+    summary: Option<String>,
+    deposit: Coin,
+    voters: Vec<Key>,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl StoreViaGov {
+    /// Describe the proposal with `summary`, defaults to the title
+    #[must_use]
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_owned());
+        self
+    }
+
+    /// Vote yes on the proposal with each of the `voters`, e.g. the validator keys of a localnet.
+    /// Without any voters the proposal is left to be voted on by others.
+    #[must_use]
+    pub fn vote_with(mut self, voters: &[Key]) -> Self {
+        self.voters = voters.to_vec();
+        self
+    }
+
+    /// How often to query the proposal status while waiting for it to pass
+    #[must_use]
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Give up with `Error::ProposalTimeout` if the proposal has not passed within `timeout` of being submitted
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Submit the store code proposal from the `proposer`, vote on it, wait for it to pass, then respond with the code ID
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Command execution fails
+    /// - The proposal ID is missing from the submission tx events
+    /// - The proposal is rejected or fails
+    /// - The proposal has not passed before the timeout
+    /// - The stored code cannot be found after the proposal passed
+    pub fn send(self, sh: &Shell, network: &dyn Network, proposer: &Key) -> Result<CodeId, Error> {
+        let chain_id = network.chain_id();
+
+        let node_uri = network.node_uri(sh)?;
+
+        debug!(
+            "Proposing to store contract bytecode: {}",
+            self.path.as_path().display()
+        );
+
+        let cmd = network
+            .cli(sh)?
+            .tx(proposer, &chain_id, &node_uri)
+            .wasm_store_proposal(
+                &self.path,
+                &self.title,
+                self.summary.as_deref().unwrap_or(&self.title),
+                &self.deposit,
+            );
+
+        let tx_id = execute_with_network_gas(cmd, network, gas::DEFAULT_ADJUSTMENT)?;
+
+        let deadline = Instant::now() + self.timeout;
+
+        let proposal_id = pass_proposal(
+            sh,
+            network,
+            &tx_id,
+            &self.voters,
+            self.poll_interval,
+            deadline,
+        )?;
+
+        debug!("Store code proposal {proposal_id} passed");
+
+        find_stored_code(sh, network, &self.path)?.ok_or(Error::ExpectedCodeId)
+    }
+}
+
+/// Wait for the proposal submitted by `tx_id` to be included, vote yes on it with each of the `voters`,
+/// then query its status every `poll_interval` until it passes, responding with the proposal ID
+pub(crate) fn pass_proposal(
+    sh: &Shell,
+    network: &dyn Network,
+    tx_id: &TxId,
+    voters: &[Key],
+    poll_interval: Duration,
+    deadline: Instant,
+) -> Result<u64, Error> {
+    let chain_id = network.chain_id();
+
+    let node_uri = network.node_uri(sh)?;
+
+    let tx_data = wait_for_tx_with_timeout(sh, network, tx_id, DEFAULT_TX_TIMEOUT)?;
+
+    let proposal_id: u64 = tx_data
+        .attributes()
+        .find(|attr| attr.key == "proposal_id")
+        .ok_or(Error::ExpectedProposalId)?
+        .value
+        .parse()?;
+
+    debug!("Submitted proposal {proposal_id}");
+
+    for voter in voters {
+        let cmd = network
+            .cli(sh)?
+            .tx(voter, &chain_id, &node_uri)
+            .gov_vote(proposal_id, VoteOption::Yes);
+
+        let tx_id = execute_with_network_gas(cmd, network, gas::DEFAULT_ADJUSTMENT)?;
+
+        wait_for_tx_with_timeout(sh, network, &tx_id, DEFAULT_TX_TIMEOUT)?;
+
+        debug!("{} voted yes on proposal {proposal_id}", voter.name());
+    }
+
+    metrics::timed(
+        StepKind::Wait,
+        &format!("proposal {proposal_id}"),
+        || loop {
+            let proposal = network
+                .cli(sh)?
+                .query(&node_uri)
+                .gov_proposal(proposal_id)?;
+
+            match proposal.status {
+                ProposalStatus::Passed => return Ok(()),
+                ProposalStatus::Rejected | ProposalStatus::Failed => {
+                    return Err(Error::ProposalRejected(proposal_id));
+                }
+                _ if Instant::now() >= deadline => {
+                    return Err(Error::ProposalTimeout(proposal_id));
+                }
+                _ => std::thread::sleep(poll_interval),
+            }
+        },
+    )?;
+
+    Ok(proposal_id)
+}
+
+/// Construct a governance proposal to store the WASM bytecode at `wasm_path` on permissioned chains, responds with the code ID once passed.
+pub fn store_via_gov<P>(wasm_path: P, title: &str, deposit: Coin) -> StoreViaGov
+where
+    P: AsRef<Path>,
+{
+    StoreViaGov {
+        path: wasm_path.as_ref().to_path_buf(),
+        title: title.to_owned(),
+        summary: None,
+        deposit,
+        voters: vec![],
+        poll_interval: Duration::from_secs(1),
+        timeout: DEFAULT_PROPOSAL_TIMEOUT,
+    }
+}
+
+/// Construct a tx to migrate a `contract` to `new_code_id` with a `msg`, responding with the response bytes.
+pub fn migrate<Msg>(
+    contract: &Contract,
+    new_code_id: CodeId,
+    msg: Msg,
+) -> Tx<Migrate, Msg, CwMigrateResponse> {
+    Tx::new(Cmd::Migrate {
+        opts: Migrate {
+            contract: contract.clone(),
+            code_id: new_code_id,
+        },
+        msg,
+    })
+}
+
+/// Construct a tx to set the admin of a `contract` to `new_admin`.
+#[must_use]
+pub fn update_admin(contract: &Contract, new_admin: &str) -> Tx<UpdateAdmin, (), ()> {
+    Tx::new(Cmd::UpdateAdmin(UpdateAdmin {
+        contract: contract.clone(),
+        new_admin: new_admin.to_owned(),
+    }))
+}
+
+/// Construct a tx to clear the admin of a `contract`, making it immutable.
+#[must_use]
+pub fn clear_admin(contract: &Contract) -> Tx<ClearAdmin, (), ()> {
+    Tx::new(Cmd::ClearAdmin(ClearAdmin {
+        contract: contract.clone(),
+    }))
+}
+
+/// Query a `contract` on the `network` with `msg`, returning the response.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Command execution fails
+/// - The response from the node contains an error
+/// - JSON deserialisation fails
+pub fn query<Msg, Response>(
+    sh: &Shell,
+    network: &dyn Network,
+    contract: &Contract,
+    msg: &Msg,
+) -> Result<Response, Error>
+where
+    Msg: Serialize,
+    Response: DeserializeOwned,
+{
+    #[derive(Deserialize)]
+    struct QueryData<T> {
+        data: T,
+    }
+
+    let node_uri = network.node_uri(sh)?;
+
+    let msg_json = serde_json::to_string_pretty(msg)?;
+
+    debug!("Querying {contract} with message:\n{msg_json}",);
+
+    let res_json = network
+        .cli(sh)?
+        .query(&node_uri)
+        .wasm_smart(contract, &msg_json)?;
+
+    serde_json::from_str::<QueryData<Response>>(&res_json)
+        .map(|res| res.data)
+        .map_err(Error::from)
+}
+
+/// Read the value stored under `storage_key` directly from a `contract`'s storage, e.g. an `Item` or `Map` entry.
+/// Responds with `None` if nothing is stored under the key.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Command execution fails
+/// - Base64 decoding of the value fails
+/// - JSON deserialisation fails
+pub fn query_raw<T>(
+    sh: &Shell,
+    network: &dyn Network,
+    contract: &Contract,
+    storage_key: impl AsRef<[u8]>,
+) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    #[derive(Deserialize)]
+    struct RawData {
+        data: Option<String>,
+    }
+
+    let node_uri = network.node_uri(sh)?;
+
+    let key = STANDARD.encode(storage_key);
+
+    debug!("Querying raw state of {contract} with key {key}");
+
+    let res_json = network.cli(sh)?.query(&node_uri).wasm_raw(contract, &key)?;
+
+    let Some(data) = serde_json::from_str::<RawData>(&res_json)?
+        .data
+        .filter(|data| !data.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let bytes = STANDARD.decode(data)?;
+
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(Error::from)
+}
+
+/// The event types holding the address of the contract a tx executed, instantiated or migrated
+const CONTRACT_EVENT_TYPES: &[&str] = &["execute", "instantiate", "migrate"];
+
+/// An event emitted by a contract, i.e. of type `wasm` or `wasm-<custom>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmEvent {
+    pub r#type: String,
+    pub attributes: Vec<(String, String)>,
+}
+
+/// A tx which touched a contract, see `recent_txs`
+#[derive(Debug, Clone)]
+pub struct ContractTx {
+    pub tx_hash: TxId,
+    pub height: u64,
+    pub gas_used: u128,
+    /// The execute, instantiate & migrate msgs of the tx for the contract, as rendered by the CLI, e.g. with `sender`, `msg` & `funds`
+    pub msgs: Vec<serde_json::Value>,
+    /// The events the contract emitted
+    pub events: Vec<WasmEvent>,
+}
+
+impl std::fmt::Display for ContractTx {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} at height {} ({} gas)",
+            self.tx_hash, self.height, self.gas_used
+        )?;
+
+        for msg in &self.msgs {
+            let body = msg.get("msg").unwrap_or(msg);
+
+            writeln!(f, "  msg: {body}")?;
+        }
+
+        for event in &self.events {
+            write!(f, "  event {}:", event.r#type)?;
+
+            for (key, value) in &event.attributes {
+                write!(f, " {key}={value}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the last `n` txs which executed, instantiated or migrated the `contract`, newest first,
+/// with the msgs sent to it & the events it emitted, e.g. to work out how its state came to be during local development.
+///
+/// Relies on the node indexing tx events, failed txs are not indexed so are never found.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Searching for the txs fails
+/// - JSON deserialisation fails
+pub fn recent_txs(
+    sh: &Shell,
+    network: &dyn Network,
+    contract: &Contract,
+    n: usize,
+) -> Result<Vec<ContractTx>, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    let limit = n as u64;
+
+    if limit == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut txs = vec![];
+
+    for event_type in CONTRACT_EVENT_TYPES {
+        let key = format!("{event_type}._contract_address");
+
+        let events = [(key.as_str(), contract.as_str())];
+
+        let first = network
+            .cli(sh)?
+            .query(&node_uri)
+            .txs_page(&events, 1, limit)?;
+
+        if first.total_count <= limit {
+            txs.extend(first.txs);
+            continue;
+        }
+
+        // Search results are oldest first, so the last `n` can span the last two pages
+        let last_page = first.total_count.div_ceil(limit);
+
+        let pages = if first.total_count % limit == 0 {
+            vec![last_page]
+        } else {
+            vec![last_page - 1, last_page]
+        };
+
+        for page in pages {
+            let page = network
+                .cli(sh)?
+                .query(&node_uri)
+                .txs_page(&events, page, limit)?;
+
+            txs.extend(page.txs);
+        }
+    }
+
+    // Newest first, a tx found by several event types ends up next to its duplicates
+    txs.sort_by(|a, b| (b.meta.height, &b.meta.txhash).cmp(&(a.meta.height, &a.meta.txhash)));
+
+    txs.dedup_by(|a, b| a.meta.txhash == b.meta.txhash);
+
+    txs.truncate(n);
+
+    debug!("Found {} recent txs of {contract}", txs.len());
+
+    Ok(txs
+        .into_iter()
+        .map(|tx| contract_tx(tx, contract))
+        .collect())
+}
+
+/// Whether the msg at `index` of a tx with the `meta` instantiated the `contract`, going by the `instantiate` events it emitted.
+/// Events are attributed to msgs by their log before SDK 0.50 & by their `msg_index` attribute after.
+fn instantiated_by(meta: &Metadata, index: usize, contract: &Contract) -> bool {
+    let instantiates = |event: &Event| {
+        event.r#type == "instantiate"
+            && event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "_contract_address" && attr.value == contract.as_str())
+    };
+
+    if !meta.logs.is_empty() {
+        return meta
+            .logs
+            .get(index)
+            .is_some_and(|log| log.events.iter().any(instantiates));
+    }
+
+    let index = index.to_string();
+
+    meta.events
+        .iter()
+        .filter(|event| {
+            // Events without a msg index are not tied to a msg
+            !event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "msg_index" && attr.value != index)
+        })
+        .any(instantiates)
+}
+
+fn contract_tx(tx: SearchedTx, contract: &Contract) -> ContractTx {
+    let msgs = tx
+        .tx
+        .pointer("/body/messages")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter(|(index, msg)| {
+            let is_instantiate = msg
+                .get("@type")
+                .and_then(serde_json::Value::as_str)
+                .is_some_and(|ty| ty.contains("MsgInstantiateContract"));
+
+            if is_instantiate {
+                return instantiated_by(&tx.meta, *index, contract);
+            }
+
+            msg.get("contract").and_then(serde_json::Value::as_str) == Some(contract.as_str())
+        })
+        .map(|(_, msg)| msg.clone())
+        .collect();
+
+    let events = tx
+        .meta
+        .events()
+        .filter(|event| event.r#type == "wasm" || event.r#type.starts_with("wasm-"))
+        .filter(|event| {
+            event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "_contract_address" && attr.value == contract.as_str())
+        })
+        .map(|event| WasmEvent {
+            r#type: event.r#type.clone(),
+            attributes: event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.clone(), attr.value.clone()))
+                .collect(),
+        })
+        .collect();
+
+    ContractTx {
+        tx_hash: TxId::from(tx.meta.txhash),
+        height: tx.meta.height,
+        gas_used: tx.meta.gas_used,
+        msgs,
+        events,
+    }
 }
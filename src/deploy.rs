@@ -0,0 +1,296 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use xshell::Shell;
+
+use crate::{
+    cli::{CodeId, Contract, TxData},
+    config::Config,
+    network::ChainId,
+    Error,
+};
+
+/// The current version of the manifest file format
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// The default directory manifests are kept in, relative to the workspace root.
+/// This can be overridden by `deployments-dir` in `xtask.toml` or setting the `COSMWASM_DEPLOYMENTS_DIR` environment variable.
+pub const DEPLOYMENTS_DIR: &str = "deployments";
+
+/// The name of the file in the artifacts directory recording the provenance of each artifact by file name
+pub const PROVENANCE_FILE: &str = "provenance.json";
+
+/// Where an artifact was built from, so a stored code can be traced to its exact sources
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Provenance {
+    /// The hex encoded sha256 checksum of the artifact
+    pub checksum: String,
+    pub git_commit: Option<String>,
+    /// Whether the git working tree had uncommitted changes
+    pub git_dirty: bool,
+    pub rustc_version: Option<String>,
+    /// The optimizer image, or `wasm-opt` version for native builds
+    pub optimizer: Option<String>,
+}
+
+impl Provenance {
+    /// The recorded provenance of the artifact at `wasm_path`, from the `provenance.json` alongside it, if it matches the artifact's `checksum`
+    #[must_use]
+    pub fn for_artifact(wasm_path: &Path, checksum: &str) -> Option<Self> {
+        let file_name = wasm_path.file_name()?.to_str()?;
+
+        let json = std::fs::read_to_string(wasm_path.with_file_name(PROVENANCE_FILE)).ok()?;
+
+        let mut records: BTreeMap<String, Provenance> = serde_json::from_str(&json).ok()?;
+
+        records
+            .remove(file_name)
+            .filter(|record| record.checksum.eq_ignore_ascii_case(checksum))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CodeRecord {
+    pub code_id: u64,
+    pub checksum: String,
+    pub tx_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
+impl CodeRecord {
+    #[must_use]
+    pub fn code_id(&self) -> CodeId {
+        CodeId::unchecked(self.code_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ContractRecord {
+    pub address: String,
+    pub code_id: u64,
+    pub label: String,
+    pub admin: Option<String>,
+    pub tx_hash: String,
+}
+
+impl ContractRecord {
+    #[must_use]
+    pub fn contract(&self) -> Contract {
+        Contract::unchecked(self.address.clone())
+    }
+
+    #[must_use]
+    pub fn code_id(&self) -> CodeId {
+        CodeId::unchecked(self.code_id)
+    }
+}
+
+/// A record of everything deployed to a single chain, kept in `<deployments-dir>/<chain-id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Manifest {
+    pub version: u32,
+    pub chain_id: String,
+    #[serde(default)]
+    pub codes: BTreeMap<String, CodeRecord>,
+    #[serde(default)]
+    pub contracts: BTreeMap<String, ContractRecord>,
+}
+
+/// Changes to the manifest which are pending confirmation of a tx
+pub(crate) enum Pending {
+    Store {
+        name: String,
+        checksum: String,
+        provenance: Option<Provenance>,
+    },
+    Instantiate {
+        name: String,
+        label: String,
+        admin: Option<String>,
+    },
+    Migrate {
+        contract: Contract,
+        code_id: CodeId,
+    },
+    UpdateAdmin {
+        contract: Contract,
+        admin: Option<String>,
+    },
+}
+
+fn find_attribute<'a, D>(tx_data: &'a TxData<D>, key: &str) -> Option<&'a str> {
+    tx_data
+        .attributes()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.as_str())
+}
+
+impl Manifest {
+    #[must_use]
+    pub fn new(chain_id: &ChainId) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            chain_id: chain_id.to_string(),
+            codes: BTreeMap::new(),
+            contracts: BTreeMap::new(),
+        }
+    }
+
+    /// The path of the manifest file for `chain_id`, see `Config::deployments_dir`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if loading `xtask.toml` fails.
+    pub fn path(sh: &Shell, chain_id: &ChainId) -> Result<PathBuf, Error> {
+        let mut path = Config::load(sh)?.deployments_dir(sh);
+        path.push(format!("{chain_id}.json"));
+        Ok(path)
+    }
+
+    /// Load the manifest for `chain_id`, or an empty one if nothing has been deployed yet
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Loading `xtask.toml` fails
+    /// - Reading the manifest file fails
+    /// - JSON deserialisation fails
+    /// - The manifest was written by a newer version of this crate
+    pub fn load(sh: &Shell, chain_id: &ChainId) -> Result<Self, Error> {
+        let path = Self::path(sh, chain_id)?;
+
+        if !sh.path_exists(&path) {
+            return Ok(Self::new(chain_id));
+        }
+
+        let manifest: Self = serde_json::from_str(&sh.read_file(path)?)?;
+
+        if manifest.version > MANIFEST_VERSION {
+            return Err(Error::UnsupportedManifestVersion(manifest.version));
+        }
+
+        Ok(manifest)
+    }
+
+    /// Write the manifest to `<deployments-dir>/<chain-id>.json`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Loading `xtask.toml` fails
+    /// - JSON serialisation fails
+    /// - Writing the manifest file fails
+    pub fn save(&self, sh: &Shell) -> Result<(), Error> {
+        let path = Self::path(sh, &ChainId::from(self.chain_id.clone()))?;
+
+        let json = serde_json::to_string_pretty(self)?;
+
+        sh.write_file(&path, json)?;
+
+        debug!("Saved deployment manifest: {}", path.display());
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn code(&self, name: &str) -> Option<&CodeRecord> {
+        self.codes.get(name)
+    }
+
+    #[must_use]
+    pub fn contract(&self, name: &str) -> Option<&ContractRecord> {
+        self.contracts.get(name)
+    }
+
+    /// Find the name & record of a stored code by the hex encoded sha256 `checksum` of its bytecode
+    #[must_use]
+    pub fn code_by_checksum(&self, checksum: &str) -> Option<(&str, &CodeRecord)> {
+        self.codes
+            .iter()
+            .find(|(_, record)| record.checksum.eq_ignore_ascii_case(checksum))
+            .map(|(name, record)| (name.as_str(), record))
+    }
+
+    /// Find the name & record of a managed contract by its `address`
+    #[must_use]
+    pub fn contract_by_address(&self, address: &str) -> Option<(&str, &ContractRecord)> {
+        self.contracts
+            .iter()
+            .find(|(_, record)| record.address == address)
+            .map(|(name, record)| (name.as_str(), record))
+    }
+
+    pub(crate) fn apply<D>(&mut self, pending: Pending, tx_data: &TxData<D>) -> Result<(), Error> {
+        let tx_hash = tx_data.meta.txhash.clone();
+
+        match pending {
+            Pending::Store {
+                name,
+                checksum,
+                provenance,
+            } => {
+                let code_id = find_attribute(tx_data, "code_id")
+                    .ok_or(Error::ExpectedCodeId)?
+                    .parse()?;
+
+                self.codes.insert(
+                    name,
+                    CodeRecord {
+                        code_id,
+                        checksum,
+                        tx_hash,
+                        provenance,
+                    },
+                );
+            }
+
+            Pending::Instantiate { name, label, admin } => {
+                let address = find_attribute(tx_data, "_contract_address")
+                    .ok_or(Error::ExpectedContractAddress)?
+                    .to_owned();
+
+                let code_id = find_attribute(tx_data, "code_id")
+                    .ok_or(Error::ExpectedCodeId)?
+                    .parse()?;
+
+                self.contracts.insert(
+                    name,
+                    ContractRecord {
+                        address,
+                        code_id,
+                        label,
+                        admin,
+                        tx_hash,
+                    },
+                );
+            }
+
+            Pending::Migrate { contract, code_id } => {
+                if let Some(record) = self.contract_record_mut(&contract) {
+                    record.code_id = code_id.u64();
+                    record.tx_hash = tx_hash;
+                }
+            }
+
+            Pending::UpdateAdmin { contract, admin } => {
+                if let Some(record) = self.contract_record_mut(&contract) {
+                    record.admin = admin;
+                    record.tx_hash = tx_hash;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn contract_record_mut(&mut self, contract: &Contract) -> Option<&mut ContractRecord> {
+        self.contracts
+            .values_mut()
+            .find(|record| record.address == contract.as_str())
+    }
+}
@@ -0,0 +1,369 @@
+//! A harness for E2E tests against a local node, taking care of starting & stopping it around the test body.
+//!
+//! ```ignore
+//! #[test]
+//! fn deploy_on_neutron() -> anyhow::Result<()> {
+//!     cosmwasm_xtask::harness::localnet::<NeutronLocalnet>().run(|sh, network| {
+//!         let code_id = store("artifacts/my_contract.wasm").send(sh, network, &network.keys()[0])?;
+//!         Ok(())
+//!     })
+//! }
+//! ```
+
+use std::{
+    any::TypeId,
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::{mpsc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::JoinHandle,
+};
+
+use xshell::Shell;
+
+use crate::{
+    cli::wait_for_blocks,
+    network::{Clean, Initialize, Isolate, Network, StartLocal, TestIsolation},
+    Error,
+};
+
+/// Held while a local node is running, as every localnet of a kind listens on the same ports
+static LOCALNET: Mutex<()> = Mutex::new(());
+
+/// The shared fixtures, by network type
+static SHARED: Mutex<BTreeMap<TypeId, Slot>> = Mutex::new(BTreeMap::new());
+
+/// Notified whenever a shared fixture leaves the `Starting` state
+static SETTLED: Condvar = Condvar::new();
+
+/// A panicking test poisons a lock, but the node it started is stopped during unwinding
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub struct Localnet<N> {
+    clean_state: bool,
+    wait_for_blocks: bool,
+    _network: PhantomData<N>,
+}
+
+/// Construct a harness running tests against a local `N` node, e.g. `localnet::<NeutronLocalnet>()`
+#[must_use]
+pub fn localnet<N>() -> Localnet<N> {
+    Localnet {
+        clean_state: false,
+        wait_for_blocks: true,
+        _network: PhantomData,
+    }
+}
+
+impl<N> Localnet<N>
+where
+    N: Initialize + Clean,
+    N::Instance: StartLocal,
+{
+    /// Remove any state left by previous runs before starting the node, so the test begins from genesis
+    #[must_use]
+    pub fn clean_state(self, clean_state: bool) -> Self {
+        Self {
+            clean_state,
+            ..self
+        }
+    }
+
+    /// Wait for the node to produce a block before running the test, enabled by default
+    #[must_use]
+    pub fn wait_for_blocks(self, wait_for_blocks: bool) -> Self {
+        Self {
+            wait_for_blocks,
+            ..self
+        }
+    }
+
+    /// Initialize & start the local node, then call `f` with it. The node is stopped once `f` returns or panics.
+    ///
+    /// Runs are serialised within a test binary, so tests using the harness don't need `#[serial]`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Creating the shell fails
+    /// - Cleaning, initializing or starting the network fails
+    /// - Waiting for blocks fails
+    /// - `f` fails
+    pub fn run<F, E>(self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&Shell, &dyn Network) -> Result<(), E>,
+        E: From<Error>,
+    {
+        let _lock = lock(&LOCALNET);
+
+        let sh = Shell::new().map_err(Error::from)?;
+
+        if self.clean_state {
+            N::clean_state(&sh)?;
+        }
+
+        let network = N::initialize(&sh)?;
+
+        let _handle = network.start_local(&sh)?;
+
+        if self.wait_for_blocks {
+            wait_for_blocks(&sh, &network)?;
+        }
+
+        f(&sh, &network)
+    }
+}
+
+impl<N> Localnet<N>
+where
+    N: Isolate,
+    N::Instance: StartLocal,
+{
+    /// Like `run` but the node is namespaced by a unique `TestIsolation`, so runs are not serialised
+    /// & tests can run concurrently with `cargo test`. The isolated state is removed afterwards, so `clean_state` has no effect.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Creating the shell fails
+    /// - Initializing or starting the network fails
+    /// - Waiting for blocks fails
+    /// - `f` fails
+    /// - Removing the isolated state fails
+    pub fn run_isolated<F, E>(self, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&Shell, &dyn Network) -> Result<(), E>,
+        E: From<Error>,
+    {
+        let sh = Shell::new().map_err(Error::from)?;
+
+        let isolation = TestIsolation::unique();
+
+        let res = self.run_in(&sh, &isolation, f);
+
+        N::clean_isolated(&sh, &isolation)?;
+
+        res
+    }
+
+    fn run_in<F, E>(self, sh: &Shell, isolation: &TestIsolation, f: F) -> Result<(), E>
+    where
+        F: FnOnce(&Shell, &dyn Network) -> Result<(), E>,
+        E: From<Error>,
+    {
+        let network = N::initialize_isolated(sh, isolation)?;
+
+        let _handle = network.start_local(sh)?;
+
+        if self.wait_for_blocks {
+            wait_for_blocks(sh, &network)?;
+        }
+
+        f(sh, &network)
+    }
+}
+
+/// A shared fixture is inserted as `Starting` so the `SHARED` lock isn't held while waiting on the node,
+/// which may itself be waiting for another fixture to stop & release `LOCALNET`
+enum Slot {
+    Starting,
+    Running(Fixture),
+}
+
+/// A local node shared by the tests of a binary, stopped by its thread once `stop` is dropped
+struct Fixture {
+    refs: usize,
+    stop: mpsc::Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+/// A reference to a local `N` node shared by every test in the binary holding one, see `TestNet::shared`
+pub struct TestNet<N: Initialize + 'static> {
+    sh: Shell,
+    network: N::Instance,
+}
+
+impl<N> TestNet<N>
+where
+    N: Initialize + 'static,
+    N::Instance: StartLocal,
+{
+    /// Get a reference to the shared local `N` node, initializing & starting it if this is the only reference.
+    /// The node keeps running until the last reference is dropped, so tests holding one at the same time only pay the startup cost once.
+    ///
+    /// Tests share chain state, e.g. account sequences & balances, so should not depend on the state left by others.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Creating the shell fails
+    /// - Initializing or starting the network fails
+    /// - Waiting for blocks fails
+    pub fn shared() -> Result<Self, Error> {
+        Self::acquire()?;
+
+        let initialized = Shell::new()
+            .map_err(Error::from)
+            .and_then(|sh| N::initialize(&sh).map(|network| (sh, network)));
+
+        match initialized {
+            Ok((sh, network)) => Ok(Self { sh, network }),
+            Err(err) => {
+                release::<N>();
+                Err(err)
+            }
+        }
+    }
+
+    /// Take a reference to the running fixture, starting it if there is none
+    fn acquire() -> Result<(), Error> {
+        let id = TypeId::of::<N>();
+
+        let mut shared = lock(&SHARED);
+
+        loop {
+            match shared.get_mut(&id) {
+                Some(Slot::Running(fixture)) => {
+                    fixture.refs += 1;
+                    return Ok(());
+                }
+                Some(Slot::Starting) => {
+                    shared = SETTLED.wait(shared).unwrap_or_else(PoisonError::into_inner);
+                }
+                None => break,
+            }
+        }
+
+        shared.insert(id, Slot::Starting);
+
+        drop(shared);
+
+        let started = Self::start();
+
+        let mut shared = lock(&SHARED);
+
+        let res = match started {
+            Ok(fixture) => {
+                shared.insert(id, Slot::Running(fixture));
+                Ok(())
+            }
+            Err(err) => {
+                shared.remove(&id);
+                Err(err)
+            }
+        };
+
+        SETTLED.notify_all();
+
+        res
+    }
+
+    /// Start the node on its own thread, which owns the node handle until told to stop
+    fn start() -> Result<Fixture, Error> {
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let (stop, stop_rx) = mpsc::channel::<()>();
+
+        let thread = std::thread::spawn(move || {
+            let _lock = lock(&LOCALNET);
+
+            let sh = match Shell::new() {
+                Ok(sh) => sh,
+                Err(err) => {
+                    let _ = ready_tx.send(Err(err.into()));
+                    return;
+                }
+            };
+
+            let started = start_node::<N>(&sh);
+
+            match started {
+                Ok(_handle) => {
+                    let _ = ready_tx.send(Ok(()));
+
+                    // Returns once the `stop` sender is dropped
+                    let _ = stop_rx.recv();
+                }
+                Err(err) => {
+                    let _ = ready_tx.send(Err(err));
+                }
+            }
+        });
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Fixture {
+                refs: 1,
+                stop,
+                thread,
+            }),
+            Ok(Err(err)) => Err(err),
+            Err(_) => {
+                let _ = thread.join();
+                Err(Error::CmdExecute(
+                    "shared localnet thread panicked".to_owned(),
+                ))
+            }
+        }
+    }
+}
+
+/// Initialize & start a local `N` node, waiting for it to produce a block
+fn start_node<N>(sh: &Shell) -> Result<<N::Instance as StartLocal>::Handle<'_>, Error>
+where
+    N: Initialize,
+    N::Instance: StartLocal,
+{
+    let network = N::initialize(sh)?;
+
+    let handle = network.start_local(sh)?;
+
+    wait_for_blocks(sh, &network)?;
+
+    Ok(handle)
+}
+
+impl<N: Initialize + 'static> TestNet<N> {
+    #[must_use]
+    pub fn shell(&self) -> &Shell {
+        &self.sh
+    }
+
+    #[must_use]
+    pub fn network(&self) -> &N::Instance {
+        &self.network
+    }
+}
+
+/// Drop a reference to the shared `N` fixture, stopping its node if it was the last
+fn release<N: 'static>() {
+    let id = TypeId::of::<N>();
+
+    let mut shared = lock(&SHARED);
+
+    let Some(Slot::Running(fixture)) = shared.get_mut(&id) else {
+        return;
+    };
+
+    fixture.refs -= 1;
+
+    if fixture.refs > 0 {
+        return;
+    }
+
+    let Some(Slot::Running(Fixture { stop, thread, .. })) = shared.remove(&id) else {
+        return;
+    };
+
+    drop(shared);
+
+    drop(stop);
+
+    let _ = thread.join();
+}
+
+impl<N: Initialize + 'static> Drop for TestNet<N> {
+    fn drop(&mut self) {
+        release::<N>();
+    }
+}
@@ -0,0 +1,382 @@
+//! Waiting on IBC channels & packets, e.g. for the relayer to finish the channel handshake before a test sends packets over it,
+//! then for the packets sent by a tx to be acknowledged by the counterparty.
+//!
+//! ```ignore
+//! let tx_data = wait_for_tx(&sh, &network, &tx_id)?;
+//!
+//! for packet in ibc::sent_packets(tx_data.meta.events()) {
+//!     ibc::wait_for_ack(&sh, &network, &packet.source, packet.sequence)?;
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+
+use log::debug;
+use sha2::{Digest, Sha256};
+use xshell::Shell;
+
+use crate::{
+    cli::{
+        poll_interval, wait_for_tx, ChannelEnd, ChannelState, Cmd, Event, IbcChannel, RawTxData,
+        TxId,
+    },
+    contract::execute_with_network_gas,
+    key::Key,
+    network::{gas, ChainId, Network, NodeUri},
+    Error,
+};
+
+/// The port of the ICS-20 token transfer module
+pub const TRANSFER_PORT: &str = "transfer";
+
+/// How long to wait for a channel to open by default, enough for the relayer to create the clients & connection
+/// & complete the four step channel handshake on freshly started localnets
+pub const DEFAULT_CHANNEL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// How long to wait for a packet to be acknowledged by default, covering a relayer clearing packets on its own interval
+pub const DEFAULT_ACK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Keep querying the channels bound to `port` until one to the `counterparty_chain` is open, responding with it
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Querying the channels or their client states fails
+/// - No such channel is open within `timeout`
+pub fn wait_for_channel(
+    sh: &Shell,
+    network: &dyn Network,
+    port: &str,
+    counterparty_chain: &ChainId,
+    timeout: Duration,
+) -> Result<IbcChannel, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    wait_for_channel_fn(
+        || network.cli(sh),
+        &node_uri,
+        port,
+        counterparty_chain,
+        timeout,
+    )
+}
+
+pub(crate) fn wait_for_channel_fn<'a, F>(
+    cli_fn: F,
+    node_uri: &NodeUri,
+    port: &str,
+    counterparty_chain: &ChainId,
+    timeout: Duration,
+) -> Result<IbcChannel, Error>
+where
+    F: Fn() -> Result<Cmd<'a>, Error>,
+{
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        for channel in cli_fn()?.query(node_uri).ibc_channels()? {
+            if channel.port_id != port || channel.state != ChannelState::Open {
+                continue;
+            }
+
+            let chain_id = cli_fn()?
+                .query(node_uri)
+                .ibc_channel_chain_id(port, &channel.channel_id)?;
+
+            if chain_id == *counterparty_chain {
+                debug!(
+                    "Channel {} on port {port} to {counterparty_chain} is open",
+                    channel.channel_id
+                );
+
+                return Ok(channel);
+            }
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::ChannelTimeout {
+                port: port.to_owned(),
+                counterparty_chain: counterparty_chain.to_string(),
+            });
+        }
+
+        std::thread::sleep(poll_interval());
+    }
+}
+
+/// A packet sent by a tx, identified on the sending chain by its `source` channel end & `sequence`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub sequence: u64,
+    pub source: ChannelEnd,
+    pub destination: ChannelEnd,
+}
+
+/// The packets sent by a tx, from the `send_packet` events among its `events`, e.g. `tx_data.meta.events()` or `&tx_result.events`
+pub fn sent_packets<'a, I>(events: I) -> Vec<Packet>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    events
+        .into_iter()
+        .filter(|event| event.r#type == "send_packet")
+        .filter_map(|event| {
+            let attr = |key: &str| {
+                event
+                    .attributes
+                    .iter()
+                    .find(|attr| attr.key == key)
+                    .map(|attr| attr.value.clone())
+            };
+
+            Some(Packet {
+                sequence: attr("packet_sequence")?.parse().ok()?,
+                source: ChannelEnd {
+                    port_id: attr("packet_src_port")?,
+                    channel_id: attr("packet_src_channel")?,
+                },
+                destination: ChannelEnd {
+                    port_id: attr("packet_dst_port")?,
+                    channel_id: attr("packet_dst_channel")?,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Wait for the packet sent over the `channel` with `sequence` to be acknowledged, giving up after `DEFAULT_ACK_TIMEOUT`.
+/// Responds with the ID of the relayer tx delivering the acknowledgement.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Searching for the acknowledgement fails
+/// - The packet timed out instead of being received
+/// - The packet is not acknowledged within `DEFAULT_ACK_TIMEOUT`
+pub fn wait_for_ack(
+    sh: &Shell,
+    network: &dyn Network,
+    channel: &ChannelEnd,
+    sequence: u64,
+) -> Result<TxId, Error> {
+    wait_for_ack_with_timeout(sh, network, channel, sequence, DEFAULT_ACK_TIMEOUT)
+}
+
+/// Keep searching the sending chain for the acknowledgement of the packet sent over the `channel` with `sequence` until `timeout` elapses
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Searching for the acknowledgement fails
+/// - The packet timed out instead of being received
+/// - The packet is not acknowledged within `timeout`
+pub fn wait_for_ack_with_timeout(
+    sh: &Shell,
+    network: &dyn Network,
+    channel: &ChannelEnd,
+    sequence: u64,
+    timeout: Duration,
+) -> Result<TxId, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let acked = packet_txs(
+            sh,
+            network,
+            &node_uri,
+            "acknowledge_packet",
+            channel,
+            sequence,
+        )?;
+
+        if let Some(tx) = acked.first() {
+            debug!(
+                "Packet {sequence} on {}/{} acknowledged in tx {}",
+                channel.port_id, channel.channel_id, tx.meta.txhash
+            );
+
+            return Ok(TxId::from(tx.meta.txhash.clone()));
+        }
+
+        let timed_out = packet_txs(sh, network, &node_uri, "timeout_packet", channel, sequence)?;
+
+        if !timed_out.is_empty() {
+            return Err(Error::PacketTimedOut {
+                port: channel.port_id.clone(),
+                channel: channel.channel_id.clone(),
+                sequence,
+            });
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::AckTimeout {
+                port: channel.port_id.clone(),
+                channel: channel.channel_id.clone(),
+                sequence,
+            });
+        }
+
+        std::thread::sleep(poll_interval());
+    }
+}
+
+/// Search for the txs emitting an `event_type` event for the packet sent over the `channel` with `sequence`
+fn packet_txs(
+    sh: &Shell,
+    network: &dyn Network,
+    node_uri: &NodeUri,
+    event_type: &str,
+    channel: &ChannelEnd,
+    sequence: u64,
+) -> Result<Vec<RawTxData>, Error> {
+    let src_port = format!("{event_type}.packet_src_port");
+    let src_channel = format!("{event_type}.packet_src_channel");
+    let packet_sequence = format!("{event_type}.packet_sequence");
+    let sequence = sequence.to_string();
+
+    network.cli(sh)?.query(node_uri).txs(&[
+        (&src_port, &channel.port_id),
+        (&src_channel, &channel.channel_id),
+        (&packet_sequence, &sequence),
+    ])
+}
+
+/// Tokens received on the destination chain of a transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Received {
+    /// The voucher denom, e.g. `ibc/27394FB0...`, or the native denom when a voucher was sent back to its origin
+    pub denom: String,
+    pub amount: u128,
+}
+
+/// Transfer `amount` of `denom` over the transfer `channel` of `from_network` from `key` to the key of the same name on `to_network`,
+/// e.g. `local1` on both chains of the Neutron localnet. Waits for the packet to be acknowledged, then for the tokens to be received.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - There is no key with the same name on `to_network`
+/// - The `channel` is not a transfer channel of `from_network`
+/// - Command execution fails
+/// - The packet timed out or was not acknowledged, see `wait_for_ack`
+/// - The recipient balance does not increase by `amount` within `DEFAULT_ACK_TIMEOUT` of the acknowledgement, e.g. the transfer failed on the destination chain & was refunded
+pub fn transfer_and_wait(
+    sh: &Shell,
+    from_network: &dyn Network,
+    to_network: &dyn Network,
+    channel: &str,
+    key: &Key,
+    amount: u128,
+    denom: &str,
+) -> Result<Received, Error> {
+    let recipient = to_network.require(key.name())?.address();
+
+    let from_node_uri = from_network.node_uri(sh)?;
+
+    let to_node_uri = to_network.node_uri(sh)?;
+
+    let counterparty = from_network
+        .cli(sh)?
+        .query(&from_node_uri)
+        .ibc_channels()?
+        .into_iter()
+        .find(|c| c.port_id == TRANSFER_PORT && c.channel_id == channel)
+        .ok_or_else(|| Error::UnknownChannel(channel.to_owned()))?
+        .counterparty;
+
+    let trace = match denom.strip_prefix("ibc/") {
+        Some(hash) => from_network
+            .cli(sh)?
+            .query(&from_node_uri)
+            .denom_trace(hash)?,
+        None => denom.to_owned(),
+    };
+
+    let received_denom = received_denom(&trace, channel, &counterparty.channel_id);
+
+    let balance = || {
+        to_network
+            .cli(sh)?
+            .query(&to_node_uri)
+            .balance(recipient, &received_denom)
+    };
+
+    let before = balance()?;
+
+    debug!(
+        "Transferring {amount}{denom} over {channel} to {recipient}, expecting {received_denom}"
+    );
+
+    let chain_id = from_network.chain_id();
+
+    let cmd = from_network
+        .cli(sh)?
+        .tx(key, &chain_id, &from_node_uri)
+        .ibc_transfer(channel, recipient, amount, denom);
+
+    let tx_id = execute_with_network_gas(cmd, from_network, gas::DEFAULT_ADJUSTMENT)?;
+
+    let tx_data = wait_for_tx(sh, from_network, &tx_id)?;
+
+    let packet = sent_packets(tx_data.meta.events())
+        .into_iter()
+        .find(|packet| packet.source.port_id == TRANSFER_PORT)
+        .ok_or_else(|| Error::ExpectedPacket(TRANSFER_PORT.to_owned()))?;
+
+    wait_for_ack(sh, from_network, &packet.source, packet.sequence)?;
+
+    let deadline = Instant::now() + DEFAULT_ACK_TIMEOUT;
+
+    loop {
+        let received = balance()?.saturating_sub(before);
+
+        if received >= amount {
+            return Ok(Received {
+                denom: received_denom,
+                amount: received,
+            });
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::TransferNotReceived {
+                recipient: recipient.to_owned(),
+                denom: received_denom,
+            });
+        }
+
+        std::thread::sleep(poll_interval());
+    }
+}
+
+/// The denom received for a token with the full denom `trace` sent over `channel`, following ICS-20:
+/// a voucher returning over the channel it arrived on is unwound, anything else gains a hop through the `counterparty_channel`.
+/// A trace without any leading `port/channel-N` hops is a native denom & is received as is, even if it contains a `/`,
+/// e.g. the tokenfactory denom `factory/<creator>/<subdenom>`.
+#[must_use]
+pub fn received_denom(trace: &str, channel: &str, counterparty_channel: &str) -> String {
+    let trace = match trace.strip_prefix(&format!("{TRANSFER_PORT}/{channel}/")) {
+        Some(unwound) => unwound.to_owned(),
+        None => format!("{TRANSFER_PORT}/{counterparty_channel}/{trace}"),
+    };
+
+    if !has_hops(&trace) {
+        return trace;
+    }
+
+    let hash = Sha256::digest(trace.as_bytes());
+
+    format!("ibc/{}", hex::encode_upper(hash))
+}
+
+/// Whether the denom `trace` starts with a `port/channel-N` hop followed by a base denom
+fn has_hops(trace: &str) -> bool {
+    let mut segments = trace.splitn(3, '/');
+
+    matches!(
+        (segments.next(), segments.next(), segments.next()),
+        (Some(port), Some(channel), Some(base))
+            if !port.is_empty() && channel.starts_with("channel-") && !base.is_empty()
+    )
+}
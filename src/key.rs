@@ -3,25 +3,105 @@ use std::{
     hash::{Hash, Hasher},
 };
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use bip39::Mnemonic;
 use derive_more::Display;
+use k256::ecdsa::{
+    signature::{Signer, Verifier},
+    Signature, SigningKey, VerifyingKey,
+};
 use nanorand::{Rng, WyRand};
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use xshell::Shell;
 
-use crate::Error;
+use crate::{network::Network, Error};
 
 #[derive(Debug, Display, Deserialize, Copy, Clone, PartialEq, Eq)]
 pub enum KeyringBackend {
     Os,
     Test,
+    /// Encrypted files, unlocked with the passphrase in the `COSMWASM_KEYRING_PASSPHRASE` environment variable
+    File,
+    Pass,
+    Memory,
 }
 
+/// The environment variable holding the passphrase for the `file` keyring backend
+pub const KEYRING_PASSPHRASE_ENV: &str = "COSMWASM_KEYRING_PASSPHRASE";
+
 impl KeyringBackend {
     #[must_use]
     pub fn as_str(&self) -> &str {
         match self {
             KeyringBackend::Os => "os",
             KeyringBackend::Test => "test",
+            KeyringBackend::File => "file",
+            KeyringBackend::Pass => "pass",
+            KeyringBackend::Memory => "memory",
+        }
+    }
+
+    /// The stdin answering the keyring passphrase prompts of the `file` backend, if the passphrase is set.
+    ///
+    /// The passphrase is given `prompts` times, the CLI asks twice when creating the keyring & once after.
+    #[must_use]
+    pub fn passphrase_input(&self, prompts: usize) -> Option<String> {
+        if *self != KeyringBackend::File {
+            return None;
+        }
+
+        let passphrase = std::env::var(KEYRING_PASSPHRASE_ENV).ok()?;
+
+        Some(format!("{passphrase}\n").repeat(prompts))
+    }
+}
+
+/// What a key is used for on the network
+#[derive(Debug, Default, Display, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum KeyRole {
+    /// A key added or recovered by the user
+    #[default]
+    User,
+    /// A well funded localnet key for demos & tests
+    Demo,
+    /// A localnet validator operator key
+    Validator,
+    /// A key used by a relayer, e.g. Hermes or the ICQ relayer, to pay for relaying txs
+    Relayer,
+}
+
+/// The SLIP-44 coin type used by most Cosmos SDK chains
+pub const COIN_TYPE_COSMOS: u32 = 118;
+
+/// The SLIP-44 coin type used by Ethereum compatible chains, e.g. Injective & Evmos
+pub const COIN_TYPE_ETH: u32 = 60;
+
+/// The SLIP-44 coin type used by Terra
+pub const COIN_TYPE_TERRA: u32 = 330;
+
+/// How a key is derived from its mnemonic
+#[derive(Debug, Default, Deserialize, Clone, PartialEq, Eq)]
+pub enum Derivation {
+    /// The chain CLI's default derivation path
+    #[default]
+    Default,
+    /// The BIP-44 path `m/44'/<coin type>'/0'/0/0`
+    CoinType(u32),
+    /// A full BIP-44 path, e.g. `m/44'/60'/0'/0/0`
+    HdPath(String),
+}
+
+impl Derivation {
+    /// The CLI arguments selecting this derivation
+    #[must_use]
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Derivation::Default => vec![],
+            Derivation::CoinType(coin_type) => {
+                vec!["--coin-type".to_owned(), coin_type.to_string()]
+            }
+            Derivation::HdPath(path) => vec!["--hd-path".to_owned(), path.clone()],
         }
     }
 }
@@ -31,6 +111,9 @@ impl KeyringBackend {
 pub struct Raw {
     name: String,
     address: String,
+    /// The kind of key, e.g. `local` or `ledger`
+    #[serde(rename = "type", default)]
+    key_type: String,
 }
 
 impl Raw {
@@ -46,7 +129,12 @@ impl Raw {
 
     #[must_use]
     pub fn with_backend(self, backend: KeyringBackend) -> Key {
-        Key { raw: self, backend }
+        Key {
+            raw: self,
+            backend,
+            derivation: Derivation::Default,
+            role: KeyRole::default(),
+        }
     }
 }
 
@@ -55,6 +143,10 @@ impl Raw {
 pub struct Key {
     raw: Raw,
     backend: KeyringBackend,
+    #[serde(default)]
+    derivation: Derivation,
+    #[serde(default)]
+    role: KeyRole,
 }
 
 impl Key {
@@ -72,22 +164,219 @@ impl Key {
     pub fn backend(&self) -> &str {
         self.backend.as_str()
     }
+
+    /// Export the private key, armored & encrypted with `passphrase`, for importing elsewhere with `Keys::import`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the export command fails.
+    pub fn export(
+        &self,
+        sh: &Shell,
+        network: &dyn Network,
+        passphrase: &str,
+    ) -> Result<String, Error> {
+        network
+            .cli(sh)?
+            .export_key(self.name(), self.backend, passphrase)
+    }
+
+    /// The address of the key on a chain with the given bech32 `prefix`, only valid for chains sharing the key's coin type
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key's address is not valid bech32.
+    pub fn address_for_prefix(&self, prefix: &str) -> Result<String, Error> {
+        convert_prefix(self.address(), prefix)
+    }
+
+    #[must_use]
+    pub fn keyring_backend(&self) -> KeyringBackend {
+        self.backend
+    }
+
+    /// Whether the key is held on a Ledger hardware wallet, txs from it are signed on the device
+    #[must_use]
+    pub fn is_ledger(&self) -> bool {
+        self.raw.key_type == "ledger"
+    }
+
+    /// Whether only the public key is held in the keyring, txs from it are signed by the network's remote signer
+    #[must_use]
+    pub fn is_offline(&self) -> bool {
+        self.raw.key_type == "offline"
+    }
+
+    #[must_use]
+    pub fn derivation(&self) -> &Derivation {
+        &self.derivation
+    }
+
+    #[must_use]
+    pub fn with_derivation(self, derivation: Derivation) -> Self {
+        Self { derivation, ..self }
+    }
+
+    #[must_use]
+    pub fn role(&self) -> KeyRole {
+        self.role
+    }
+
+    #[must_use]
+    pub fn with_role(self, role: KeyRole) -> Self {
+        Self { role, ..self }
+    }
 }
 
-/// Generate a BIP-39 Mnemonic string using entropy from the operating system
-/// to seed the RNG.
+/// Re-encode a bech32 `address` with `new_prefix`, e.g. to get the Gaia address of a Neutron account
 ///
-/// WARNING: Do not use for real wallets.
+/// # Errors
+///
+/// This function will return an error if:
+/// - The `address` is not valid bech32
+/// - The `new_prefix` is not a valid bech32 human readable part
+pub fn convert_prefix(address: &str, new_prefix: &str) -> Result<String, Error> {
+    let (_, data, variant) = bech32::decode(address)?;
+
+    bech32::encode(new_prefix, data, variant).map_err(Error::from)
+}
+
+/// A public key as output by the CLI, e.g. `{"@type":"/cosmos.crypto.secp256k1.PubKey","key":"<base64>"}`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    #[serde(rename = "@type")]
+    pub type_url: String,
+    /// The base64 encoded key bytes, empty for multisig keys
+    #[serde(default)]
+    pub key: String,
+}
+
+impl PublicKey {
+    /// The signing algorithm of the key taken from its type URL, e.g. `secp256k1`
+    #[must_use]
+    pub fn algo(&self) -> &str {
+        self.type_url.rsplit('.').nth(1).unwrap_or_default()
+    }
+}
+
+/// The details of a key in the keyring
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub address: String,
+    #[serde(deserialize_with = "deserialize_pubkey")]
+    pub pubkey: PublicKey,
+}
+
+/// The CLI outputs the public key as a JSON encoded string, older versions as an object
+fn deserialize_pubkey<'de, D>(deserializer: D) -> Result<PublicKey, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawPublicKey {
+        Json(String),
+        Object(PublicKey),
+    }
+
+    match RawPublicKey::deserialize(deserializer)? {
+        RawPublicKey::Json(json) => serde_json::from_str(&json).map_err(serde::de::Error::custom),
+        RawPublicKey::Object(pubkey) => Ok(pubkey),
+    }
+}
+
+/// The signing algorithm supported by `sign` & `verify`
+const SECP256K1: &str = "secp256k1";
+
+/// Sign `bytes` with the private key of `key`, returning the 64 byte `r || s` signature over their SHA-256 hash.
+/// Matches `secp256k1_verify` in contracts, e.g. for verifying airdrop claims signed off-chain.
+///
+/// The private key is exported from the keyring & only held in memory while signing.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The key is held on a Ledger, its private key cannot be exported
+/// - The key export command fails
+/// - The exported private key is invalid
+pub fn sign(sh: &Shell, network: &dyn Network, key: &Key, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    if key.is_ledger() {
+        return Err(Error::UnsupportedSigningKey("ledger".to_owned()));
+    }
+
+    let private_key = hex::decode(network.cli(sh)?.export_key_hex(key.name(), key.backend)?)?;
+
+    let signing_key = SigningKey::from_slice(&private_key)?;
+
+    let signature: Signature = signing_key.sign(bytes);
+
+    Ok(signature.to_bytes().to_vec())
+}
+
+/// Verify a `signature` made with `sign` over `bytes` against `pubkey`, see `Cmd::show_key` to obtain it.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The key is not a `secp256k1` key, e.g. an Ethereum style key
+/// - The public key or signature are malformed
+pub fn verify(pubkey: &PublicKey, signature: &[u8], bytes: &[u8]) -> Result<bool, Error> {
+    if pubkey.algo() != SECP256K1 {
+        return Err(Error::UnsupportedSigningKey(pubkey.algo().to_owned()));
+    }
+
+    let verifying_key = VerifyingKey::from_sec1_bytes(&BASE64.decode(&pubkey.key)?)?;
+
+    let signature = Signature::from_slice(signature)?;
+
+    Ok(verifying_key.verify(bytes, &signature).is_ok())
+}
+
+/// The number of words in a generated mnemonic
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MnemonicStrength {
+    /// 12 words from 128 bits of entropy
+    #[default]
+    Words12,
+    /// 24 words from 256 bits of entropy
+    Words24,
+}
+
+impl MnemonicStrength {
+    #[must_use]
+    pub fn entropy_bytes(self) -> usize {
+        match self {
+            MnemonicStrength::Words12 => 16,
+            MnemonicStrength::Words24 => 32,
+        }
+    }
+}
+
+/// Generate a 12 word BIP-39 Mnemonic string using entropy from the operating system
 ///
 /// # Errors
 ///
 /// This function will return an error if:
+/// - The operating system fails to provide entropy
+/// - Building the mnemonic fails
 pub fn generate_mnemonic() -> Result<String, Error> {
-    let mut rng = WyRand::new();
+    generate_mnemonic_with_strength(MnemonicStrength::default())
+}
 
-    let mut bytes = [0u8; 16];
+/// Generate a BIP-39 Mnemonic string of the given `strength` using entropy from the operating system
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The operating system fails to provide entropy
+/// - Building the mnemonic fails
+pub fn generate_mnemonic_with_strength(strength: MnemonicStrength) -> Result<String, Error> {
+    let mut bytes = vec![0u8; strength.entropy_bytes()];
 
-    rng.fill_bytes(&mut bytes);
+    getrandom::getrandom(&mut bytes)?;
 
     let mnemomic = Mnemonic::from_entropy(&bytes)?;
 
@@ -96,7 +385,7 @@ pub fn generate_mnemonic() -> Result<String, Error> {
 
 /// Generate a BIP-39 Mnemonic string using the provided `seed` for the RNG
 ///
-/// WARNING: Do not use for real wallets.
+/// WARNING: Test only, the mnemonic is predictable from the `seed`. Do not use for real wallets.
 ///
 /// # Errors
 ///
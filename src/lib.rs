@@ -21,29 +21,214 @@ pub enum Error {
     #[error(transparent)]
     ParseProtobuf(#[from] prost::DecodeError),
     #[error(transparent)]
+    ParseBase64(#[from] base64::DecodeError),
+    #[error(transparent)]
+    ParseBech32(#[from] bech32::Error),
+    #[error("expected address with prefix {expected} but found {found}")]
+    UnexpectedAddressPrefix { expected: String, found: String },
+    #[error(transparent)]
+    Entropy(#[from] getrandom::Error),
+    #[error(transparent)]
     StdIo(#[from] std::io::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
     #[error("{0}")]
     CmdExecute(String),
     #[error("{0}")]
-    TxExecute(String),
+    TxExecute(Box<cli::TxFailure>),
+    #[error("timed out waiting for tx {0} to be included in a block")]
+    TxTimeout(cli::TxId),
+    #[error("tx {0} not found after {1} queries")]
+    TxNotFound(cli::TxId, u32),
+    #[error("expected gas estimate in simulation output")]
+    ExpectedGasEstimate,
+    #[error("wasm check failed: {0}")]
+    WasmCheck(String),
+    #[error("invalid wasm bytecode: {0}")]
+    InvalidWasm(String),
     #[error("expected code id")]
     ExpectedCodeId,
     #[error("expected at least one message response in tx data")]
     ExpectedAtLeastOneMsgResponse,
+    #[error("expected proposal id")]
+    ExpectedProposalId,
+    #[error("proposal {0} was rejected or failed")]
+    ProposalRejected(u64),
+    #[error("timed out waiting for proposal {0} to pass")]
+    ProposalTimeout(u64),
+    #[error("timed out waiting for a channel on port {port} to {counterparty_chain} to open")]
+    ChannelTimeout {
+        port: String,
+        counterparty_chain: String,
+    },
+    #[error("timed out waiting for packet {sequence} on {port}/{channel} to be acknowledged")]
+    AckTimeout {
+        port: String,
+        channel: String,
+        sequence: u64,
+    },
+    #[error("expected interchain query id")]
+    ExpectedQueryId,
+    #[error("timed out waiting for a result of interchain query {0}")]
+    QueryResultTimeout(u64),
+    #[error("no transfer channel {0}")]
+    UnknownChannel(String),
+    #[error("{recipient} did not receive the transferred {denom}")]
+    TransferNotReceived { recipient: String, denom: String },
+    #[error("expected a packet sent from port {0}")]
+    ExpectedPacket(String),
+    #[error("timed out waiting for interchain account {interchain_account_id} of {owner} to be registered")]
+    IcaTimeout {
+        owner: String,
+        interchain_account_id: String,
+    },
+    #[error("packet {sequence} on {port}/{channel} timed out before it was received")]
+    PacketTimedOut {
+        port: String,
+        channel: String,
+        sequence: u64,
+    },
+    #[error("{address} has {balance}{denom} but at least {required}{denom} is required")]
+    InsufficientBalance {
+        address: String,
+        denom: String,
+        balance: u128,
+        required: u128,
+    },
+    #[error("no network given & no default network in xtask.toml")]
+    MissingNetwork,
+    #[error("module {0} is missing from the genesis")]
+    MissingGenesisModule(String),
+    #[error("genesis preset {preset} sets {param} which is missing from the genesis")]
+    MissingGenesisParam { preset: String, param: String },
+    #[error("unknown genesis preset {0}")]
+    UnknownGenesisPreset(String),
+    #[error("unknown network {0}")]
+    UnknownNetwork(String),
+    #[error("dry run, not executed: {0}")]
+    DryRun(String),
+    #[error("deploy aborted")]
+    WizardAborted,
+    #[error("{0} is not a local network")]
+    NotLocalNetwork(String),
+    #[error("unsupported host: {0}")]
+    UnsupportedHost(String),
+    #[error("environment variable {0} is not set")]
+    MissingEnvVar(String),
+    #[error("environment variable {name} has invalid value {value}")]
+    InvalidEnvVar { name: String, value: String },
+    #[error("key {0} is offline but the network has no remote signer")]
+    MissingSigner(String),
+    #[error("key {0} not found")]
+    KeyNotFound(String),
+    #[error("the network has no demo keys")]
+    NoDemoKeys,
+    #[error("no key to deploy to {0} with, set `deployer` in xtask.toml or recover a key")]
+    NoDeployer(String),
+    #[error("expected armored private key in export output")]
+    ExpectedArmoredKey,
+    #[error("expected hex private key in export output")]
+    ExpectedPrivateKey,
+    #[error("unsupported key for signing: {0}")]
+    UnsupportedSigningKey(String),
+    #[error(transparent)]
+    Signature(#[from] k256::ecdsa::Error),
+    #[error(
+        "artifact {artifact} has checksum {found} but {expected} is recorded in checksums.txt"
+    )]
+    ArtifactChecksumMismatch {
+        artifact: String,
+        expected: String,
+        found: String,
+    },
+    #[error("{tool} is at {found} but {locked} is recorded in tools.lock")]
+    ToolVersionDrift {
+        tool: String,
+        locked: String,
+        found: String,
+    },
+    #[error("{path} has checksum {found} but {expected} was expected")]
+    ChecksumMismatch {
+        path: String,
+        expected: String,
+        found: String,
+    },
+    #[error("the environment cannot run the network, see the doctor report")]
+    UnhealthyEnvironment,
+    #[error("node state remains at {0} after cleaning, its genesis cannot be replaced")]
+    StaleState(String),
+    #[error("no genesis snapshot at {0}, clean the network state & initialize it again")]
+    MissingGenesisSnapshot(String),
+    #[error("required tool {0} is not installed")]
+    MissingTool(String),
+    #[error("expected a package name in {0}/Cargo.toml")]
+    ExpectedPackageName(String),
+    #[error("expected contract address")]
+    ExpectedContractAddress,
+    #[error("no contract named {0} in the deployment manifest")]
+    UnknownContract(String),
+    #[error("unsupported deployment manifest version {0}")]
+    UnsupportedManifestVersion(u32),
+    #[error("code id {code_id} has checksum {on_chain} but the local wasm has checksum {local}")]
+    CodeChecksumMismatch {
+        code_id: u64,
+        local: String,
+        on_chain: String,
+    },
 }
 
+impl Error {
+    /// How the chain rejected a tx, if this is a chain failure. Failed commands are classified from their output,
+    /// e.g. a gas simulation which ran out of gas.
+    #[must_use]
+    pub fn chain_error(&self) -> Option<cli::ChainError> {
+        match self {
+            Self::TxExecute(failure) => Some(failure.kind.clone()),
+            Self::CmdExecute(output) => match cli::ChainError::from_log(output) {
+                cli::ChainError::Unknown => None,
+                known => Some(known),
+            },
+            _ => None,
+        }
+    }
+}
+
+pub mod broadcast;
 pub mod cli;
+#[cfg(feature = "cli-app")]
+pub mod cli_app;
+pub mod client;
+pub mod config;
 pub mod contract;
+pub mod deploy;
+pub mod harness;
+pub mod ibc;
 pub mod key;
+pub mod metrics;
 pub mod network;
 pub mod ops;
+pub mod output;
+mod progress;
+pub mod upgrade;
+#[cfg(feature = "wizard")]
+pub mod wizard;
 
-pub use cli::wait_for_blocks;
-pub use contract::{execute, instantiate, query, store};
+#[doc(hidden)]
+pub mod __private {
+    pub use xshell::Shell;
+}
+
+pub use cli::{wait_for_blocks, wait_for_height, wait_for_n_blocks};
+pub use config::Config;
+pub use contract::{
+    by_name, execute, instantiate, migrate, query, query_raw, store, store_and_instantiate,
+    store_many, store_via_gov,
+};
 pub use network::{
     archway::{CmdExt as ArchwayCmdExt, Local as ArchwayLocalnet},
     gas::Prices as GasPrices,
     neutron::local::Local as NeutronLocalnet,
     neutron::testnet::Testnet as NeutronTestnet,
-    Initialize, IntoForeground, Keys, Network, StartLocal,
+    Initialize, IntoForeground, Isolate, Keys, Network, ResetState, ResourceLimits, StartLocal,
+    TestIsolation,
 };
@@ -0,0 +1,152 @@
+//! Wall-clock timing of builds, txs & waits, summarised to track CI time regressions
+
+use std::{
+    fmt::Display,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+static STEPS: Mutex<Vec<Step>> = Mutex::new(Vec::new());
+
+/// The number of slowest steps listed by the `Summary` display
+pub const SLOWEST_STEPS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepKind {
+    Build,
+    Store,
+    Instantiate,
+    Tx,
+    Wait,
+}
+
+impl Display for StepKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            StepKind::Build => "build",
+            StepKind::Store => "store",
+            StepKind::Instantiate => "instantiate",
+            StepKind::Tx => "tx",
+            StepKind::Wait => "wait",
+        };
+
+        f.pad(kind)
+    }
+}
+
+/// A timed operation, with the gas used if it was a tx
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub kind: StepKind,
+    pub label: String,
+    pub duration: Duration,
+    pub gas_used: Option<u128>,
+}
+
+fn steps() -> std::sync::MutexGuard<'static, Vec<Step>> {
+    STEPS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Record a completed `step`, to be included in the next `summary`
+pub fn record(step: Step) {
+    steps().push(step);
+}
+
+/// Time `f`, recording it as a step of `kind` if it succeeds
+pub(crate) fn timed<T, E, F>(kind: StepKind, label: &str, f: F) -> Result<T, E>
+where
+    F: FnOnce() -> Result<T, E>,
+{
+    let started = Instant::now();
+
+    let res = f()?;
+
+    record(Step {
+        kind,
+        label: label.to_owned(),
+        duration: started.elapsed(),
+        gas_used: None,
+    });
+
+    Ok(res)
+}
+
+/// Summarise the steps recorded so far
+#[must_use]
+pub fn summary() -> Summary {
+    Summary {
+        steps: steps().clone(),
+    }
+}
+
+/// Clear the recorded steps, e.g. between test cases
+pub fn reset() {
+    steps().clear();
+}
+
+/// The steps recorded by a run, in the order they completed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub steps: Vec<Step>,
+}
+
+impl Summary {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The gas used by all the recorded txs
+    #[must_use]
+    pub fn total_gas(&self) -> u128 {
+        self.steps.iter().filter_map(|step| step.gas_used).sum()
+    }
+
+    /// The sum of the step durations
+    #[must_use]
+    pub fn total_time(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    /// Up to `n` steps, slowest first
+    #[must_use]
+    pub fn slowest(&self, n: usize) -> Vec<&Step> {
+        let mut steps: Vec<_> = self.steps.iter().collect();
+
+        steps.sort_by_key(|step| std::cmp::Reverse(step.duration));
+
+        steps.truncate(n);
+
+        steps
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} steps in {:.1?}, {} gas used",
+            self.steps.len(),
+            self.total_time(),
+            self.total_gas()
+        )?;
+
+        for step in self.slowest(SLOWEST_STEPS) {
+            write!(
+                f,
+                "  {:>8.1?} {:<11} {}",
+                step.duration, step.kind, step.label
+            )?;
+
+            if let Some(gas_used) = step.gas_used {
+                write!(f, " ({gas_used} gas)")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
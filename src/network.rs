@@ -2,18 +2,32 @@ use derive_more::{Display, From, FromStr};
 use xshell::Shell;
 
 use crate::{
+    broadcast::{Broadcaster, CliBroadcaster, Signer, TxEvent, TxHook},
     cli::Cli,
-    key::{Key, KeyringBackend},
+    key::{Key, KeyRole, KeyringBackend},
     Error,
 };
 
 pub mod archway;
+pub mod fork;
+pub mod genesis;
 
 pub mod neutron {
+    pub mod ica;
+    pub mod icq;
     pub mod local;
     pub mod testnet;
 }
 
+/// The capabilities enabled by a stock `wasmd` chain
+pub const DEFAULT_WASM_CAPABILITIES: &[&str] = &[
+    "iterator",
+    "staking",
+    "stargate",
+    "cosmwasm_1_1",
+    "cosmwasm_1_2",
+];
+
 #[derive(Debug, Display, From, Clone)]
 pub struct NodeUri(String);
 
@@ -24,7 +38,7 @@ impl NodeUri {
     }
 }
 
-#[derive(Debug, Display, From, FromStr, Clone)]
+#[derive(Debug, Display, From, FromStr, Clone, PartialEq, Eq)]
 pub struct ChainId(String);
 
 impl ChainId {
@@ -75,6 +89,9 @@ pub mod gas {
         pub price: Price,
     }
 
+    /// The default factor a simulated gas estimate is scaled by
+    pub const DEFAULT_ADJUSTMENT: f64 = 1.3;
+
     pub trait Prices {
         fn low_gas_price(&self) -> Price;
 
@@ -93,11 +110,61 @@ pub trait Node {
     fn node_uri(&self, sh: &Shell) -> Result<NodeUri, Error>;
 
     fn chain_id(&self) -> ChainId;
+
+    /// Whether the node can simulate txs to estimate their gas usage
+    fn supports_gas_simulation(&self) -> bool {
+        true
+    }
+
+    /// The name of a well funded key which can top up other accounts, only available on localnets
+    fn faucet(&self) -> Option<&str> {
+        None
+    }
+
+    /// The backend used to sign & broadcast txs, the network's CLI by default
+    fn broadcaster(&self) -> &dyn Broadcaster {
+        &CliBroadcaster
+    }
+
+    /// The wasm capabilities enabled on the chain, used to validate WASM bytecode before it is stored
+    fn wasm_capabilities(&self) -> &[&str] {
+        DEFAULT_WASM_CAPABILITIES
+    }
+
+    /// Where to look for the node's own account of a failed tx, only available on localnets
+    fn logs_hint(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait Keys: Cli {
     fn keys(&self) -> &[Key];
 
+    /// Find the network's key with the given name or address
+    fn get(&self, name_or_address: &str) -> Option<&Key> {
+        self.keys()
+            .iter()
+            .find(|key| key.name() == name_or_address || key.address() == name_or_address)
+    }
+
+    /// Find the network's key with the given name or address, see `Keys::get`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no such key.
+    fn require(&self, name_or_address: &str) -> Result<&Key, Error> {
+        self.get(name_or_address)
+            .ok_or_else(|| Error::KeyNotFound(name_or_address.to_owned()))
+    }
+
+    /// The network's keys with the given `role`, e.g. the relayer keys on a localnet to check their balances
+    fn keys_with_role(&self, role: KeyRole) -> Vec<&Key> {
+        self.keys()
+            .iter()
+            .filter(|key| key.role() == role)
+            .collect()
+    }
+
     /// Recover a key with the given `mnemonic` & add it to the network's keys as `name` in the given `backend`.
     ///
     /// # Errors
@@ -110,11 +177,62 @@ pub trait Keys: Cli {
         mnemonic: &str,
         backend: KeyringBackend,
     ) -> Result<Key, Error>;
+
+    /// Recover a key with the mnemonic held in the environment variable `var` & add it to the network's keys as `name` in the given `backend`.
+    /// Useful for injecting a deployer key as a CI secret, the mnemonic is never logged or included in errors.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The environment variable `var` is not set
+    /// - The key recovery or additions commands fail
+    fn recover_from_env(
+        &mut self,
+        sh: &Shell,
+        name: &str,
+        var: &str,
+        backend: KeyringBackend,
+    ) -> Result<Key, Error> {
+        let mnemonic = std::env::var(var).map_err(|_| Error::MissingEnvVar(var.to_owned()))?;
+
+        self.recover(sh, name, mnemonic.trim(), backend)
+    }
+
+    /// Import an `armored` private key encrypted with `passphrase`, see `Key::export`, & add it to the network's keys as `name` in the given `backend`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key import or listing commands fail.
+    fn import(
+        &mut self,
+        sh: &Shell,
+        name: &str,
+        armored: &str,
+        passphrase: &str,
+        backend: KeyringBackend,
+    ) -> Result<Key, Error>;
 }
 
-pub trait Network: Node + Cli + Keys + gas::Prices {}
+pub trait TxHooks {
+    /// The hooks to fire before each tx is broadcast & after it is confirmed
+    fn tx_hooks(&self) -> &[TxHook];
 
-impl<T> Network for T where T: Node + Cli + Keys + gas::Prices {}
+    /// Fire every hook with the `event`
+    fn fire_tx_event(&self, event: &TxEvent) {
+        for hook in self.tx_hooks() {
+            hook(event);
+        }
+    }
+}
+
+pub trait RemoteSigning {
+    /// The signer for txs from offline keys, see `Key::is_offline`
+    fn signer(&self) -> Option<&dyn Signer>;
+}
+
+pub trait Network: Node + Cli + Keys + TxHooks + RemoteSigning + gas::Prices {}
+
+impl<T> Network for T where T: Node + Cli + Keys + TxHooks + RemoteSigning + gas::Prices {}
 
 pub trait Initialize {
     type Instance: Network;
@@ -127,6 +245,204 @@ pub trait Initialize {
     fn initialize(sh: &Shell) -> Result<Self::Instance, Error>;
 }
 
+/// Set to the memory limit of each localnet service in MiB
+pub const LOCALNET_MEMORY_ENV: &str = "COSMWASM_LOCALNET_MEMORY_MB";
+/// Set to the number of CPUs each localnet service may use, e.g. `1.5`
+pub const LOCALNET_CPUS_ENV: &str = "COSMWASM_LOCALNET_CPUS";
+/// Set to the niceness of the localnet service processes, from `0` to `19`
+pub const LOCALNET_NICE_ENV: &str = "COSMWASM_LOCALNET_NICE";
+
+/// Limits on the resources used by each localnet service, so a long test run does not starve the rest of the machine.
+///
+/// Containers are limited by docker. Native services are limited through their runtimes: memory is a soft limit for the
+/// Go services via `GOMEMLIMIT`, CPUs bound their worker threads via `GOMAXPROCS` & `TOKIO_WORKER_THREADS`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub memory_mb: Option<u64>,
+    pub cpus: Option<f64>,
+    pub nice: Option<u8>,
+}
+
+impl ResourceLimits {
+    /// The limits set by `COSMWASM_LOCALNET_MEMORY_MB`, `COSMWASM_LOCALNET_CPUS` & `COSMWASM_LOCALNET_NICE`, unlimited if unset
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a variable is set but not a valid limit.
+    pub fn from_env() -> Result<Self, Error> {
+        fn invalid(name: &str) -> Error {
+            Error::InvalidEnvVar {
+                name: name.to_owned(),
+                value: std::env::var(name).unwrap_or_default(),
+            }
+        }
+
+        fn var<T: std::str::FromStr>(name: &str) -> Result<Option<T>, Error> {
+            let Ok(value) = std::env::var(name) else {
+                return Ok(None);
+            };
+
+            value.trim().parse().map(Some).map_err(|_| invalid(name))
+        }
+
+        let limits = Self {
+            memory_mb: var(LOCALNET_MEMORY_ENV)?,
+            cpus: var(LOCALNET_CPUS_ENV)?,
+            nice: var(LOCALNET_NICE_ENV)?,
+        };
+
+        if limits.cpus.is_some_and(|cpus| cpus <= 0.0) {
+            return Err(invalid(LOCALNET_CPUS_ENV));
+        }
+
+        if limits.nice.is_some_and(|nice| nice > 19) {
+            return Err(invalid(LOCALNET_NICE_ENV));
+        }
+
+        Ok(limits)
+    }
+
+    /// The `docker run` flags applying the limits to a container
+    pub(crate) fn docker_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        if let Some(memory_mb) = self.memory_mb {
+            args.push(format!("--memory={memory_mb}m"));
+        }
+
+        if let Some(cpus) = self.cpus {
+            args.push(format!("--cpus={cpus}"));
+        }
+
+        args
+    }
+
+    /// The environment bounding the memory & threads of a native service's runtime
+    pub(crate) fn runtime_env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![];
+
+        if let Some(memory_mb) = self.memory_mb {
+            env.push(("GOMEMLIMIT", format!("{memory_mb}MiB")));
+        }
+
+        if let Some(cpus) = self.cpus {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let threads = (cpus.ceil() as u64).max(1).to_string();
+
+            env.push(("GOMAXPROCS", threads.clone()));
+            env.push(("TOKIO_WORKER_THREADS", threads));
+        }
+
+        env
+    }
+}
+
+/// Set to the platform localnet images are pulled & run for, e.g. `linux/amd64` to force emulation on Apple Silicon
+pub const DOCKER_PLATFORM_ENV: &str = "COSMWASM_DOCKER_PLATFORM";
+
+/// Whether the host CPU is arm64, including Apple Silicon when this process is translated by Rosetta
+pub(crate) fn host_is_arm64() -> bool {
+    if cfg!(target_arch = "aarch64") {
+        return true;
+    }
+
+    cfg!(target_os = "macos")
+        && std::process::Command::new("sysctl")
+            .args(["-n", "hw.optional.arm64"])
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+}
+
+/// The platforms already resolved per image, each resolution may query the registry
+static DOCKER_PLATFORMS: std::sync::Mutex<std::collections::BTreeMap<String, Option<String>>> =
+    std::sync::Mutex::new(std::collections::BTreeMap::new());
+
+/// The `--platform` flag to pull & run `image` with, if any.
+///
+/// `COSMWASM_DOCKER_PLATFORM` takes precedence. Otherwise arm64 hosts select the native `linux/arm64` variant when
+/// the image is published for it & fall back to an emulated `linux/amd64` when it is not, other hosts leave it to docker.
+pub(crate) fn docker_platform_args(sh: &Shell, image: &str) -> Vec<String> {
+    if let Ok(platform) = std::env::var(DOCKER_PLATFORM_ENV) {
+        return vec![format!("--platform={}", platform.trim())];
+    }
+
+    if !host_is_arm64() {
+        return vec![];
+    }
+
+    let mut platforms = DOCKER_PLATFORMS.lock().unwrap();
+
+    let platform = platforms.entry(image.to_owned()).or_insert_with(|| {
+        // Only the manifest is fetched, an image missing from the registry e.g. a local build is left to docker
+        let manifest = xshell::cmd!(sh, "docker manifest inspect {image}")
+            .quiet()
+            .ignore_stderr()
+            .read()
+            .ok()?;
+
+        if manifest.contains(r#""architecture": "arm64""#) {
+            return Some("linux/arm64".to_owned());
+        }
+
+        log::warn!("{image} is not published for arm64, running it emulated");
+
+        Some("linux/amd64".to_owned())
+    });
+
+    platform
+        .iter()
+        .map(|platform| format!("--platform={platform}"))
+        .collect()
+}
+
+/// The namespace of a localnet isolated from others, so tests can run them concurrently, see `Isolate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestIsolation {
+    namespace: String,
+}
+
+impl TestIsolation {
+    /// Isolate within `namespace`, which must be a valid path segment & container name suffix
+    #[must_use]
+    pub fn new(namespace: &str) -> Self {
+        Self {
+            namespace: namespace.to_owned(),
+        }
+    }
+
+    /// Isolate within a namespace unique to this process & call
+    #[must_use]
+    pub fn unique() -> Self {
+        static NEXT: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+        let n = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        Self::new(&format!("{}_{n}", std::process::id()))
+    }
+
+    #[must_use]
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+}
+
+pub trait Isolate: Initialize {
+    /// Initialize network resources and/or any required state within the `isolation` namespace,
+    /// with its own home directories & container names, & without binding fixed host ports
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementation.
+    fn initialize_isolated(sh: &Shell, isolation: &TestIsolation) -> Result<Self::Instance, Error>;
+
+    /// Remove the state of the `isolation` namespace
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementation.
+    fn clean_isolated(sh: &Shell, isolation: &TestIsolation) -> Result<(), Error>;
+}
+
 pub trait IntoForeground {
     /// Consume a `StartLocal::Handle` to bring it to the foreground & follow it's logs until Ctrl + C is received
     ///
@@ -147,6 +463,20 @@ pub trait StartLocal {
     fn start_local<'shell>(&self, sh: &'shell Shell) -> Result<Self::Handle<'shell>, Error>;
 }
 
+pub trait ResetState: StartLocal {
+    /// Stop the nodes running under `handle`, restore their state to right after genesis & start them again,
+    /// giving a test a clean chain in seconds while keeping the built binaries & images
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error depending on the implementation.
+    fn reset_state<'shell>(
+        &self,
+        sh: &'shell Shell,
+        handle: Self::Handle<'shell>,
+    ) -> Result<Self::Handle<'shell>, Error>;
+}
+
 pub trait Clean {
     /// Remove any network state
     ///
@@ -166,6 +496,8 @@ pub trait Clean {
 pub struct Instance<Network> {
     pub keys: Vec<Key>,
     network: Network,
+    tx_hooks: Vec<TxHook>,
+    signer: Option<Box<dyn Signer>>,
 }
 
 impl<Network> std::ops::Deref for Instance<Network> {
@@ -176,13 +508,21 @@ impl<Network> std::ops::Deref for Instance<Network> {
     }
 }
 
+/// The variable tools read the user's home directory from, redirected to keep their state under `target`
+#[cfg(not(windows))]
+pub(crate) const HOME_ENV: &str = "HOME";
+
+#[cfg(windows)]
+pub(crate) const HOME_ENV: &str = "USERPROFILE";
+
 macro_rules! home_path_prefix {
     () => {{
-        let mut path = String::new();
-        path.push_str("target/");
-        path.push_str(&module_path!());
-        let path = path.replace("::", "/");
-        std::path::PathBuf::from(path)
+        // Push each segment so the path gets the host's separators
+        let mut path = std::path::PathBuf::from("target");
+        for segment in module_path!().split("::") {
+            path.push(segment);
+        }
+        path
     }};
 }
 
@@ -216,14 +556,44 @@ impl<Network> Instance<Network> {
         Self {
             keys: vec![],
             network,
+            tx_hooks: vec![],
+            signer: None,
         }
     }
 
+    /// Register a hook fired before each tx is broadcast & after it is confirmed, e.g. for logging or metrics
+    pub fn on_tx<F>(&mut self, hook: F)
+    where
+        F: Fn(&TxEvent) + 'static,
+    {
+        self.tx_hooks.push(Box::new(hook));
+    }
+
+    /// Set the signer for txs from offline keys, for keys which cannot be held in a local keyring
+    pub fn set_signer<S>(&mut self, signer: S)
+    where
+        S: Signer + 'static,
+    {
+        self.signer = Some(Box::new(signer));
+    }
+
     fn network(&self) -> &Network {
         &self.network
     }
 }
 
+impl<Network> TxHooks for Instance<Network> {
+    fn tx_hooks(&self) -> &[TxHook] {
+        &self.tx_hooks
+    }
+}
+
+impl<Network> RemoteSigning for Instance<Network> {
+    fn signer(&self) -> Option<&dyn Signer> {
+        self.signer.as_deref()
+    }
+}
+
 impl<Network> Keys for Instance<Network>
 where
     Self: Cli,
@@ -239,10 +609,49 @@ where
         mnemonic: &str,
         backend: KeyringBackend,
     ) -> Result<Key, Error> {
+        // Ensure a file keyring exists, so its passphrase is only asked for once when recovering
+        if backend == KeyringBackend::File {
+            self.cli(sh)?.list_keys(backend)?;
+        }
+
         let key = self.cli(sh)?.recover_key(name, mnemonic, backend)?;
 
         self.keys.push(key.clone());
 
         Ok(key)
     }
+
+    fn import(
+        &mut self,
+        sh: &Shell,
+        name: &str,
+        armored: &str,
+        passphrase: &str,
+        backend: KeyringBackend,
+    ) -> Result<Key, Error> {
+        // Ensure a file keyring exists, so its passphrase is only asked for once when importing
+        if backend == KeyringBackend::File {
+            self.cli(sh)?.list_keys(backend)?;
+        }
+
+        let tmp_dir = sh.create_temp_dir()?;
+
+        let armored_path = tmp_dir.path().join(format!("{name}.armor"));
+
+        sh.write_file(&armored_path, armored)?;
+
+        self.cli(sh)?
+            .import_key(name, &armored_path, passphrase, backend)?;
+
+        let key = self
+            .cli(sh)?
+            .list_keys(backend)?
+            .into_iter()
+            .find(|key| key.name() == name)
+            .ok_or_else(|| Error::KeyNotFound(name.to_owned()))?;
+
+        self.keys.push(key.clone());
+
+        Ok(key)
+    }
 }
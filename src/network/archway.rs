@@ -1,19 +1,26 @@
-use std::path::PathBuf;
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use once_cell::unsync::OnceCell;
 use xshell::{cmd, Shell};
 
 use crate::{
-    cli::{Cli, Cmd, ReadyTxCmd},
-    contract::{Execute, Tx},
-    key::KeyringBackend,
+    cli::{poll_interval, Cli, Cmd, ReadyTxCmd, ShellCmdExt},
+    contract::Tx,
+    key::{KeyRole, KeyringBackend},
     Error,
 };
 
 use super::{
+    docker_platform_args,
+    fork::Fork,
     gas::{Price as GasPrice, Prices as GasPrices},
-    make_abs_path, make_abs_root, ChainId, Clean, Initialize, Instance, IntoForeground, Node,
-    NodeUri, StartLocal,
+    genesis::{self, Preset},
+    make_abs_path, make_abs_root, ChainId, Clean, Initialize, Instance, IntoForeground, Isolate,
+    Node, NodeUri, ResetState, ResourceLimits, StartLocal, TestIsolation,
 };
 
 pub trait CmdExt: Sized {
@@ -22,25 +29,24 @@ pub trait CmdExt: Sized {
 }
 
 impl<'a> CmdExt for ReadyTxCmd<'a> {
-    fn fees(mut self, amount: u128, denom: &str) -> Self {
-        self.cmd = self
-            .cmd
-            .args(["--fees", format!("{amount}{denom}").as_str()]);
-        self
+    fn fees(self, amount: u128, denom: &str) -> Self {
+        ReadyTxCmd::fees(self, amount, denom)
     }
 }
 
-impl<Msg, Response> CmdExt for Tx<Execute, Msg, Response> {
+impl<Opts, Msg, Response> CmdExt for Tx<Opts, Msg, Response> {
     fn fees(self, amount: u128, denom: &str) -> Self {
-        let denom = denom.to_owned();
-        self.pre_execute_hook(move |cmd| cmd.fees(amount, denom.as_str()))
+        Tx::fees(self, amount, denom)
     }
 }
 
-#[derive(Default)]
 pub struct Local {
     home_path: PathBuf,
-    node_uri: OnceCell<NodeUri>,
+    image: RefCell<String>,
+    container_name: String,
+    isolated: bool,
+    /// Cached until the container is restarted, its IP may change
+    node_uri: RefCell<OnceCell<NodeUri>>,
 }
 
 pub const LOCAL_HOME_DIR: &str = "data";
@@ -48,25 +54,43 @@ pub const LOCAL_CHAIN_ID: &str = "localnet";
 pub const LOCAL_CHAIN_MONIKER: &str = "archway-local";
 pub const LOCAL_CHAIN_DENOM: &str = "stake";
 pub const LOCAL_CONTAINER_NAME: &str = "cosmwasm_xtask_archwayd";
-
-impl Initialize for Local {
-    type Instance = Instance<Local>;
-
-    fn initialize(sh: &Shell) -> Result<Self::Instance, Error> {
-        cmd!(sh, "docker pull ghcr.io/archway-network/archwayd:v1.0.0")
+pub const LOCAL_FORKED_GENESIS_FILE: &str = "forked_genesis.json";
+pub const LOCAL_PRESET_GENESIS_FILE: &str = "preset_genesis.json";
+pub const LOCAL_IMAGE: &str = "ghcr.io/archway-network/archwayd:v1.0.0";
+/// Has the shell tools the node image lacks, used to edit the node state owned by the container user
+pub const LOCAL_DEBUG_IMAGE: &str = "ghcr.io/archway-network/archwayd-debug:v1.0.0";
+/// How long to wait for a stopped node container to be removed before starting it again
+pub const CONTAINER_REMOVAL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Short enough for governance proposals, e.g. store code or software upgrades, to pass within a test
+pub const LOCAL_VOTING_PERIOD: &str = "20s";
+
+impl Local {
+    /// Initialize the node state in `network.home_path` if it doesn't exist yet
+    fn initialize_in(sh: &Shell, network: Local) -> Result<Instance<Local>, Error> {
+        let platform = docker_platform_args(sh, LOCAL_IMAGE);
+
+        cmd!(sh, "docker pull {platform...} {LOCAL_IMAGE}")
             .ignore_stdout()
             .ignore_stderr()
             .quiet()
+            .logged()
             .run()?;
 
-        let mut instance = Instance::new(Local {
-            home_path: make_abs_path!(sh, LOCAL_HOME_DIR),
-            ..Default::default()
-        });
+        let mut instance = Instance::new(network);
 
         if sh.path_exists(&instance.network.home_path) {
             let keys = instance.cli(sh)?.list_keys(KeyringBackend::Test)?;
-            instance.keys = keys;
+            instance.keys = keys
+                .into_iter()
+                .map(|key| {
+                    let role = if key.name() == "local0" {
+                        KeyRole::Validator
+                    } else {
+                        KeyRole::Demo
+                    };
+                    key.with_role(role)
+                })
+                .collect();
             return Ok(instance);
         }
 
@@ -99,23 +123,86 @@ impl Initialize for Local {
             LOCAL_CHAIN_ID,
         )?;
 
-        instance.keys.push(local0);
+        instance.keys.push(local0.with_role(KeyRole::Validator));
 
-        instance.keys.push(local1);
+        instance.keys.push(local1.with_role(KeyRole::Demo));
 
         instance.cli(sh)?.collect_gentx()?;
 
         instance.cli(sh)?.validate_genesis()?;
 
+        let platform = docker_platform_args(sh, LOCAL_DEBUG_IMAGE);
+
+        cmd!(sh, "docker pull {platform...} {LOCAL_DEBUG_IMAGE}")
+            .ignore_stdout()
+            .ignore_stderr()
+            .logged()
+            .run()?;
+
+        let abs_home_path = instance.network.home_path.as_path();
+
+        Self::sed_in_home(sh, abs_home_path, "s/127.0.0.1/0.0.0.0/g", "config.toml")?;
+
+        Self::sed_in_home(
+            sh,
+            abs_home_path,
+            r#"s/cors_allowed_origins = \[\]/cors_allowed_origins = \["*"\]/g"#,
+            "config.toml",
+        )?;
+
+        Self::sed_in_home(
+            sh,
+            abs_home_path,
+            &format!(r#"s/"voting_period": "[0-9]*s"/"voting_period": "{LOCAL_VOTING_PERIOD}"/g"#),
+            "genesis.json",
+        )?;
+
+        Self::copy_home(
+            sh,
+            abs_home_path,
+            &Self::genesis_snapshot_path(abs_home_path),
+        )?;
+
+        Ok(instance)
+    }
+
+    /// Where the node state right after genesis is kept for `ResetState::reset_state`
+    fn genesis_snapshot_path(home_path: &Path) -> PathBuf {
+        PathBuf::from(format!("{}_genesis", home_path.display()))
+    }
+
+    /// Replace the node state at `to` with a copy of the state at `from`, with a container as the files are owned by its user
+    fn copy_home(sh: &Shell, from: &Path, to: &Path) -> Result<(), Error> {
+        let platform = docker_platform_args(sh, LOCAL_DEBUG_IMAGE);
+
+        let script = "rm -rf /to/* && cp -a /from/. /to/";
+
         cmd!(
             sh,
-            "docker pull ghcr.io/archway-network/archwayd-debug:v1.0.0"
+            "docker run 
+                    --rm 
+                    --interactive 
+                    --volume {from}:/from 
+                    --volume {to}:/to 
+                    --entrypoint /bin/sh
+                    {platform...}
+                    {LOCAL_DEBUG_IMAGE}
+                    -c {script}"
         )
-        .ignore_stdout()
-        .ignore_stderr()
+        .logged()
         .run()?;
 
-        let abs_home_path = instance.network.home_path.as_path();
+        Ok(())
+    }
+
+    /// Edit the `config_file` in the node config dir with the sed `script`, with a container as the files are owned by its user
+    fn sed_in_home(
+        sh: &Shell,
+        abs_home_path: &Path,
+        script: &str,
+        config_file: &str,
+    ) -> Result<(), Error> {
+        let platform = docker_platform_args(sh, LOCAL_DEBUG_IMAGE);
 
         cmd!(
             sh,
@@ -124,37 +211,195 @@ impl Initialize for Local {
                     --interactive 
                     --volume {abs_home_path}:/home 
                     --entrypoint /bin/sed
-                    ghcr.io/archway-network/archwayd-debug:v1.0.0
-                    -i 's/127.0.0.1/0.0.0.0/g' /home/config/config.toml"
+                    {platform...}
+                    {LOCAL_DEBUG_IMAGE}
+                    -i {script} /home/config/{config_file}"
         )
+        .logged()
         .run()?;
 
+        Ok(())
+    }
+
+    /// Remove the node state at `home_path`, also with a container. Its parent is mounted rather than the
+    /// workspace root as the host path of `home_path` does not exist inside the container.
+    fn remove_home(sh: &Shell, home_path: &Path) -> Result<(), Error> {
+        let (Some(parent), Some(name)) = (home_path.parent(), home_path.file_name()) else {
+            return Ok(());
+        };
+
+        if !sh.path_exists(home_path) {
+            return Ok(());
+        }
+
+        let target = Path::new("/parent").join(name);
+
+        let platform = docker_platform_args(sh, LOCAL_DEBUG_IMAGE);
+
         cmd!(
             sh,
             "docker run 
                     --rm 
                     --interactive 
+                    --volume {parent}:/parent 
+                    --entrypoint /bin/rm
+                    {platform...}
+                    {LOCAL_DEBUG_IMAGE}
+                    -rf {target}"
+        )
+        .logged()
+        .run()?;
+
+        Ok(())
+    }
+}
+
+impl Initialize for Local {
+    type Instance = Instance<Local>;
+
+    fn initialize(sh: &Shell) -> Result<Self::Instance, Error> {
+        Self::initialize_in(
+            sh,
+            Local {
+                home_path: make_abs_path!(sh, LOCAL_HOME_DIR),
+                image: RefCell::new(LOCAL_IMAGE.to_owned()),
+                container_name: LOCAL_CONTAINER_NAME.to_owned(),
+                isolated: false,
+                node_uri: RefCell::new(OnceCell::new()),
+            },
+        )
+    }
+}
+
+impl Local {
+    /// Initialize a localnet from scratch with the state of the `fork` merged into its genesis, replacing any existing state
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Removing the existing state or initializing the network fails
+    /// - State remains after removing it
+    /// - Merging the forked state fails, see `Fork::merge`
+    /// - Replacing the genesis fails
+    pub fn initialize_forked(sh: &Shell, fork: &Fork) -> Result<Instance<Local>, Error> {
+        Self::initialize_with_genesis(sh, LOCAL_FORKED_GENESIS_FILE, |genesis| {
+            fork.merge(sh, genesis)
+        })
+    }
+
+    /// Initialize a localnet from scratch with the genesis `presets` applied, replacing any existing state
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Removing the existing state or initializing the network fails
+    /// - State remains after removing it
+    /// - Applying a preset fails, see `genesis::apply`
+    /// - Replacing the genesis fails
+    pub fn initialize_with_presets(
+        sh: &Shell,
+        presets: &[Preset],
+    ) -> Result<Instance<Local>, Error> {
+        Self::initialize_with_genesis(sh, LOCAL_PRESET_GENESIS_FILE, |genesis| {
+            genesis::apply(genesis, presets)
+        })
+    }
+
+    /// Initialize from scratch, then replace the genesis with the output of `edit`, staged in `staging_file` at the workspace root
+    fn initialize_with_genesis<F>(
+        sh: &Shell,
+        staging_file: &str,
+        edit: F,
+    ) -> Result<Instance<Local>, Error>
+    where
+        F: FnOnce(&str) -> Result<String, Error>,
+    {
+        Self::clean_state(sh)?;
+
+        let home_path = make_abs_path!(sh, LOCAL_HOME_DIR);
+
+        // `initialize` keeps existing state, which would put the new genesis under a chain which already ran
+        if sh.path_exists(&home_path) {
+            return Err(Error::StaleState(home_path.display().to_string()));
+        }
+
+        let instance = Self::initialize(sh)?;
+
+        let genesis_path = instance.network.home_path.join("config/genesis.json");
+
+        let genesis = edit(&sh.read_file(&genesis_path)?)?;
+
+        let root = make_abs_root!(sh);
+
+        sh.write_file(root.join(staging_file), genesis)?;
+
+        let abs_home_path = instance.network.home_path.as_path();
+
+        let platform = docker_platform_args(sh, LOCAL_DEBUG_IMAGE);
+
+        cmd!(
+            sh,
+            "docker run 
+                    --rm 
+                    --interactive 
+                    --volume {root}:/work 
                     --volume {abs_home_path}:/home 
-                    --entrypoint /bin/sed
-                    ghcr.io/archway-network/archwayd-debug:v1.0.0"
+                    --entrypoint /bin/cp
+                    {platform...}
+                    {LOCAL_DEBUG_IMAGE}
+                    /work/{staging_file} /home/config/genesis.json"
         )
-        .args([
-            "-i",
-            r#"s/cors_allowed_origins = \[\]/cors_allowed_origins = \["*"\]/g"#,
-            "/home/config/config.toml",
-        ])
+        .logged()
         .run()?;
 
+        // Reset to the replaced genesis
+        Self::copy_home(
+            sh,
+            abs_home_path,
+            &Self::genesis_snapshot_path(abs_home_path),
+        )?;
+
         Ok(instance)
     }
 }
 
+impl Isolate for Local {
+    fn initialize_isolated(sh: &Shell, isolation: &TestIsolation) -> Result<Self::Instance, Error> {
+        let namespace = isolation.namespace();
+
+        Self::initialize_in(
+            sh,
+            Local {
+                home_path: make_abs_path!(sh, format!("{LOCAL_HOME_DIR}_{namespace}")),
+                image: RefCell::new(LOCAL_IMAGE.to_owned()),
+                container_name: format!("{LOCAL_CONTAINER_NAME}_{namespace}"),
+                isolated: true,
+                node_uri: RefCell::new(OnceCell::new()),
+            },
+        )
+    }
+
+    fn clean_isolated(sh: &Shell, isolation: &TestIsolation) -> Result<(), Error> {
+        let namespace = isolation.namespace();
+
+        let home_path = make_abs_path!(sh, format!("{LOCAL_HOME_DIR}_{namespace}"));
+
+        Self::remove_home(sh, &Self::genesis_snapshot_path(&home_path))?;
+
+        Self::remove_home(sh, &home_path)
+    }
+}
+
 impl Cli for Instance<Local> {
     fn cli<'a>(&self, sh: &'a Shell) -> Result<Cmd<'a>, Error> {
         let current_dir = sh.current_dir();
 
         let abs_home_path = self.network.home_path.as_path();
 
+        let image = self.network.image.borrow().clone();
+
+        let platform = docker_platform_args(sh, &image);
+
         let cmd = cmd!(
             sh,
             "docker run 
@@ -163,7 +408,8 @@ impl Cli for Instance<Local> {
                     --volume {abs_home_path}:/home 
                     --volume {current_dir}:/work 
                     --workdir /work 
-                    ghcr.io/archway-network/archwayd:v1.0.0
+                    {platform...}
+                    {image}
                     --home /home
                     "
         );
@@ -174,14 +420,18 @@ impl Cli for Instance<Local> {
 
 pub struct LocalHandle<'a> {
     sh: &'a Shell,
+    container_name: String,
 }
 
 impl<'a> IntoForeground for LocalHandle<'a> {
     fn into_foreground(self) -> Result<(), Error> {
         ctrlc::set_handler(|| {})?;
 
-        cmd!(self.sh, "docker logs -f {LOCAL_CONTAINER_NAME}")
+        let container_name = &self.container_name;
+
+        cmd!(self.sh, "docker logs -f {container_name}")
             .ignore_status()
+            .logged()
             .run()?;
 
         Ok(())
@@ -190,10 +440,31 @@ impl<'a> IntoForeground for LocalHandle<'a> {
 
 impl<'a> Drop for LocalHandle<'a> {
     fn drop(&mut self) {
-        cmd!(self.sh, "docker stop {LOCAL_CONTAINER_NAME}")
+        let container_name = &self.container_name;
+
+        cmd!(self.sh, "docker stop {container_name}")
             .ignore_status()
+            .logged()
             .run()
             .expect("docker stop command status ignored");
+
+        wait_for_container_removal(self.sh, container_name);
+    }
+}
+
+/// The stopped container is removed in the background, its name is only free to start another once it is gone
+fn wait_for_container_removal(sh: &Shell, container_name: &str) {
+    let started = Instant::now();
+
+    while started.elapsed() < CONTAINER_REMOVAL_TIMEOUT
+        && cmd!(sh, "docker container inspect {container_name}")
+            .quiet()
+            .ignore_stdout()
+            .ignore_stderr()
+            .run()
+            .is_ok()
+    {
+        std::thread::sleep(poll_interval());
     }
 }
 
@@ -205,24 +476,95 @@ impl StartLocal for Instance<Local> {
 
         let abs_home_path = self.network.home_path.as_path();
 
+        let container_name = &self.network.container_name;
+
+        let image = self.network.image.borrow().clone();
+
+        // Isolated nodes publish their ports on ephemeral host ports, they are reached by container IP anyway
+        let (grpc_port, rpc_port) = if self.network.isolated {
+            ("9090", "26657")
+        } else {
+            ("9090:9090", "26657:26657")
+        };
+
+        // Niceness is not applied, the container process is owned by the docker daemon
+        let limits = ResourceLimits::from_env()?.docker_args();
+
+        let platform = docker_platform_args(sh, &image);
+
         cmd!(
             sh,
             "docker run
                     --rm
                     --detach
-                    --name {LOCAL_CONTAINER_NAME}
+                    --name {container_name}
                     --volume {abs_home_path}:/home 
                     --volume {cwd}:/work 
                     --workdir /work 
-                    --publish 9090:9090
-                    --publish 26657:26657
-                    ghcr.io/archway-network/archwayd:v1.0.0
+                    --publish {grpc_port}
+                    --publish {rpc_port}
+                    {limits...}
+                    {platform...}
+                    {image}
                     start
                     --home /home"
         )
+        .logged()
         .run()?;
 
-        Ok(LocalHandle { sh })
+        // The new container may be given another IP than the last one
+        self.network.node_uri.replace(OnceCell::new());
+
+        Ok(LocalHandle {
+            sh,
+            container_name: container_name.clone(),
+        })
+    }
+}
+
+impl Instance<Local> {
+    /// Pull & run the node from `image` from now on, e.g. the new version to restart with after a software upgrade halts the chain.
+    /// A running node is not affected until it is restarted, see `upgrade::restart`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if pulling the image fails.
+    pub fn swap_image(&self, sh: &Shell, image: &str) -> Result<(), Error> {
+        let platform = docker_platform_args(sh, image);
+
+        cmd!(sh, "docker pull {platform...} {image}")
+            .ignore_stdout()
+            .ignore_stderr()
+            .logged()
+            .run()?;
+
+        self.network.image.replace(image.to_owned());
+
+        Ok(())
+    }
+}
+
+impl ResetState for Instance<Local> {
+    fn reset_state<'shell>(
+        &self,
+        sh: &'shell Shell,
+        handle: LocalHandle<'shell>,
+    ) -> Result<LocalHandle<'shell>, Error> {
+        let home_path = self.network.home_path.as_path();
+
+        let snapshot_path = Local::genesis_snapshot_path(home_path);
+
+        if !sh.path_exists(&snapshot_path) {
+            return Err(Error::MissingGenesisSnapshot(
+                snapshot_path.display().to_string(),
+            ));
+        }
+
+        drop(handle);
+
+        Local::copy_home(sh, &snapshot_path, home_path)?;
+
+        self.start_local(sh)
     }
 }
 
@@ -230,12 +572,13 @@ impl Node for Instance<Local> {
     fn node_uri(&self, sh: &Shell) -> Result<NodeUri, Error> {
         self.network
             .node_uri
+            .borrow()
             .get_or_try_init(|| {
                 cmd!(sh, "docker inspect")
                     .args([
                         "-f",
                         "'{{range .NetworkSettings.Networks}}{{.IPAddress}}{{end}}'",
-                        LOCAL_CONTAINER_NAME,
+                        self.network.container_name.as_str(),
                     ])
                     .read()
                     .map(|ip| {
@@ -254,38 +597,29 @@ impl Node for Instance<Local> {
     fn chain_id(&self) -> ChainId {
         ChainId::from(LOCAL_CHAIN_ID.to_owned())
     }
+
+    fn faucet(&self) -> Option<&str> {
+        Some("local1")
+    }
+
+    fn logs_hint(&self) -> Option<String> {
+        Some(format!("run `docker logs {}`", self.network.container_name))
+    }
 }
 
 impl Clean for Local {
     fn clean_state(sh: &Shell) -> Result<(), Error> {
-        let cwd = make_abs_root!(sh);
-
         let home_path = make_abs_path!(sh, LOCAL_HOME_DIR);
 
-        cmd!(
-            sh,
-            "docker run 
-                    --rm 
-                    --interactive 
-                    --volume {cwd}:/work 
-                    --workdir /work 
-                    --entrypoint /bin/rm
-                    ghcr.io/archway-network/archwayd-debug:v1.0.0
-                    -rf {home_path}"
-        )
-        .run()?;
+        Self::remove_home(sh, &Self::genesis_snapshot_path(&home_path))?;
 
-        Ok(())
+        Self::remove_home(sh, &home_path)
     }
 
     fn clean_all(sh: &Shell) -> Result<(), Error> {
         Self::clean_state(sh)?;
 
-        cmd!(
-            sh,
-            "docker rmi ghcr.io/archway-network/archwayd-debug:v1.0.0"
-        )
-        .run()?;
+        cmd!(sh, "docker rmi {LOCAL_DEBUG_IMAGE}").logged().run()?;
 
         Ok(())
     }
@@ -0,0 +1,73 @@
+//! Fork the state of a live network into a localnet, so contracts can be tested against realistic on-chain state.
+//!
+//! The module state of an exported genesis, e.g. from `<chaind> export --height <h>`, is merged into the localnet genesis.
+//! The localnet keeps its own validator set, accounts & staking state, so it produces blocks with the local keys.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use xshell::Shell;
+
+use crate::Error;
+
+/// The modules forked by default, i.e. the stored codes & contract state
+pub const DEFAULT_FORKED_MODULES: &[&str] = &["wasm"];
+
+pub struct Fork {
+    export_path: PathBuf,
+    modules: Vec<String>,
+}
+
+/// Construct a fork of the genesis exported to `export_path`, see `Fork::merge`
+pub fn from_export<P>(export_path: P) -> Fork
+where
+    P: AsRef<Path>,
+{
+    Fork {
+        export_path: export_path.as_ref().to_path_buf(),
+        modules: DEFAULT_FORKED_MODULES
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    }
+}
+
+impl Fork {
+    /// Fork the state of `modules` rather than `DEFAULT_FORKED_MODULES`.
+    /// Modules holding validator state, e.g. `staking` or `slashing`, should not be forked as the local keys would no longer be validators.
+    #[must_use]
+    pub fn modules(self, modules: &[&str]) -> Self {
+        Self {
+            modules: modules.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Replace the state of the forked modules in the localnet `genesis` JSON with their state in the export
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Reading the export fails
+    /// - JSON (de)serialisation fails
+    /// - A forked module is missing from the export or the localnet genesis
+    pub fn merge(&self, sh: &Shell, genesis: &str) -> Result<String, Error> {
+        let export: Value = serde_json::from_str(&sh.read_file(&self.export_path)?)?;
+
+        let mut genesis: Value = serde_json::from_str(genesis)?;
+
+        for module in &self.modules {
+            let state = export
+                .pointer(&format!("/app_state/{module}"))
+                .ok_or_else(|| Error::MissingGenesisModule(module.clone()))?;
+
+            let local_state = genesis
+                .pointer_mut(&format!("/app_state/{module}"))
+                .ok_or_else(|| Error::MissingGenesisModule(module.clone()))?;
+
+            *local_state = state.clone();
+        }
+
+        Ok(serde_json::to_string_pretty(&genesis)?)
+    }
+}
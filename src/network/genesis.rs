@@ -0,0 +1,137 @@
+//! Named genesis presets for common test scenarios, applied to a localnet genesis before its first block.
+//!
+//! ```ignore
+//! let network = ArchwayLocalnet::initialize_with_presets(&sh, &[Preset::FastGov, Preset::IcaOpen])?;
+//! ```
+
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+
+use crate::Error;
+
+/// The voting & deposit period set by `Preset::FastGov`, below every localnet's own voting period
+/// (e.g. `archway::LOCAL_VOTING_PERIOD`) so the preset never slows governance down
+pub const FAST_GOV_PERIOD: &str = "10s";
+
+/// The fixed yearly inflation rate set by `Preset::HighInflation`
+pub const HIGH_INFLATION_RATE: &str = "0.500000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// `fast-gov`: governance proposals can be deposited on & pass within `FAST_GOV_PERIOD`
+    FastGov,
+    /// `high-inflation`: the staking token inflates at `HIGH_INFLATION_RATE`, for testing reward distribution
+    HighInflation,
+    /// `ica-open`: interchain accounts are enabled on both ends & the host allows any msg
+    IcaOpen,
+}
+
+impl Preset {
+    pub const ALL: &'static [Preset] = &[Preset::FastGov, Preset::HighInflation, Preset::IcaOpen];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::FastGov => "fast-gov",
+            Preset::HighInflation => "high-inflation",
+            Preset::IcaOpen => "ica-open",
+        }
+    }
+
+    /// The params set by the preset, each at the first of its JSON pointers present as params have moved between SDK versions
+    fn params(self) -> Vec<(&'static [&'static str], Value)> {
+        match self {
+            Preset::FastGov => vec![
+                (
+                    &[
+                        "/app_state/gov/params/voting_period",
+                        "/app_state/gov/voting_params/voting_period",
+                    ],
+                    json!(FAST_GOV_PERIOD),
+                ),
+                (
+                    &[
+                        "/app_state/gov/params/max_deposit_period",
+                        "/app_state/gov/deposit_params/max_deposit_period",
+                    ],
+                    json!(FAST_GOV_PERIOD),
+                ),
+            ],
+            Preset::HighInflation => vec![
+                (&["/app_state/mint/minter/inflation"], json!(HIGH_INFLATION_RATE)),
+                (
+                    &["/app_state/mint/params/inflation_min"],
+                    json!(HIGH_INFLATION_RATE),
+                ),
+                (
+                    &["/app_state/mint/params/inflation_max"],
+                    json!(HIGH_INFLATION_RATE),
+                ),
+            ],
+            Preset::IcaOpen => vec![
+                (
+                    &["/app_state/interchainaccounts/host_genesis_state/params/host_enabled"],
+                    json!(true),
+                ),
+                (
+                    &["/app_state/interchainaccounts/host_genesis_state/params/allow_messages"],
+                    json!(["*"]),
+                ),
+                (
+                    &["/app_state/interchainaccounts/controller_genesis_state/params/controller_enabled"],
+                    json!(true),
+                ),
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Preset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .iter()
+            .copied()
+            .find(|preset| preset.name() == s)
+            .ok_or_else(|| Error::UnknownGenesisPreset(s.to_owned()))
+    }
+}
+
+/// Apply the `presets` to the `genesis` JSON in order, a later preset overriding any param also set by an earlier one
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - JSON (de)serialisation fails
+/// - A param set by a preset is missing from the genesis, e.g. the chain has no such module
+pub fn apply(genesis: &str, presets: &[Preset]) -> Result<String, Error> {
+    let mut genesis: Value = serde_json::from_str(genesis)?;
+
+    for preset in presets {
+        for (pointers, value) in preset.params() {
+            let Some(pointer) = pointers
+                .iter()
+                .find(|pointer| genesis.pointer(pointer).is_some())
+            else {
+                return Err(Error::MissingGenesisParam {
+                    preset: preset.name().to_owned(),
+                    param: pointers[0].to_owned(),
+                });
+            };
+
+            if let Some(param) = genesis.pointer_mut(pointer) {
+                *param = value;
+            }
+        }
+    }
+
+    Ok(serde_json::to_string_pretty(&genesis)?)
+}
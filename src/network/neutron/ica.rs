@@ -0,0 +1,205 @@
+//! Interchain account (ICA) workflows for Neutron contracts, on the Neutron localnet or testnet.
+//!
+//! Neutron only accepts ICA registration & submission msgs from contracts, so the helpers execute the owner contract
+//! with its own msgs, then wait on the resulting interchain account & packets.
+//!
+//! ```ignore
+//! let ica = Ica::new(&contract, "ica0");
+//!
+//! let address = ica::register_ica(&sh, &network, &network.keys()[0], &ica, &ExecuteMsg::Register {}, &[])?;
+//!
+//! let packets = ica::submit_ica_tx(&sh, &network, &network.keys()[0], &ica, &ExecuteMsg::Delegate { amount })?;
+//! ```
+
+use std::time::{Duration, Instant};
+
+use log::debug;
+use serde::Serialize;
+use xshell::Shell;
+
+use crate::{
+    cli::{poll_interval, Coin, Contract},
+    contract::execute,
+    ibc::{sent_packets, wait_for_ack, Packet},
+    key::Key,
+    network::Network,
+    Error,
+};
+
+use super::local::NTRN_GAIA_CONNECTION_ID;
+
+/// How long to wait for an interchain account to be registered by default, covering the relayer opening the ICA channel
+/// & the host chain creating the account before Neutron records its address
+pub const DEFAULT_ICA_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// An interchain account owned by a Neutron contract, identified by the contract's `interchain_account_id` on a connection
+#[derive(Debug, Clone)]
+pub struct Ica {
+    owner: Contract,
+    connection_id: String,
+    interchain_account_id: String,
+}
+
+impl Ica {
+    /// The account `interchain_account_id` of the `owner` contract on the localnet connection to Gaia
+    #[must_use]
+    pub fn new(owner: &Contract, interchain_account_id: &str) -> Self {
+        Self {
+            owner: owner.clone(),
+            connection_id: NTRN_GAIA_CONNECTION_ID.to_owned(),
+            interchain_account_id: interchain_account_id.to_owned(),
+        }
+    }
+
+    /// Use the account on `connection_id` rather than the localnet connection to Gaia, e.g. on testnet
+    #[must_use]
+    pub fn connection(self, connection_id: &str) -> Self {
+        Self {
+            connection_id: connection_id.to_owned(),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn owner(&self) -> &Contract {
+        &self.owner
+    }
+
+    #[must_use]
+    pub fn connection_id(&self) -> &str {
+        &self.connection_id
+    }
+
+    #[must_use]
+    pub fn interchain_account_id(&self) -> &str {
+        &self.interchain_account_id
+    }
+
+    /// The controller port of the account, the source port of the packets it sends
+    #[must_use]
+    pub fn port_id(&self) -> String {
+        format!(
+            "icacontroller-{}.{}",
+            self.owner.as_str(),
+            self.interchain_account_id
+        )
+    }
+}
+
+/// Execute the owner contract with its registration `msg` from `from`, attaching `funds` for any registration fee,
+/// then wait for the interchain account to be registered & respond with its address on the host chain
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Executing the owner contract fails
+/// - Querying the interchain account fails
+/// - The account is not registered within `DEFAULT_ICA_TIMEOUT`
+pub fn register_ica<Msg>(
+    sh: &Shell,
+    network: &dyn Network,
+    from: &Key,
+    ica: &Ica,
+    msg: &Msg,
+    funds: &[Coin],
+) -> Result<String, Error>
+where
+    Msg: Serialize,
+{
+    debug!(
+        "Registering interchain account {} of {}",
+        ica.interchain_account_id, ica.owner
+    );
+
+    funds
+        .iter()
+        .fold(execute(&ica.owner, msg), |tx, coin| {
+            tx.add_funds(coin.amount, &coin.denom)
+        })
+        .send(sh, network, from)?;
+
+    wait_for_ica_address(sh, network, ica, DEFAULT_ICA_TIMEOUT)
+}
+
+/// Keep querying the interchain account until it is registered or `timeout` elapses, responding with its address on the host chain
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Querying the interchain account fails
+/// - The account is not registered within `timeout`
+pub fn wait_for_ica_address(
+    sh: &Shell,
+    network: &dyn Network,
+    ica: &Ica,
+    timeout: Duration,
+) -> Result<String, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let address = network.cli(sh)?.query(&node_uri).interchain_account(
+            ica.owner.as_str(),
+            &ica.connection_id,
+            &ica.interchain_account_id,
+        )?;
+
+        if let Some(address) = address {
+            debug!(
+                "Interchain account {} of {} registered: {address}",
+                ica.interchain_account_id, ica.owner
+            );
+
+            return Ok(address);
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::IcaTimeout {
+                owner: ica.owner.to_string(),
+                interchain_account_id: ica.interchain_account_id.clone(),
+            });
+        }
+
+        std::thread::sleep(poll_interval());
+    }
+}
+
+/// Execute the owner contract with a `msg` submitting txs to the interchain account, then wait for the host chain to acknowledge them.
+/// Responds with the packets sent, an acknowledgement only means the host chain received the packet, not that its msgs succeeded.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Executing the owner contract fails
+/// - The execution did not send a packet from the interchain account
+/// - A packet timed out or was not acknowledged, see `ibc::wait_for_ack`
+pub fn submit_ica_tx<Msg>(
+    sh: &Shell,
+    network: &dyn Network,
+    from: &Key,
+    ica: &Ica,
+    msg: &Msg,
+) -> Result<Vec<Packet>, Error>
+where
+    Msg: Serialize,
+{
+    let res = execute(&ica.owner, msg).send_full(sh, network, from)?;
+
+    let port_id = ica.port_id();
+
+    let packets: Vec<_> = sent_packets(&res.events)
+        .into_iter()
+        .filter(|packet| packet.source.port_id == port_id)
+        .collect();
+
+    if packets.is_empty() {
+        return Err(Error::ExpectedPacket(port_id));
+    }
+
+    for packet in &packets {
+        wait_for_ack(sh, network, &packet.source, packet.sequence)?;
+    }
+
+    Ok(packets)
+}
@@ -0,0 +1,245 @@
+//! Interchain query (ICQ) workflows for Neutron contracts, answered by the ICQ relayer the localnet runs.
+//!
+//! Neutron only accepts query registrations from contracts, so the owner contract is executed with the register msgs
+//! of the reference interchain queries contract, e.g. `{"register_balance_query": {"connection_id": .., "addr": .., "denom": .., "update_period": ..}}`.
+//!
+//! ```ignore
+//! let query_id = icq::register_balance_query(&sh, &network, &key, &contract, &BalanceQuery::new(&address, "uatom"), &[])?;
+//!
+//! icq::wait_for_query_result(&sh, &network, query_id, icq::DEFAULT_QUERY_TIMEOUT)?;
+//! ```
+
+use std::time::{Duration, Instant};
+
+use log::debug;
+use serde::Serialize;
+use xshell::Shell;
+
+use crate::{
+    cli::{poll_interval, Coin, Contract},
+    contract::execute,
+    key::Key,
+    network::Network,
+    Error,
+};
+
+use super::local::NTRN_GAIA_CONNECTION_ID;
+
+/// How often a registered query is updated by default, in blocks of the host chain
+pub const DEFAULT_UPDATE_PERIOD: u64 = 5;
+
+/// How long to wait for the relayer to submit a query result by default
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// A KV query of the balance of `addr` in `denom` on the host chain
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceQuery {
+    connection_id: String,
+    addr: String,
+    denom: String,
+    update_period: u64,
+}
+
+impl BalanceQuery {
+    /// Query the balance on the localnet connection to Gaia every `DEFAULT_UPDATE_PERIOD` blocks
+    #[must_use]
+    pub fn new(addr: &str, denom: &str) -> Self {
+        Self {
+            connection_id: NTRN_GAIA_CONNECTION_ID.to_owned(),
+            addr: addr.to_owned(),
+            denom: denom.to_owned(),
+            update_period: DEFAULT_UPDATE_PERIOD,
+        }
+    }
+
+    #[must_use]
+    pub fn connection(self, connection_id: &str) -> Self {
+        Self {
+            connection_id: connection_id.to_owned(),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn update_period(self, update_period: u64) -> Self {
+        Self {
+            update_period,
+            ..self
+        }
+    }
+}
+
+/// A TX query of the transfers to `recipient` on the host chain, the results are delivered to the owner contract's `sudo` entry point
+#[derive(Debug, Clone, Serialize)]
+pub struct TransfersQuery {
+    connection_id: String,
+    recipient: String,
+    update_period: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_height: Option<u64>,
+}
+
+impl TransfersQuery {
+    /// Query the transfers on the localnet connection to Gaia every `DEFAULT_UPDATE_PERIOD` blocks
+    #[must_use]
+    pub fn new(recipient: &str) -> Self {
+        Self {
+            connection_id: NTRN_GAIA_CONNECTION_ID.to_owned(),
+            recipient: recipient.to_owned(),
+            update_period: DEFAULT_UPDATE_PERIOD,
+            min_height: None,
+        }
+    }
+
+    #[must_use]
+    pub fn connection(self, connection_id: &str) -> Self {
+        Self {
+            connection_id: connection_id.to_owned(),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn update_period(self, update_period: u64) -> Self {
+        Self {
+            update_period,
+            ..self
+        }
+    }
+
+    /// Only match transfers included at or after `min_height` on the host chain
+    #[must_use]
+    pub fn min_height(self, min_height: u64) -> Self {
+        Self {
+            min_height: Some(min_height),
+            ..self
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum RegisterMsg<'a> {
+    RegisterBalanceQuery(&'a BalanceQuery),
+    RegisterTransfersQuery(&'a TransfersQuery),
+}
+
+/// Register the balance `query` by executing the `owner` contract from `from`, responding with the query ID.
+/// The owner pays the query deposit, attach it with `funds` if the contract does not hold it already.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Executing the owner contract fails
+/// - The query ID is missing from the tx events
+pub fn register_balance_query(
+    sh: &Shell,
+    network: &dyn Network,
+    from: &Key,
+    owner: &Contract,
+    query: &BalanceQuery,
+    funds: &[Coin],
+) -> Result<u64, Error> {
+    register(
+        sh,
+        network,
+        from,
+        owner,
+        &RegisterMsg::RegisterBalanceQuery(query),
+        funds,
+    )
+}
+
+/// Register the transfers `query` by executing the `owner` contract from `from`, responding with the query ID.
+/// The owner pays the query deposit, attach it with `funds` if the contract does not hold it already.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Executing the owner contract fails
+/// - The query ID is missing from the tx events
+pub fn register_tx_query(
+    sh: &Shell,
+    network: &dyn Network,
+    from: &Key,
+    owner: &Contract,
+    query: &TransfersQuery,
+    funds: &[Coin],
+) -> Result<u64, Error> {
+    register(
+        sh,
+        network,
+        from,
+        owner,
+        &RegisterMsg::RegisterTransfersQuery(query),
+        funds,
+    )
+}
+
+fn register(
+    sh: &Shell,
+    network: &dyn Network,
+    from: &Key,
+    owner: &Contract,
+    msg: &RegisterMsg,
+    funds: &[Coin],
+) -> Result<u64, Error> {
+    let res = funds
+        .iter()
+        .fold(execute(owner, msg), |tx, coin| {
+            tx.add_funds(coin.amount, &coin.denom)
+        })
+        .send_full(sh, network, from)?;
+
+    let query_id = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.as_slice())
+        .find(|attr| attr.key == "query_id")
+        .ok_or(Error::ExpectedQueryId)?
+        .value
+        .parse()?;
+
+    debug!("Registered interchain query {query_id} owned by {owner}");
+
+    Ok(query_id)
+}
+
+/// Keep querying the interchain query `query_id` until the relayer has submitted a result or `timeout` elapses,
+/// responding with the local height the result was submitted at.
+///
+/// KV query results are stored on chain. TX query results are passed to the owner contract's `sudo` entry point,
+/// so also query the contract to check it handled them.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Querying the interchain query fails
+/// - No result is submitted within `timeout`
+pub fn wait_for_query_result(
+    sh: &Shell,
+    network: &dyn Network,
+    query_id: u64,
+    timeout: Duration,
+) -> Result<u64, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let query = network
+            .cli(sh)?
+            .query(&node_uri)
+            .registered_query(query_id)?;
+
+        if query.last_submitted_result_local_height > 0 {
+            return Ok(query.last_submitted_result_local_height);
+        }
+
+        if Instant::now() >= deadline {
+            return Err(Error::QueryResultTimeout(query_id));
+        }
+
+        std::thread::sleep(poll_interval());
+    }
+}
@@ -10,18 +10,22 @@ use std::{
 
 use duct::{Expression as DuctExpression, Handle as DuctHandle};
 use log::{error, info};
+use sha2::{Digest, Sha256};
 use xshell::{cmd, Cmd as ShellCmd, Shell};
 
 use crate::{
-    cli::{wait_for_blocks_fn, Cli, Cmd},
-    key::{Key, KeyringBackend},
+    cli::{poll_interval, wait_for_blocks_fn, Cli, Cmd, ShellCmdExt},
+    contract::checksum,
+    ibc::{wait_for_channel_fn, DEFAULT_CHANNEL_TIMEOUT},
+    key::{Key, KeyRole, KeyringBackend},
     network::{
-        concat_paths,
+        concat_paths, docker_platform_args,
         gas::{Price as GasPrice, Prices as GasPrices},
-        home_path_prefix, make_abs_path, make_abs_root, ChainId, Clean, Initialize, Instance,
-        IntoForeground, Node, NodeUri, StartLocal,
+        genesis::{self, Preset},
+        home_path_prefix, host_is_arm64, make_abs_path, make_abs_root, ChainId, Clean, Initialize,
+        Instance, IntoForeground, Node, NodeUri, ResetState, ResourceLimits, StartLocal, HOME_ENV,
     },
-    Error,
+    progress, Error,
 };
 
 pub const NTRN_REPO_URL: &str = "https://github.com/neutron-org/neutron.git";
@@ -30,6 +34,12 @@ pub const NTRN_REPO_CLONE_DIR: &str = "neutron/src";
 pub const NTRN_BIN_PATH: &str = "bin/neutrond";
 pub const NTRN_LOGFILE: &str = "neutron/neutrond.log";
 pub const NTRN_CHAIN_HOME_DIR: &str = "neutron/data";
+/// Set to use a Neutron source tree, e.g. with `go mod vendor` run in it to build offline. Still needed with a prebuilt binary, for the Hermes config instead of cloning the repo
+pub const NTRN_SRC_ENV: &str = "COSMWASM_LOCALNET_NEUTRON_SRC";
+/// Set to use a prebuilt `neutrond`, built with `make install-test-binary` instead of building it
+pub const NTRN_BIN_ENV: &str = "COSMWASM_LOCALNET_NEUTRON_BIN";
+/// Set to the sha256 checksum the prebuilt binary must have
+pub const NTRN_BIN_SHA256_ENV: &str = "COSMWASM_LOCALNET_NEUTRON_BIN_SHA256";
 pub const NTRN_CHAIN_ID: &str = "test-1";
 pub const NTRN_CHAIN_DENOM: &str = "untrn";
 pub const NTRN_P2P_PORT: u16 = 26656;
@@ -38,6 +48,14 @@ pub const NTRN_REST_PORT: u16 = 1317;
 pub const NTRN_GRPC_PORT: u16 = 8090;
 pub const NTRN_GRPC_WEB_PORT: u16 = 8091;
 pub const NTRN_ROSETTA_PORT: u16 = 8080;
+pub const NTRN_WASM_CAPABILITIES: &[&str] = &[
+    "iterator",
+    "staking",
+    "stargate",
+    "neutron",
+    "cosmwasm_1_1",
+    "cosmwasm_1_2",
+];
 
 pub const GAIA_REPO_URL: &str = "https://github.com/cosmos/gaia.git";
 pub const GAIA_REPO_BRANCH: &str = "v13.0.2";
@@ -45,6 +63,12 @@ pub const GAIA_REPO_CLONE_DIR: &str = "gaia/src";
 pub const GAIA_BIN_PATH: &str = "bin/gaiad";
 pub const GAIA_LOGFILE: &str = "gaia/gaiad.log";
 pub const GAIA_CHAIN_HOME_DIR: &str = "gaia/data";
+/// Set to use a Gaia source tree, built offline when its dependencies are vendored instead of cloning the repo
+pub const GAIA_SRC_ENV: &str = "COSMWASM_LOCALNET_GAIA_SRC";
+/// Set to use a prebuilt `gaiad` instead of building it
+pub const GAIA_BIN_ENV: &str = "COSMWASM_LOCALNET_GAIA_BIN";
+/// Set to the sha256 checksum the prebuilt binary must have
+pub const GAIA_BIN_SHA256_ENV: &str = "COSMWASM_LOCALNET_GAIA_BIN_SHA256";
 pub const GAIA_CHAIN_ID: &str = "test-2";
 pub const GAIA_CHAIN_DENOM: &str = "uatom";
 pub const GAIA_P2P_PORT: u16 = 16656;
@@ -62,6 +86,12 @@ pub const HERMES_HOME_DIR: &str = ".hermes";
 pub const HERMES_LOGFILE: &str = ".hermes/hermes.log";
 pub const HERMES_CONFIG_FILE: &str = "config.toml";
 pub const HERMES_COPY_CONFIG_PATH: &str = "network/hermes/config.toml";
+/// Set to use the `ibc-relayer-cli` crate dir of a Hermes source tree with its dependencies vendored, installed with `--offline` instead of cloning the repo
+pub const HERMES_SRC_ENV: &str = "COSMWASM_LOCALNET_HERMES_SRC";
+/// Set to use a prebuilt `hermes` instead of building it
+pub const HERMES_BIN_ENV: &str = "COSMWASM_LOCALNET_HERMES_BIN";
+/// Set to the sha256 checksum the prebuilt binary must have
+pub const HERMES_BIN_SHA256_ENV: &str = "COSMWASM_LOCALNET_HERMES_BIN_SHA256";
 
 pub const ICQ_RLY_REPO_URL: &str = "https://github.com/neutron-org/neutron-query-relayer.git";
 pub const ICQ_RLY_REPO_BRANCH: &str = "feat/upd-sdk47";
@@ -69,6 +99,36 @@ pub const ICQ_RLY_REPO_CLONE_DIR: &str = "icq_rly/src";
 pub const ICQ_RLY_DB_PATH: &str = "icq_rly/db";
 pub const ICQ_RLY_BIN_PATH: &str = "bin/neutron_query_relayer";
 pub const ICQ_RLY_LOGFILE: &str = "icq_rly/icq_rly.log";
+/// Set to use an ICQ relayer source tree, built offline when its dependencies are vendored instead of cloning the repo
+pub const ICQ_RLY_SRC_ENV: &str = "COSMWASM_LOCALNET_ICQ_RELAYER_SRC";
+/// Set to use a prebuilt `neutron_query_relayer` instead of building it
+pub const ICQ_RLY_BIN_ENV: &str = "COSMWASM_LOCALNET_ICQ_RELAYER_BIN";
+/// Set to the sha256 checksum the prebuilt binary must have
+pub const ICQ_RLY_BIN_SHA256_ENV: &str = "COSMWASM_LOCALNET_ICQ_RELAYER_BIN_SHA256";
+
+/// Set to also serve a block explorer for the localnet at `http://127.0.0.1:{EXPLORER_PORT}`
+pub const EXPLORER_ENV: &str = "COSMWASM_LOCALNET_EXPLORER";
+pub const EXPLORER_IMAGE: &str = "ghcr.io/ping-pub/explorer:latest";
+pub const EXPLORER_CONTAINER_NAME: &str = "cosmwasm_xtask_explorer";
+pub const EXPLORER_PORT: u16 = 8888;
+pub const EXPLORER_CHAIN_CONFIG_DIR: &str = "explorer/chains";
+/// Where the explorer image reads its chain configs from
+pub const EXPLORER_CHAIN_CONFIG_MOUNT: &str = "/app/chains/mainnet";
+pub const EXPLORER_LOGFILE: &str = "explorer/explorer.log";
+
+/// Set to cache the initialized chain state & restore it instead of running genesis again after `Clean::clean_state`
+pub const SNAPSHOT_ENV: &str = "COSMWASM_LOCALNET_SNAPSHOTS";
+pub const SNAPSHOT_DIR: &str = "snapshots";
+/// Where the state right after genesis is kept for `ResetState::reset_state`
+pub const GENESIS_SNAPSHOT_DIR: &str = "genesis_snapshot";
+
+/// Records the version of each external component in the workspace root, so later builds clone the locked commits
+/// & fail rather than drift from them.
+/// Remove a component's line to lock the version it is next built from instead.
+pub const TOOLS_LOCK_FILE: &str = "tools.lock";
+
+/// The connection Hermes creates from Neutron to Gaia on startup
+pub const NTRN_GAIA_CONNECTION_ID: &str = "connection-0";
 
 pub const IBC_ATOM_DENOM: &str = "uibcatom";
 pub const IBC_USDC_DENOM: &str = "uibcusdc";
@@ -83,6 +143,226 @@ pub const VAL_MNEMONIC_2: &str = "angry twist harsh drastic left brass behave ho
 pub const RLY_MNEMONIC_1: &str = "alley afraid soup fall idea toss can goose become valve initial strong forward bright dish figure check leopard decide warfare hub unusual join cart";
 pub const RLY_MNEMONIC_2: &str = "record gift you once hip style during joke field prize dust unique length more pencil transfer quit train device arrive energy sort steak upset";
 
+/// Bump when the node configuration written during initialization changes in a way the other inputs of `snapshot_key`
+/// do not capture, so snapshots taken before are no longer restored. Version 1 lets the explorer make cross-origin
+/// requests to the LCD.
+const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Identifies the configuration the chain state was initialized with, so a snapshot is only restored into a matching localnet
+fn snapshot_key() -> String {
+    let config = [
+        env!("CARGO_PKG_VERSION"),
+        NTRN_REPO_BRANCH,
+        NTRN_CHAIN_ID,
+        NTRN_CHAIN_DENOM,
+        GAIA_REPO_BRANCH,
+        GAIA_CHAIN_ID,
+        GAIA_CHAIN_DENOM,
+        HERMES_CRATE_VERSION,
+        &SNAPSHOT_FORMAT_VERSION.to_string(),
+        ICQ_RLY_REPO_BRANCH,
+        &format!(
+            "{NTRN_P2P_PORT}:{NTRN_RPC_PORT}:{NTRN_REST_PORT}:{NTRN_ROSETTA_PORT}:{GAIA_P2P_PORT}:{GAIA_RPC_PORT}:{GAIA_REST_PORT}:{GAIA_ROSETTA_PORT}"
+        ),
+        &GENESIS_ALLOCATION.to_string(),
+        DEMO_MNEMONIC_1,
+        DEMO_MNEMONIC_2,
+        DEMO_MNEMONIC_3,
+        VAL_MNEMONIC_1,
+        VAL_MNEMONIC_2,
+        RLY_MNEMONIC_1,
+        RLY_MNEMONIC_2,
+    ];
+
+    let hash = Sha256::digest(config.join("\n"));
+
+    hex::encode(&hash[..8])
+}
+
+/// The role of the localnet key with the given `name`, `rly1` & `rly2` are used by Hermes for Neutron & Gaia respectively
+fn key_role(name: &str) -> KeyRole {
+    if name.starts_with("val") {
+        KeyRole::Validator
+    } else if name.starts_with("rly") {
+        KeyRole::Relayer
+    } else if name.starts_with("local") {
+        KeyRole::Demo
+    } else {
+        KeyRole::User
+    }
+}
+
+/// Whether this process runs translated by Rosetta on Apple Silicon, where the toolchains default to building `x86_64`
+fn translated_on_arm64() -> bool {
+    cfg!(target_os = "macos") && !cfg!(target_arch = "aarch64") && host_is_arm64()
+}
+
+/// The environment to build a Go service in the current dir with, for the native CPU & offline if its dependencies are vendored
+fn go_build_env(sh: &Shell) -> Vec<(&'static str, &'static str)> {
+    let mut env = vec![];
+
+    if sh.path_exists("vendor") {
+        // make go module cache not break rm -r
+        // https://go.dev/doc/go1.14#go-command
+        env.extend([("GOFLAGS", "-modcacherw -mod=vendor"), ("GOPROXY", "off")]);
+    } else {
+        env.push(("GOFLAGS", "-modcacherw"));
+    }
+
+    // Build for the native arm64 CPU rather than the toolchain default, so the services do not run emulated
+    if translated_on_arm64() {
+        // Cross compiling disables cgo, which wasmvm requires, unless it is enabled explicitly
+        env.extend([
+            ("GOARCH", "arm64"),
+            ("CGO_ENABLED", "1"),
+            ("CC", "clang -arch arm64"),
+        ]);
+    }
+
+    env
+}
+
+/// The path set in the `env` variable overriding a source tree or binary, if any
+fn vendored_path(env: &str) -> Option<PathBuf> {
+    std::env::var_os(env)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Copy a `vendored` source tree to `src_path`, where it is built like a clone
+fn copy_vendored_dir(sh: &Shell, vendored: &Path, src_path: &Path) -> Result<(), Error> {
+    if let Some(parent) = src_path.parent() {
+        sh.create_dir(parent)?;
+    }
+
+    cmd!(sh, "cp -R {vendored} {src_path}").logged().run()?;
+
+    Ok(())
+}
+
+/// The contents of `tools.lock`, empty if there is none yet
+fn read_tools_lock(sh: &Shell) -> Result<String, Error> {
+    let path = sh.current_dir().join(TOOLS_LOCK_FILE);
+
+    if sh.path_exists(&path) {
+        Ok(sh.read_file(&path)?)
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// The version of the `tool` recorded in the `lock`
+fn locked_version<'a>(lock: &'a str, tool: &str) -> Option<&'a str> {
+    lock.lines().find_map(|line| {
+        line.split_once(char::is_whitespace)
+            .filter(|(name, _)| *name == tool)
+            .map(|(_, locked)| locked.trim())
+    })
+}
+
+/// The commit of `repo_branch` recorded for the `tool` in `tools.lock`, a lock of another branch is left to `verify_or_lock`
+fn locked_commit(sh: &Shell, tool: &str, repo_branch: &str) -> Result<Option<String>, Error> {
+    let lock = read_tools_lock(sh)?;
+
+    let commit = locked_version(&lock, tool)
+        .and_then(|locked| locked.split_once(char::is_whitespace))
+        .filter(|(branch, _)| *branch == repo_branch)
+        .map(|(_, commit)| commit.trim().to_owned());
+
+    Ok(commit)
+}
+
+/// Check the `version` of the `tool` against the one recorded in `tools.lock`, recording it if there is none yet
+fn verify_or_lock(sh: &Shell, tool: &str, version: &str) -> Result<(), Error> {
+    let path = sh.current_dir().join(TOOLS_LOCK_FILE);
+
+    let lock = read_tools_lock(sh)?;
+
+    match locked_version(&lock, tool) {
+        Some(locked) if locked == version => Ok(()),
+        Some(locked) => Err(Error::ToolVersionDrift {
+            tool: tool.to_owned(),
+            locked: locked.to_owned(),
+            found: version.to_owned(),
+        }),
+        None => {
+            let mut lines: Vec<_> = lock
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .collect();
+
+            let entry = format!("{tool} {version}");
+
+            lines.push(&entry);
+
+            lines.sort_unstable();
+
+            sh.write_file(&path, lines.join("\n") + "\n")?;
+
+            Ok(())
+        }
+    }
+}
+
+/// Copy a `prebuilt` binary to `bin_path` instead of building it, verifying it first if its checksum is set in `sha256_env`
+fn copy_prebuilt_bin(
+    sh: &Shell,
+    prebuilt: &Path,
+    bin_path: &Path,
+    sha256_env: &str,
+) -> Result<(), Error> {
+    if let Ok(expected) = std::env::var(sha256_env) {
+        verify_checksum(prebuilt, &expected)?;
+    }
+
+    if let Some(parent) = bin_path.parent() {
+        sh.create_dir(parent)?;
+    }
+
+    sh.copy_file(prebuilt, bin_path)?;
+
+    Ok(())
+}
+
+/// Where the checksum of the installed binary at `bin_path` is recorded
+fn checksum_path(bin_path: &Path) -> PathBuf {
+    bin_path.with_extension("sha256")
+}
+
+/// Record the checksum of the binary just installed at `bin_path`, to verify it has not changed before each start
+fn record_checksum(sh: &Shell, bin_path: &Path) -> Result<(), Error> {
+    sh.write_file(checksum_path(bin_path), checksum(bin_path)?)?;
+
+    Ok(())
+}
+
+/// Verify the binary at `bin_path` still has the checksum recorded when it was installed, if any
+fn verify_installed(sh: &Shell, bin_path: &Path) -> Result<(), Error> {
+    let path = checksum_path(bin_path);
+
+    if !sh.path_exists(&path) {
+        return Ok(());
+    }
+
+    verify_checksum(bin_path, &sh.read_file(path)?)
+}
+
+fn verify_checksum(path: &Path, expected: &str) -> Result<(), Error> {
+    let expected = expected.trim().to_lowercase();
+
+    let found = checksum(path)?;
+
+    if found != expected {
+        return Err(Error::ChecksumMismatch {
+            path: path.display().to_string(),
+            expected,
+            found,
+        });
+    }
+
+    Ok(())
+}
+
 macro_rules! find_and_replace_in_file {
     ($sh:expr, $file_path:expr, $($pattern:expr => $replace:expr),+) => {
         let path = concat_paths!($sh.current_dir(), $file_path);
@@ -171,6 +451,7 @@ where
         sh,
         "config/app.toml",
         "enable = false"                => "enable = true",
+        "enabled-unsafe-cors = false"   => "enabled-unsafe-cors = true",
         "swagger = false"               => "swagger = true",
         "prometheus-retention-time = 0" => "prometheus-retention-time = 1000" ,
         r#"minimum-gas-prices = """#    =>
@@ -203,8 +484,60 @@ macro_rules! impl_path_fns {
     }
 
 macro_rules! impl_clone_and_run {
-    ($t:ident, $repo_url:expr, $repo_branch:expr) => {
+    ($t:ident, $repo_url:expr, $repo_branch:expr, $src_env:expr, $bin_env:expr, $sha256_env:expr) => {
         impl $t {
+            /// The name the version of the component is recorded under in `tools.lock`
+            fn tool(&self) -> String {
+                self.bin_path()
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            }
+
+            /// Copy the vendored source tree if one is set, otherwise clone the repo at the commit locked in `tools.lock`,
+            /// or the tip of its branch if none is locked yet, unless the source is already there
+            fn fetch_src(&self, sh: &Shell) -> Result<(), Error> {
+                let src_path = self.src_path();
+                let repo_url = $repo_url;
+                let repo_branch = $repo_branch;
+
+                if sh.path_exists(src_path) {
+                    return Ok(());
+                }
+
+                if let Some(vendored) = vendored_path($src_env) {
+                    return progress::phase(&format!("Copying {}", vendored.display()), || {
+                        copy_vendored_dir(sh, &vendored, src_path)
+                    });
+                }
+
+                let Some(commit) = locked_commit(sh, &self.tool(), repo_branch)? else {
+                    return progress::phase(&format!("Cloning {repo_url}"), || {
+                        progress::run(cmd!(
+                            sh,
+                            "git -c transfer.fsckObjects=true clone --depth 1 --branch {repo_branch} {repo_url} {src_path}"
+                        ))
+                    });
+                };
+
+                progress::phase(&format!("Cloning {repo_url} at {commit}"), || {
+                    cmd!(sh, "git init --quiet {src_path}").quiet().run()?;
+                    cmd!(sh, "git -C {src_path} remote add origin {repo_url}").quiet().run()?;
+                    progress::run(cmd!(
+                        sh,
+                        "git -c transfer.fsckObjects=true -C {src_path} fetch --depth 1 origin {commit}"
+                    ))?;
+                    cmd!(sh, "git -C {src_path} checkout --quiet {commit}").quiet().run()?;
+
+                    Ok(())
+                })
+                // Unlike a failed clone, a failed fetch leaves the repo behind, which would be taken as the source
+                .inspect_err(|_| {
+                    sh.remove_path(src_path).ok();
+                })
+            }
+
             fn clone_and_run<F>(&self, sh: &Shell, run_fn: F) -> Result<(), Error>
             where
                 F: FnOnce(&Path) -> Result<(), Error>,
@@ -214,20 +547,35 @@ macro_rules! impl_clone_and_run {
                 let repo_url = $repo_url;
                 let repo_branch = $repo_branch;
 
-                if !sh.path_exists(src_path) {
-                    cmd!(
-                        sh,
-                        "git clone --depth 1 --branch {repo_branch} {repo_url} {src_path}"
-                    )
-                    .run()?;
+                // A prebuilt binary needs no source, so nothing is fetched
+                if let Some(prebuilt) = vendored_path($bin_env) {
+                    if !sh.path_exists(bin_path) {
+                        copy_prebuilt_bin(sh, &prebuilt, bin_path, $sha256_env)?;
+
+                        record_checksum(sh, bin_path)?;
+                    }
+
+                    return Ok(());
                 }
 
-                let root = sh.current_dir();
+                self.fetch_src(sh)?;
+
+                // A vendored tree without git history has no commit to lock. Without its own `.git`, git would find
+                // the repo of the workspace the source is kept in & report its commit instead.
+                if sh.path_exists(src_path.join(".git")) {
+                    let commit = cmd!(sh, "git -C {src_path} rev-parse HEAD").quiet().read()?;
+
+                    verify_or_lock(sh, &self.tool(), &format!("{repo_branch} {commit}"))?;
+                }
 
                 if !sh.path_exists(bin_path) {
+                    let root = sh.current_dir();
+
                     let _cd = sh.push_dir(src_path);
 
-                    run_fn(&root)?;
+                    progress::phase(&format!("Building {repo_url}"), || run_fn(&root))?;
+
+                    record_checksum(sh, bin_path)?;
                 }
 
                 Ok(())
@@ -290,7 +638,7 @@ impl Handle {
         };
 
         let inner = expr
-            .env("HOME", home)
+            .env(HOME_ENV, home)
             .stderr_to_stdout()
             .stdout_file(logfile)
             .start()?;
@@ -301,6 +649,32 @@ impl Handle {
         })
     }
 
+    /// Start a long running service, bounding its runtime & lowering its priority according to the `limits`
+    fn start_limited(
+        sh: &Shell,
+        expr: &DuctExpression,
+        logfile_path: &Path,
+        logfile_mode: LogfileMode,
+        limits: &ResourceLimits,
+    ) -> Result<Self, Error> {
+        let expr = limits
+            .runtime_env()
+            .into_iter()
+            .fold(expr.clone(), |expr, (key, value)| expr.env(key, value));
+
+        let handle = Self::try_from_duct_expression(sh, &expr, logfile_path, logfile_mode)?;
+
+        if let (Some(nice), Some(inner)) = (limits.nice, &handle.inner) {
+            for pid in inner.pids() {
+                duct::cmd!("renice", "-n", nice.to_string(), "-p", pid.to_string())
+                    .stdout_null()
+                    .run()?;
+            }
+        }
+
+        Ok(handle)
+    }
+
     fn wait(&mut self) -> Result<(), Error> {
         if let Some(inner) = self.inner.take() {
             inner.into_output()?;
@@ -338,7 +712,14 @@ impl_path_fns!(Neutrond, src_path, home_path, bin_path, logfile_path);
 
 impl_is_initialised!(Neutrond, src_path, home_path, bin_path);
 
-impl_clone_and_run!(Neutrond, NTRN_REPO_URL, NTRN_REPO_BRANCH);
+impl_clone_and_run!(
+    Neutrond,
+    NTRN_REPO_URL,
+    NTRN_REPO_BRANCH,
+    NTRN_SRC_ENV,
+    NTRN_BIN_ENV,
+    NTRN_BIN_SHA256_ENV
+);
 
 impl_node_uri!(Neutrond, NTRN_RPC_PORT);
 
@@ -361,16 +742,14 @@ impl Neutrond {
 
     fn init(&self, sh: &Shell) -> Result<(), Error> {
         self.clone_and_run(sh, |root| {
-            cmd!(sh, "make install-test-binary")
-                .env(
-                    "GOPATH",
-                    concat_paths!(root.to_owned(), home_path_prefix!()),
-                )
-                // make go module cache not break rm -r
-                // https://go.dev/doc/go1.14#go-command
-                .env("GOFLAGS", "-modcacherw")
-                .run()
-                .map_err(Error::from)
+            progress::run(
+                cmd!(sh, "make install-test-binary")
+                    .env(
+                        "GOPATH",
+                        concat_paths!(root.to_owned(), home_path_prefix!()),
+                    )
+                    .envs(go_build_env(sh)),
+            )
         })?;
 
         let bin_path = self.bin_path();
@@ -379,21 +758,25 @@ impl Neutrond {
 
         sh.remove_path(home_path).ok();
 
-        init_chain(
-            sh,
-            || self.cli(sh),
-            home_path,
-            InitParams {
-                chain_id: NTRN_CHAIN_ID,
-                stake_denom: NTRN_CHAIN_DENOM,
-                p2p_port: NTRN_P2P_PORT,
-                rpc_port: NTRN_RPC_PORT,
-                rest_port: NTRN_REST_PORT,
-                rosetta_port: NTRN_ROSETTA_PORT,
-            },
-        )?;
+        progress::phase(&format!("Initializing {NTRN_CHAIN_ID} genesis"), || {
+            init_chain(
+                sh,
+                || self.cli(sh),
+                home_path,
+                InitParams {
+                    chain_id: NTRN_CHAIN_ID,
+                    stake_denom: NTRN_CHAIN_DENOM,
+                    p2p_port: NTRN_P2P_PORT,
+                    rpc_port: NTRN_RPC_PORT,
+                    rest_port: NTRN_REST_PORT,
+                    rosetta_port: NTRN_ROSETTA_PORT,
+                },
+            )
+        })?;
 
-        cmd!(sh, "{bin_path} add-consumer-section --home {home_path}").run()?;
+        cmd!(sh, "{bin_path} add-consumer-section --home {home_path}")
+            .logged()
+            .run()?;
 
         let _cd = sh.push_dir(home_path);
 
@@ -418,7 +801,9 @@ impl Neutrond {
         Ok(())
     }
 
-    fn start(&self, sh: &Shell) -> Result<Handle, Error> {
+    fn start(&self, sh: &Shell, limits: &ResourceLimits) -> Result<Handle, Error> {
+        verify_installed(sh, self.bin_path())?;
+
         let expr = duct::cmd!(
             self.bin_path(),
             "start",
@@ -434,7 +819,13 @@ impl Neutrond {
             "--trace"
         );
 
-        Handle::try_from_duct_expression(sh, &expr, self.logfile_path(), LogfileMode::Overwrite)
+        Handle::start_limited(
+            sh,
+            &expr,
+            self.logfile_path(),
+            LogfileMode::Overwrite,
+            limits,
+        )
     }
 }
 
@@ -449,7 +840,14 @@ impl_path_fns!(Gaiad, src_path, home_path, bin_path, logfile_path);
 
 impl_is_initialised!(Gaiad, src_path, home_path, bin_path);
 
-impl_clone_and_run!(Gaiad, GAIA_REPO_URL, GAIA_REPO_BRANCH);
+impl_clone_and_run!(
+    Gaiad,
+    GAIA_REPO_URL,
+    GAIA_REPO_BRANCH,
+    GAIA_SRC_ENV,
+    GAIA_BIN_ENV,
+    GAIA_BIN_SHA256_ENV
+);
 
 impl_node_uri!(Gaiad, GAIA_RPC_PORT);
 
@@ -478,32 +876,32 @@ impl Gaiad {
                 "$(BUILD_TARGETS): check_version go.sum $(BUILDDIR)/" => "$(BUILD_TARGETS): go.sum $(BUILDDIR)/"
             );
 
-            cmd!(sh, "make install")
-                .env("GOPATH", concat_paths!(root.to_owned(), home_path_prefix!()))
-                // make go module cache not break rm -r
-                // https://go.dev/doc/go1.14#go-command
-                .env("GOFLAGS", "-modcacherw") 
-                .run()
-                .map_err(Error::from)
+            progress::run(
+                cmd!(sh, "make install")
+                    .env("GOPATH", concat_paths!(root.to_owned(), home_path_prefix!()))
+                    .envs(go_build_env(sh)),
+            )
         })?;
 
         let home_path = self.home_path();
 
         sh.remove_path(home_path).ok();
 
-        let keys = init_chain(
-            sh,
-            || self.cli(sh),
-            home_path,
-            InitParams {
-                chain_id: GAIA_CHAIN_ID,
-                stake_denom: GAIA_CHAIN_DENOM,
-                p2p_port: GAIA_P2P_PORT,
-                rpc_port: GAIA_RPC_PORT,
-                rest_port: GAIA_REST_PORT,
-                rosetta_port: GAIA_ROSETTA_PORT,
-            },
-        )?;
+        let keys = progress::phase(&format!("Initializing {GAIA_CHAIN_ID} genesis"), || {
+            init_chain(
+                sh,
+                || self.cli(sh),
+                home_path,
+                InitParams {
+                    chain_id: GAIA_CHAIN_ID,
+                    stake_denom: GAIA_CHAIN_DENOM,
+                    p2p_port: GAIA_P2P_PORT,
+                    rpc_port: GAIA_RPC_PORT,
+                    rest_port: GAIA_REST_PORT,
+                    rosetta_port: GAIA_ROSETTA_PORT,
+                },
+            )
+        })?;
 
         let _cd = sh.push_dir(home_path);
 
@@ -533,7 +931,9 @@ impl Gaiad {
         Ok(())
     }
 
-    fn start(&self, sh: &Shell) -> Result<Handle, Error> {
+    fn start(&self, sh: &Shell, limits: &ResourceLimits) -> Result<Handle, Error> {
+        verify_installed(sh, self.bin_path())?;
+
         let expr = duct::cmd!(
             self.bin_path(),
             "start",
@@ -549,7 +949,13 @@ impl Gaiad {
             "--trace"
         );
 
-        Handle::try_from_duct_expression(sh, &expr, self.logfile_path(), LogfileMode::Overwrite)
+        Handle::start_limited(
+            sh,
+            &expr,
+            self.logfile_path(),
+            LogfileMode::Overwrite,
+            limits,
+        )
     }
 }
 
@@ -584,13 +990,46 @@ impl Hermesd {
     fn init(&self, sh: &Shell, neutrond: &Neutrond) -> Result<(), Error> {
         if !sh.path_exists(self.bin_path()) {
             let root = make_abs_root!(sh);
-            cmd!(
-                sh,
-                "cargo install {HERMES_CRATE} --bin {HERMES_CRATE_BIN} --version {HERMES_CRATE_VERSION} --locked --root {root}"
-            )
-            .run()?;
+            let target = if translated_on_arm64() {
+                vec!["--target", "aarch64-apple-darwin"]
+            } else {
+                vec![]
+            };
+            if let Some(prebuilt) = vendored_path(HERMES_BIN_ENV) {
+                copy_prebuilt_bin(sh, &prebuilt, self.bin_path(), HERMES_BIN_SHA256_ENV)?;
+            } else if let Some(vendored) = vendored_path(HERMES_SRC_ENV) {
+                progress::phase("Installing hermes", || {
+                    progress::run(cmd!(
+                        sh,
+                        "cargo install --path {vendored} --bin {HERMES_CRATE_BIN} --locked --offline --root {root} {target...}"
+                    ))
+                })?;
+            } else {
+                progress::phase("Installing hermes", || {
+                    progress::run(cmd!(
+                        sh,
+                        "cargo install {HERMES_CRATE} --bin {HERMES_CRATE_BIN} --version {HERMES_CRATE_VERSION} --locked --root {root} {target...}"
+                    ))
+                })?;
+            }
+
+            record_checksum(sh, self.bin_path())?;
         }
 
+        let bin_path = self.bin_path();
+
+        verify_installed(sh, bin_path)?;
+
+        let version = cmd!(sh, "{bin_path} --version").quiet().read()?;
+
+        // Reported as `hermes <version>`
+        let version = version.split_whitespace().last().unwrap_or_default();
+
+        verify_or_lock(sh, HERMES_CRATE_BIN, version)?;
+
+        // The config ships with the Neutron source, which is not fetched when neutrond is prebuilt
+        neutrond.fetch_src(sh)?;
+
         let copy_config_src =
             concat_paths!(neutrond.src_path().to_owned(), HERMES_COPY_CONFIG_PATH);
 
@@ -610,7 +1049,8 @@ impl Hermesd {
 
         self.cli(sh)
             .args(["keys", "delete", "--chain", NTRN_CHAIN_ID, "--all"])
-            .env("HOME", make_abs_root!(sh))
+            .env(HOME_ENV, make_abs_root!(sh))
+            .logged()
             .run()?;
 
         self.cli(sh)
@@ -623,13 +1063,15 @@ impl Hermesd {
                 NTRN_CHAIN_ID,
                 "--mnemonic-file",
             ])
-            .env("HOME", make_abs_root!(sh))
+            .env(HOME_ENV, make_abs_root!(sh))
             .arg(&mnemonic1_file)
+            .logged()
             .run()?;
 
         self.cli(sh)
             .args(["keys", "delete", "--chain", GAIA_CHAIN_ID, "--all"])
-            .env("HOME", make_abs_root!(sh))
+            .env(HOME_ENV, make_abs_root!(sh))
+            .logged()
             .run()?;
 
         self.cli(sh)
@@ -642,16 +1084,19 @@ impl Hermesd {
                 GAIA_CHAIN_ID,
                 "--mnemonic-file",
             ])
-            .env("HOME", make_abs_root!(sh))
+            .env(HOME_ENV, make_abs_root!(sh))
             .arg(&mnemonic2_file)
+            .logged()
             .run()?;
 
         Ok(())
     }
 
-    fn start(&self, sh: &Shell) -> Result<Handle, Error> {
+    fn start(&self, sh: &Shell, limits: &ResourceLimits) -> Result<Handle, Error> {
         let bin_path = self.bin_path();
 
+        verify_installed(sh, bin_path)?;
+
         let config_path = self.config_file_path();
 
         // Why do you need this Hermes?
@@ -686,7 +1131,7 @@ impl Hermesd {
                 "--a-chain",
                 NTRN_CHAIN_ID,
                 "--a-connection",
-                "connection-0",
+                NTRN_GAIA_CONNECTION_ID,
                 "--a-port",
                 "transfer",
                 "--b-port",
@@ -697,11 +1142,12 @@ impl Hermesd {
         )?
         .wait()?;
 
-        Handle::try_from_duct_expression(
+        Handle::start_limited(
             sh,
             &duct::cmd!(bin_path, "--config", config_path, "start"),
             self.logfile_path(),
             LogfileMode::Append,
+            limits,
         )
     }
 }
@@ -717,7 +1163,14 @@ impl_path_fns!(IcqRlyd, src_path, bin_path, db_path, logfile_path);
 
 impl_is_initialised!(IcqRlyd, src_path, bin_path);
 
-impl_clone_and_run!(IcqRlyd, ICQ_RLY_REPO_URL, ICQ_RLY_REPO_BRANCH);
+impl_clone_and_run!(
+    IcqRlyd,
+    ICQ_RLY_REPO_URL,
+    ICQ_RLY_REPO_BRANCH,
+    ICQ_RLY_SRC_ENV,
+    ICQ_RLY_BIN_ENV,
+    ICQ_RLY_BIN_SHA256_ENV
+);
 
 impl IcqRlyd {
     fn new(sh: &Shell) -> Self {
@@ -731,20 +1184,26 @@ impl IcqRlyd {
 
     fn init(&self, sh: &Shell) -> Result<(), Error> {
         self.clone_and_run(sh, |root| {
-            cmd!(sh, "make install")
-                .env(
-                    "GOPATH",
-                    concat_paths!(root.to_owned(), home_path_prefix!()),
-                )
-                // make go module cache not break rm -r
-                // https://go.dev/doc/go1.14#go-command
-                .env("GOFLAGS", "-modcacherw")
-                .run()
-                .map_err(Error::from)
+            progress::run(
+                cmd!(sh, "make install")
+                    .env(
+                        "GOPATH",
+                        concat_paths!(root.to_owned(), home_path_prefix!()),
+                    )
+                    .envs(go_build_env(sh)),
+            )
         })
     }
 
-    fn start(&self, sh: &Shell, neutrond: &Neutrond, gaiad: &Gaiad) -> Result<Handle, Error> {
+    fn start(
+        &self,
+        sh: &Shell,
+        neutrond: &Neutrond,
+        gaiad: &Gaiad,
+        limits: &ResourceLimits,
+    ) -> Result<Handle, Error> {
+        verify_installed(sh, self.bin_path())?;
+
         macro_rules! set_env_vars {
             ($cmd:ident, $($key:literal = $value:literal),+) => {{
                 let vars = [
@@ -807,7 +1266,107 @@ impl IcqRlyd {
         .env("RELAYER_TARGET_CHAIN_HOME_DIR", gaiad.home_path())
         .env("RELAYER_STORAGE_PATH", self.db_path());
 
-        Handle::try_from_duct_expression(sh, &cmd, self.logfile_path(), LogfileMode::Overwrite)
+        Handle::start_limited(
+            sh,
+            &cmd,
+            self.logfile_path(),
+            LogfileMode::Overwrite,
+            limits,
+        )
+    }
+}
+
+struct Explorer {
+    chain_config_dir: PathBuf,
+    logfile_path: PathBuf,
+}
+
+impl_path_fns!(Explorer, chain_config_dir, logfile_path);
+
+/// Removes the explorer container when dropped, killing `docker run` alone leaves it running
+struct ExplorerHandle {
+    _container: Handle,
+}
+
+impl Drop for ExplorerHandle {
+    fn drop(&mut self) {
+        let _ = duct::cmd!("docker", "rm", "--force", EXPLORER_CONTAINER_NAME)
+            .stdout_null()
+            .stderr_null()
+            .run();
+    }
+}
+
+impl Explorer {
+    fn new(sh: &Shell) -> Self {
+        Self {
+            chain_config_dir: make_abs_path!(sh, EXPLORER_CHAIN_CONFIG_DIR),
+            logfile_path: make_abs_path!(sh, EXPLORER_LOGFILE),
+        }
+    }
+
+    /// Serve the explorer if `EXPLORER_ENV` is set, pointed at the Neutron LCD & RPC
+    fn start(&self, sh: &Shell, limits: &ResourceLimits) -> Result<Option<ExplorerHandle>, Error> {
+        if std::env::var_os(EXPLORER_ENV).is_none() {
+            return Ok(None);
+        }
+
+        let chain_config = serde_json::json!({
+            "chain_name": "neutron-localnet",
+            "registry_name": "neutron",
+            "api": [format!("http://127.0.0.1:{NTRN_REST_PORT}")],
+            "rpc": [format!("http://127.0.0.1:{NTRN_RPC_PORT}")],
+            "sdk_version": "0.47",
+            "coin_type": "118",
+            "min_tx_fee": "1000",
+            "addr_prefix": "neutron",
+            "assets": [{
+                "base": NTRN_CHAIN_DENOM,
+                "symbol": "NTRN",
+                "exponent": "6",
+            }],
+        });
+
+        let chain_config_dir = self.chain_config_dir();
+
+        sh.create_dir(chain_config_dir)?;
+
+        sh.write_file(
+            chain_config_dir.join("neutron-localnet.json"),
+            serde_json::to_string_pretty(&chain_config)?,
+        )?;
+
+        // A container left by a run which did not stop cleanly holds the name & port
+        let _ = duct::cmd!("docker", "rm", "--force", EXPLORER_CONTAINER_NAME)
+            .stdout_null()
+            .stderr_null()
+            .run();
+
+        let args = [
+            "run".to_owned(),
+            "--rm".to_owned(),
+            format!("--name={EXPLORER_CONTAINER_NAME}"),
+            format!("--publish=127.0.0.1:{EXPLORER_PORT}:80"),
+            format!(
+                "--volume={}:{EXPLORER_CHAIN_CONFIG_MOUNT}:ro",
+                chain_config_dir.display()
+            ),
+        ]
+        .into_iter()
+        .chain(limits.docker_args())
+        .chain(docker_platform_args(sh, EXPLORER_IMAGE))
+        .chain([EXPLORER_IMAGE.to_owned()]);
+
+        let handle = Handle::try_from_duct_expression(
+            sh,
+            &duct::cmd("docker", args),
+            self.logfile_path(),
+            LogfileMode::Overwrite,
+        )?;
+
+        info!("explorer serving at http://127.0.0.1:{EXPLORER_PORT}");
+
+        Ok(Some(ExplorerHandle { _container: handle }))
     }
 }
 
@@ -816,6 +1375,7 @@ pub struct Local {
     pub gaiad: Gaiad,
     hermesd: Hermesd,
     icq_rlyd: IcqRlyd,
+    explorer: Explorer,
 }
 
 impl Local {
@@ -825,10 +1385,13 @@ impl Local {
             gaiad: Gaiad::new(sh),
             hermesd: Hermesd::new(sh),
             icq_rlyd: IcqRlyd::new(sh),
+            explorer: Explorer::new(sh),
         }
     }
 
     fn init(&self, sh: &Shell) -> Result<(), Error> {
+        ensure_unix_host()?;
+
         if self.neutrond.is_initialized(sh)
             && self.gaiad.is_initialized(sh)
             && self.hermesd.is_initialized(sh)
@@ -837,23 +1400,95 @@ impl Local {
             return Ok(());
         }
 
-        self.neutrond.init(sh)?;
+        let snapshot_path = std::env::var_os(SNAPSHOT_ENV)
+            .is_some()
+            .then(|| make_abs_path!(sh, SNAPSHOT_DIR, snapshot_key()));
+
+        let restored = match &snapshot_path {
+            Some(snapshot_path) => self.restore_snapshot(sh, snapshot_path)?,
+            None => false,
+        };
 
-        self.gaiad.init(sh)?;
+        if !restored {
+            self.neutrond.init(sh)?;
 
-        self.hermesd.init(sh, &self.neutrond)?;
+            self.gaiad.init(sh)?;
 
-        self.icq_rlyd.init(sh)?;
+            self.hermesd.init(sh, &self.neutrond)?;
+
+            self.icq_rlyd.init(sh)?;
+
+            if let Some(snapshot_path) = &snapshot_path {
+                self.save_snapshot(sh, snapshot_path)?;
+            }
+        }
+
+        self.save_snapshot(sh, &make_abs_path!(sh, GENESIS_SNAPSHOT_DIR))
+    }
+
+    /// The chain home directories & Hermes home (with its keys) captured in a snapshot
+    fn snapshot_dirs(&self) -> [(&Path, &str); 3] {
+        [
+            (self.neutrond.home_path(), "neutron"),
+            (self.gaiad.home_path(), "gaia"),
+            (self.hermesd.home_path(), "hermes"),
+        ]
+    }
+
+    /// Restore the state in `snapshot_path` if it exists & the binaries are already built, responding with whether it was restored
+    fn restore_snapshot(&self, sh: &Shell, snapshot_path: &Path) -> Result<bool, Error> {
+        let binaries_built = [
+            self.neutrond.bin_path(),
+            self.gaiad.bin_path(),
+            self.hermesd.bin_path(),
+            self.icq_rlyd.bin_path(),
+        ]
+        .iter()
+        .all(|path| sh.path_exists(path));
+
+        if !binaries_built || !sh.path_exists(snapshot_path) {
+            return Ok(false);
+        }
+
+        info!("Restoring chain state from {}", snapshot_path.display());
+
+        for (home_path, name) in self.snapshot_dirs() {
+            let snapshot = snapshot_path.join(name);
+
+            sh.remove_path(home_path).ok();
+
+            cmd!(sh, "cp -a {snapshot} {home_path}").logged().run()?;
+        }
+
+        Ok(true)
+    }
+
+    fn save_snapshot(&self, sh: &Shell, snapshot_path: &Path) -> Result<(), Error> {
+        sh.remove_path(snapshot_path).ok();
+
+        sh.create_dir(snapshot_path)?;
+
+        for (home_path, name) in self.snapshot_dirs() {
+            let snapshot = snapshot_path.join(name);
+
+            cmd!(sh, "cp -a {home_path} {snapshot}").logged().run()?;
+        }
+
+        info!("Saved chain state snapshot to {}", snapshot_path.display());
 
         Ok(())
     }
 
     fn start(&self, sh: &Shell) -> Result<Handles, Error> {
+        ensure_unix_host()?;
+
+        let limits = ResourceLimits::from_env()?;
+
         info!("starting neutron");
-        let ntrn = self.neutrond.start(sh)?;
+        let ntrn = self.neutrond.start(sh, &limits)?;
 
         info!("starting gaia");
-        let gaia = self.gaiad.start(sh)?;
+        let gaia = self.gaiad.start(sh, &limits)?;
 
         info!("waiting for neutron blocks");
         wait_for_blocks_fn(|| Ok(self.neutrond.cli(sh)), &self.neutrond.node_uri())?;
@@ -862,20 +1497,47 @@ impl Local {
         wait_for_blocks_fn(|| Ok(self.gaiad.cli(sh)), &self.gaiad.node_uri())?;
 
         info!("starting hermes");
-        let hermes = self.hermesd.start(sh)?;
+        let hermes = self.hermesd.start(sh, &limits)?;
+
+        info!("waiting for the transfer channel to open");
+        wait_for_channel_fn(
+            || Ok(self.neutrond.cli(sh)),
+            &self.neutrond.node_uri(),
+            "transfer",
+            &ChainId::from(GAIA_CHAIN_ID.to_owned()),
+            DEFAULT_CHANNEL_TIMEOUT,
+        )?;
 
         info!("starting ICQ relayer");
-        let icq_rly = self.icq_rlyd.start(sh, &self.neutrond, &self.gaiad)?;
+        let icq_rly = self
+            .icq_rlyd
+            .start(sh, &self.neutrond, &self.gaiad, &limits)?;
+
+        let explorer = self.explorer.start(sh, &limits)?;
 
         Ok(Handles {
             ntrn,
             _gaia: gaia,
             _icq_rly: icq_rly,
             _hermes: hermes,
+            _explorer: explorer,
         })
     }
 }
 
+/// The services are built with `make` & supervised as native processes, neither of which works on a Windows host,
+/// where the localnet has to be run from within WSL instead
+fn ensure_unix_host() -> Result<(), Error> {
+    if cfg!(windows) {
+        return Err(Error::UnsupportedHost(
+            "the Neutron localnet requires a Unix host, run it from within WSL, as `cli_app` does"
+                .to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
 impl Initialize for Local {
     type Instance = Instance<Local>;
 
@@ -884,9 +1546,58 @@ impl Initialize for Local {
 
         network.init(sh)?;
 
-        let keys = network.neutrond.cli(sh).list_keys(KeyringBackend::Test)?;
+        let mut instance = Instance::new(network);
+
+        instance.keys = instance
+            .network()
+            .neutrond
+            .cli(sh)
+            .list_keys(KeyringBackend::Test)?
+            .into_iter()
+            .map(|key| {
+                let role = key_role(key.name());
+                key.with_role(role)
+            })
+            .collect();
+
+        Ok(instance)
+    }
+}
+
+impl Local {
+    /// Initialize the localnet from scratch with the genesis `presets` applied to the Neutron chain, replacing any existing state.
+    /// Gaia keeps its default genesis, which already hosts interchain accounts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Removing the existing state or initializing the network fails
+    /// - Applying a preset fails, see `genesis::apply`
+    /// - Replacing the genesis fails
+    pub fn initialize_with_presets(
+        sh: &Shell,
+        presets: &[Preset],
+    ) -> Result<Instance<Local>, Error> {
+        Self::clean_state(sh)?;
+
+        let instance = Self::initialize(sh)?;
+
+        let genesis_path = instance
+            .network()
+            .neutrond
+            .home_path()
+            .join("config/genesis.json");
 
-        Ok(Instance { keys, network })
+        let genesis = genesis::apply(&sh.read_file(&genesis_path)?, presets)?;
+
+        sh.write_file(genesis_path, genesis)?;
+
+        // Reset to the state with the presets applied
+        instance
+            .network()
+            .save_snapshot(sh, &make_abs_path!(sh, GENESIS_SNAPSHOT_DIR))?;
+
+        Ok(instance)
     }
 }
 
@@ -901,6 +1612,7 @@ pub struct Handles {
     _gaia: Handle,
     _icq_rly: Handle,
     _hermes: Handle,
+    _explorer: Option<ExplorerHandle>,
 }
 
 fn follow_file(path: &Path) -> Result<(), Error> {
@@ -919,10 +1631,10 @@ fn follow_file(path: &Path) -> Result<(), Error> {
 
     while keep_running.load(Ordering::Relaxed) {
         while reader.read_line(&mut line)? > 0 {
-            eprint!("{line}");
+            info!("{}", line.trim_end());
             line.clear();
         }
-        std::thread::sleep(std::time::Duration::from_millis(250));
+        std::thread::sleep(poll_interval());
     }
 
     Ok(())
@@ -946,6 +1658,36 @@ impl StartLocal for Instance<Local> {
     }
 }
 
+impl ResetState for Instance<Local> {
+    fn reset_state(&self, sh: &Shell, handle: Handles) -> Result<Handles, Error> {
+        let snapshot_path = make_abs_path!(sh, GENESIS_SNAPSHOT_DIR);
+
+        if !sh.path_exists(&snapshot_path) {
+            return Err(Error::MissingGenesisSnapshot(
+                snapshot_path.display().to_string(),
+            ));
+        }
+
+        // Dropping the handles kills the services & waits for them to exit
+        drop(handle);
+
+        info!("Resetting chain state from {}", snapshot_path.display());
+
+        for (home_path, name) in self.network().snapshot_dirs() {
+            sh.remove_path(home_path)?;
+
+            let snapshot = snapshot_path.join(name);
+
+            cmd!(sh, "cp -a {snapshot} {home_path}").logged().run()?;
+        }
+
+        // The relayer would otherwise resume from heights the chains never reach again
+        sh.remove_path(self.network().icq_rlyd.db_path())?;
+
+        self.network().start(sh)
+    }
+}
+
 impl Node for Instance<Local> {
     fn node_uri(&self, _sh: &Shell) -> Result<NodeUri, Error> {
         Ok(self.network().neutrond.node_uri())
@@ -954,6 +1696,21 @@ impl Node for Instance<Local> {
     fn chain_id(&self) -> ChainId {
         ChainId::from(NTRN_CHAIN_ID.to_owned())
     }
+
+    fn wasm_capabilities(&self) -> &[&str] {
+        NTRN_WASM_CAPABILITIES
+    }
+
+    fn faucet(&self) -> Option<&str> {
+        Some("local3")
+    }
+
+    fn logs_hint(&self) -> Option<String> {
+        Some(format!(
+            "see {}",
+            self.network().neutrond.logfile_path().display()
+        ))
+    }
 }
 
 impl Clean for Local {
@@ -962,6 +1719,8 @@ impl Clean for Local {
         sh.remove_path(make_abs_path!(sh, GAIA_CHAIN_HOME_DIR)).ok();
         sh.remove_path(make_abs_path!(sh, HERMES_HOME_DIR)).ok();
         sh.remove_path(make_abs_path!(sh, ICQ_RLY_DB_PATH)).ok();
+        sh.remove_path(make_abs_path!(sh, GENESIS_SNAPSHOT_DIR))
+            .ok();
         Ok(())
     }
 
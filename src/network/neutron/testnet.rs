@@ -9,7 +9,7 @@ use crate::{
         gas::{Price as GasPrice, Prices as GasPrices},
         make_abs_path, make_abs_root, ChainId, Clean, Initialize, Instance, Node, NodeUri,
     },
-    Error,
+    progress, Error,
 };
 
 pub const REPO_URL: &str = "https://github.com/neutron-org/neutron.git";
@@ -19,6 +19,14 @@ pub const NODE: &str = "https://rpc-t.neutron.nodestake.top:443";
 pub const CHAIN_HOME_DIR: &str = "data";
 pub const CHAIN_ID: &str = "pion-1";
 pub const CHAIN_DENOM: &str = "untrn";
+pub const WASM_CAPABILITIES: &[&str] = &[
+    "iterator",
+    "staking",
+    "stargate",
+    "neutron",
+    "cosmwasm_1_1",
+    "cosmwasm_1_2",
+];
 
 #[derive(Default)]
 pub struct Testnet {
@@ -43,15 +51,18 @@ impl Initialize for Testnet {
             return Ok(instance);
         }
 
-        cmd!(
-            sh,
-            "git clone --depth 1 --branch {REPO_BRANCH} {REPO_URL} {rel_src_path}"
-        )
-        .run()?;
+        progress::phase(&format!("Cloning {REPO_URL}"), || {
+            progress::run(cmd!(
+                sh,
+                "git clone --depth 1 --branch {REPO_BRANCH} {REPO_URL} {rel_src_path}"
+            ))
+        })?;
 
         let _cd = sh.push_dir(rel_src_path);
 
-        cmd!(sh, "make build").run()?;
+        progress::phase(&format!("Building {REPO_URL}"), || {
+            progress::run(cmd!(sh, "make build"))
+        })?;
 
         Ok(instance)
     }
@@ -75,6 +86,10 @@ impl Node for Instance<Testnet> {
     fn chain_id(&self) -> ChainId {
         ChainId::from(CHAIN_ID.to_owned())
     }
+
+    fn wasm_capabilities(&self) -> &[&str] {
+        WASM_CAPABILITIES
+    }
 }
 
 impl Clean for Testnet {
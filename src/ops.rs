@@ -1,37 +1,2418 @@
-use xshell::{cmd, Shell};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use crate::Error;
+use log::debug;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use xshell::{cmd, Cmd as ShellCmd, Shell};
 
-/// Build and optimize all contract crates in `<workspace-root>/contracts` using the `cosmwasm/workspace-optimizer` docker image.
-/// Artifacts are placed in `<workspace-root>/artifacts` by default, this can be overridden by setting the `COSMWASM_ARTIFACTS_DIR` environment variable.
+use crate::{
+    cli::{
+        poll_interval, wait_for_tx, CodeId, Coin, Contract, ShellCmdExt, TxId, DEFAULT_TX_TIMEOUT,
+    },
+    config::Config,
+    contract::{
+        check_wasm_with_capabilities, checksum, execute_with_network_gas, required_capabilities,
+        update_admin, DEFAULT_GAS_UNITS,
+    },
+    deploy::{Manifest, Provenance, PROVENANCE_FILE},
+    key::{Key, KeyRole},
+    metrics::{self, Step, StepKind},
+    network::{gas, host_is_arm64, Network, DEFAULT_WASM_CAPABILITIES},
+    Error,
+};
+
+/// The environment variable overriding the optimizer image architecture, either `amd64` or `arm64`
+pub const OPTIMIZER_ARCH_ENV: &str = "COSMWASM_OPTIMIZER_ARCH";
+
+/// The architecture of the optimizer docker image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizerArch {
+    Amd64,
+    /// Native on Apple Silicon, the image name & artifact names gain an architecture suffix
+    Arm64,
+}
+
+impl OptimizerArch {
+    /// The architecture set in `COSMWASM_OPTIMIZER_ARCH`, otherwise that of the host CPU, avoiding emulation on Apple Silicon
+    /// even when this process is an `x86_64` binary translated by Rosetta
+    #[must_use]
+    pub fn detect() -> Self {
+        match std::env::var(OPTIMIZER_ARCH_ENV).as_deref() {
+            Ok("arm64" | "aarch64") => OptimizerArch::Arm64,
+            Err(_) if host_is_arm64() => OptimizerArch::Arm64,
+            _ => OptimizerArch::Amd64,
+        }
+    }
+
+    fn image_suffix(self) -> &'static str {
+        match self {
+            OptimizerArch::Amd64 => "",
+            OptimizerArch::Arm64 => "-arm64",
+        }
+    }
+
+    fn artifact_suffix(self) -> &'static str {
+        match self {
+            OptimizerArch::Amd64 => "",
+            OptimizerArch::Arm64 => "-aarch64",
+        }
+    }
+}
+
+/// The default tag of the optimizer images
+pub const OPTIMIZER_TAG: &str = "0.14.0";
+
+/// A docker optimizer run, by default the `cosmwasm/workspace-optimizer` or `cosmwasm/rust-optimizer` images at `OPTIMIZER_TAG`
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    image: String,
+    tag: String,
+    env: Vec<(String, String)>,
+    arch: Option<OptimizerArch>,
+    check_capabilities: Option<Vec<String>>,
+    contracts: Vec<String>,
+    artifacts_dir: Option<PathBuf>,
+    cache_volume: Option<String>,
+    features: Vec<String>,
+}
+
+impl Optimizer {
+    /// The `cosmwasm/workspace-optimizer` image, building all contract crates in `<workspace-root>/contracts`
+    #[must_use]
+    pub fn workspace() -> Self {
+        Self::new("cosmwasm/workspace-optimizer")
+    }
+
+    /// The `cosmwasm/rust-optimizer` image, building the single contract crate at the workspace root
+    #[must_use]
+    pub fn rust() -> Self {
+        Self::new("cosmwasm/rust-optimizer")
+    }
+
+    /// A custom optimizer `image`, e.g. a fork, which must take the same arguments as the upstream images
+    #[must_use]
+    pub fn new(image: &str) -> Self {
+        Self {
+            image: image.to_owned(),
+            tag: OPTIMIZER_TAG.to_owned(),
+            env: vec![],
+            arch: None,
+            check_capabilities: None,
+            contracts: vec![],
+            artifacts_dir: None,
+            cache_volume: None,
+            features: vec![],
+        }
+    }
+
+    /// Pin the image `tag`, e.g. to use a newer optimizer release
+    #[must_use]
+    pub fn tag(self, tag: &str) -> Self {
+        Self {
+            tag: tag.to_owned(),
+            ..self
+        }
+    }
+
+    /// Set the environment variable `key` to `value` in the container
+    #[must_use]
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Use the image for `arch` rather than detecting it, see `OptimizerArch::detect`
+    #[must_use]
+    pub fn arch(mut self, arch: OptimizerArch) -> Self {
+        self.arch = Some(arch);
+        self
+    }
+
+    /// Validate the artifacts with `cosmwasm-check` against the target chain's `capabilities` after building, see `check_artifacts`
+    #[must_use]
+    pub fn check(self, capabilities: &[&str]) -> Self {
+        Self {
+            check_capabilities: Some(capabilities.iter().map(ToString::to_string).collect()),
+            ..self
+        }
+    }
+
+    /// Only build the contract crates at `paths` relative to the workspace root, e.g. `./contracts/cw20`, only supported by `cosmwasm/rust-optimizer`
+    #[must_use]
+    pub fn contracts(self, paths: &[&str]) -> Self {
+        Self {
+            contracts: paths.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Place the artifacts in `dir` rather than the default, see `Optimizer::run`
+    #[must_use]
+    pub fn artifacts_dir<P>(self, dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            artifacts_dir: Some(dir.as_ref().to_path_buf()),
+            ..self
+        }
+    }
+
+    /// Prefix the target cache volumes with `prefix` rather than the workspace directory name,
+    /// e.g. `<repo>_<branch>` so CI jobs for repos sharing a directory name or branches of the same repo don't share a cache
+    #[must_use]
+    pub fn cache_volume(self, prefix: &str) -> Self {
+        Self {
+            cache_volume: Some(prefix.to_owned()),
+            ..self
+        }
+    }
+
+    /// Build the contract crates with the Cargo `features` enabled, which every crate built must define.
+    ///
+    /// The optimizer scripts don't take features, so the image's entrypoint is replaced with a `cargo build` & `wasm-opt`
+    /// run mirroring them. This needs the toolchain & `wasm-opt` of `cosmwasm/rust-optimizer` or a derived image.
+    #[must_use]
+    pub fn features(self, features: &[&str]) -> Self {
+        Self {
+            features: features.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    fn resolved_arch(&self) -> OptimizerArch {
+        self.arch.unwrap_or_else(OptimizerArch::detect)
+    }
+
+    /// The shell script building the crates at `contracts`, or the one at the workspace root if empty, with the selected features.
+    /// Builds go to their own target dir, cleared of previous artifacts, so only the crates built are optimized.
+    fn features_script(&self, contracts: &[&str]) -> String {
+        let contracts = if contracts.is_empty() {
+            ".".to_owned()
+        } else {
+            contracts.join(" ")
+        };
+
+        let features = self.features.join(",");
+
+        let suffix = self.resolved_arch().artifact_suffix();
+
+        format!(
+            "set -e
+            release=/code/target/features/wasm32-unknown-unknown/release
+            rm -f $release/*.wasm
+            for contract in {contracts}; do
+                (cd $contract && RUSTFLAGS='-C link-arg=-s' cargo build --release --lib --locked \
+                    --target wasm32-unknown-unknown --target-dir /code/target/features --features '{features}')
+            done
+            for wasm in $release/*.wasm; do
+                wasm-opt -Os --signext-lowering $wasm -o /code/artifacts/$(basename $wasm .wasm){suffix}.wasm
+            done"
+        )
+    }
+
+    /// The full image reference, e.g. `cosmwasm/workspace-optimizer-arm64:0.14.0`
+    fn image_ref(&self) -> String {
+        format!(
+            "{}{}:{}",
+            self.image,
+            self.resolved_arch().image_suffix(),
+            self.tag
+        )
+    }
+
+    fn resolved_artifacts_dir(&self, sh: &Shell) -> Result<PathBuf, Error> {
+        match &self.artifacts_dir {
+            Some(dir) => Ok(sh.current_dir().join(dir)),
+            None => default_artifacts_dir(sh),
+        }
+    }
+
+    /// The path of the artifact the optimizer produces for `package_name`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if loading `xtask.toml` fails.
+    pub fn artifact_path(&self, sh: &Shell, package_name: &str) -> Result<PathBuf, Error> {
+        let mut artifact = self.resolved_artifacts_dir(sh)?;
+        artifact.push(format!(
+            "{}{}.wasm",
+            package_name.replace('-', "_"),
+            self.resolved_arch().artifact_suffix()
+        ));
+        Ok(artifact)
+    }
+
+    /// Run the optimizer over the workspace root.
+    /// Artifacts are placed in `<workspace-root>/artifacts` by default, this can be overridden by `artifacts-dir` in `xtask.toml` or setting the `COSMWASM_ARTIFACTS_DIR` environment variable.
+    /// The sha256 checksum of each artifact is written to `checksums.txt` alongside them, see `verify_artifacts`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Creating the artifacts directory if it does not exist fails
+    /// - Running the docker command fails
+    /// - Checking is enabled & an artifact fails validation
+    pub fn run(&self, sh: &Shell) -> Result<(), Error> {
+        let artifacts_dir = self.prepare_artifacts_dir(sh)?;
+
+        let contracts: Vec<_> = self.contracts.iter().map(String::as_str).collect();
+
+        metrics::timed(StepKind::Build, &self.image_ref(), || {
+            self.docker_cmd(sh, &artifacts_dir, "cache", &contracts)?
+                .logged()
+                .run()
+                .map_err(Error::from)
+        })?;
+
+        self.finish(sh, &artifacts_dir)
+    }
+
+    /// Optimize each of the contract crates at `paths` in its own container concurrently, sharing the registry cache,
+    /// see `Optimizer::contracts`. Only supported by `cosmwasm/rust-optimizer`.
+    ///
+    /// Each crate gets its own target cache, so the first parallel build of a workspace is slower than a sequential one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Creating the artifacts directory if it does not exist fails
+    /// - Starting a docker command fails or any of them fail
+    /// - Checking is enabled & an artifact fails validation
+    pub fn run_parallel(&self, sh: &Shell, paths: &[&str]) -> Result<(), Error> {
+        let artifacts_dir = self.prepare_artifacts_dir(sh)?;
+
+        let started = Instant::now();
+
+        let children = paths
+            .iter()
+            .map(|path| {
+                let volume = format!(
+                    "{}_cache",
+                    path.trim_start_matches("./").replace(['/', '\\', '.'], "_")
+                );
+
+                let cmd = self
+                    .docker_cmd(sh, &artifacts_dir, &volume, &[path])?
+                    .logged();
+
+                let child = std::process::Command::from(cmd).spawn()?;
+
+                Ok((path, child))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // Wait for every container before reporting a failure, so none are left running
+        let mut failures = vec![];
+
+        for (path, mut child) in children {
+            let status = child.wait()?;
+
+            if !status.success() {
+                failures.push(format!("optimizing {path} failed with {status}"));
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(Error::CmdExecute(failures.join("\n")));
+        }
+
+        metrics::record(Step {
+            kind: StepKind::Build,
+            label: format!("{} x{}", self.image_ref(), paths.len()),
+            duration: started.elapsed(),
+            gas_used: None,
+        });
+
+        self.finish(sh, &artifacts_dir)
+    }
+
+    /// Create the artifacts directory if needed, responding with its canonical path
+    fn prepare_artifacts_dir(&self, sh: &Shell) -> Result<PathBuf, Error> {
+        let artifacts_dir = self.resolved_artifacts_dir(sh)?;
+
+        if !sh.path_exists(&artifacts_dir) {
+            sh.create_dir(&artifacts_dir)?;
+        }
+
+        Ok(artifacts_dir.canonicalize()?)
+    }
+
+    /// The docker command optimizing `contracts` with the target dir cached in the volume `<cache-volume>_<target_volume>`
+    fn docker_cmd<'a>(
+        &self,
+        sh: &'a Shell,
+        artifacts_dir: &Path,
+        target_volume: &str,
+        contracts: &[&str],
+    ) -> Result<ShellCmd<'a>, Error> {
+        let image = self.image_ref();
+
+        let env_args = self
+            .env
+            .iter()
+            .flat_map(|(key, value)| ["-e".to_owned(), format!("{key}={value}")]);
+
+        let cwd = sh.current_dir().canonicalize()?;
+
+        let cwd_path = mount_path(&cwd);
+
+        let artifacts_dir = mount_path(artifacts_dir);
+
+        let cache_volume = self
+            .cache_volume
+            .clone()
+            .unwrap_or_else(|| cwd.file_stem().unwrap().to_string_lossy().into_owned());
+
+        if !self.features.is_empty() {
+            let script = self.features_script(contracts);
+
+            return Ok(cmd!(
+                sh,
+                "docker run --rm -v {cwd_path}:/code
+                  -v {artifacts_dir}:/code/artifacts
+                  --mount type=volume,source={cache_volume}_{target_volume},target=/code/target
+                  --mount type=volume,source=registry_cache,target=/usr/local/cargo/registry
+                  --workdir /code
+                  --entrypoint /bin/sh
+                  {env_args...}
+                  {image}
+                  -c {script}"
+            ));
+        }
+
+        Ok(cmd!(
+            sh,
+            "docker run --rm -v {cwd_path}:/code
+              -v {artifacts_dir}:/code/artifacts
+              --mount type=volume,source={cache_volume}_{target_volume},target=/code/target
+              --mount type=volume,source=registry_cache,target=/usr/local/cargo/registry
+              {env_args...}
+              {image}
+              {contracts...}"
+        ))
+    }
+
+    /// Write the checksums & provenance of the artifacts & check them if enabled
+    fn finish(&self, sh: &Shell, artifacts_dir: &Path) -> Result<(), Error> {
+        write_checksums(sh, artifacts_dir)?;
+
+        let image = self.image_ref();
+
+        let rustc_version = cmd!(sh, "docker run --rm --entrypoint rustc {image} --version")
+            .quiet()
+            .ignore_stderr()
+            .read()
+            .ok();
+
+        write_provenance(sh, artifacts_dir, rustc_version.as_deref(), Some(&image))?;
+
+        if let Some(capabilities) = &self.check_capabilities {
+            let capabilities: Vec<_> = capabilities.iter().map(String::as_str).collect();
+
+            for artifact in list_artifacts(sh, artifacts_dir)? {
+                check_wasm_with_capabilities(sh, &capabilities, &artifact)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A canonical host `path` as docker accepts it for a bind mount, without the `\\?\` prefix Windows canonicalization adds
+fn mount_path(path: &Path) -> PathBuf {
+    let display = path.to_string_lossy();
+
+    match display.strip_prefix(r"\\?\") {
+        Some(stripped) => PathBuf::from(stripped),
+        None => path.to_owned(),
+    }
+}
+
+/// A `relative` host path as the optimizer container sees it from the mounted workspace root
+fn container_path(relative: &Path) -> String {
+    let segments: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+
+    format!("./{}", segments.join("/"))
+}
+
+/// The artifacts directory, see `Config::artifacts_dir`
+fn default_artifacts_dir(sh: &Shell) -> Result<PathBuf, Error> {
+    Ok(Config::load(sh)?.artifacts_dir(sh))
+}
+
+/// Validate every artifact with `cosmwasm-check` against the target chain's `capabilities`, e.g. `network.wasm_capabilities()`,
+/// failing when a contract uses a feature the chain does not support.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Listing the artifacts fails
+/// - Running `cosmwasm-check` fails, e.g. it is not installed
+/// - An artifact fails validation
+pub fn check_artifacts(sh: &Shell, capabilities: &[&str]) -> Result<(), Error> {
+    for artifact in list_artifacts(sh, &default_artifacts_dir(sh)?)? {
+        check_wasm_with_capabilities(sh, capabilities, &artifact)?;
+    }
+
+    Ok(())
+}
+
+/// The capabilities an artifact requires which the target chain does not support
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityLint {
+    pub artifact: String,
+    pub required: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+impl CapabilityLint {
+    /// Whether the chain supports every capability the artifact requires
+    #[must_use]
+    pub fn is_supported(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+impl std::fmt::Display for CapabilityLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_supported() {
+            write!(
+                f,
+                "{} requires [{}]",
+                self.artifact,
+                self.required.join(", ")
+            )
+        } else {
+            write!(
+                f,
+                "{} requires [{}] unsupported by the target chain",
+                self.artifact,
+                self.missing.join(", ")
+            )
+        }
+    }
+}
+
+/// Check the capabilities required by each artifact against those enabled on the `network`, see `Network::wasm_capabilities`.
+/// Unlike `check_artifacts` this does not need `cosmwasm-check` & reports every missing capability,
+/// catching contracts which would store but then fail to instantiate with an unsupported feature.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Listing the artifacts fails
+/// - An artifact is not valid WASM bytecode
+pub fn lint_capabilities(sh: &Shell, network: &dyn Network) -> Result<Vec<CapabilityLint>, Error> {
+    let available = network.wasm_capabilities();
+
+    list_artifacts(sh, &default_artifacts_dir(sh)?)?
+        .into_iter()
+        .map(|artifact| {
+            let required = required_capabilities(&artifact)?;
+
+            let missing = required
+                .iter()
+                .filter(|capability| !available.contains(&capability.as_str()))
+                .cloned()
+                .collect();
+
+            let lint = CapabilityLint {
+                artifact: artifact.file_name().unwrap().to_string_lossy().into_owned(),
+                required,
+                missing,
+            };
+
+            debug!("{lint}");
+
+            Ok(lint)
+        })
+        .collect()
+}
+
+/// The wasm files in the `artifacts_dir`, sorted by name
+pub(crate) fn list_artifacts(sh: &Shell, artifacts_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut artifacts: Vec<_> = sh
+        .read_dir(artifacts_dir)?
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+
+    artifacts.sort();
+
+    Ok(artifacts)
+}
+
+/// The name of the checksums file in the artifacts directory, in `sha256sum` format
+pub const CHECKSUMS_FILE: &str = "checksums.txt";
+
+/// Write the sha256 checksum of every artifact to `<artifacts-dir>/checksums.txt`, responding with its path
+fn write_checksums(sh: &Shell, artifacts_dir: &Path) -> Result<PathBuf, Error> {
+    let mut checksums = String::new();
+
+    for artifact in list_artifacts(sh, artifacts_dir)? {
+        checksums.push_str(&checksum(&artifact)?);
+        checksums.push_str("  ");
+        checksums.push_str(&artifact.file_name().unwrap().to_string_lossy());
+        checksums.push('\n');
+    }
+
+    let path = artifacts_dir.join(CHECKSUMS_FILE);
+
+    sh.write_file(&path, checksums)?;
+
+    Ok(path)
+}
+
+/// Record the provenance of each artifact built since the last write in `<artifacts-dir>/provenance.json`, see `Provenance`.
+/// Artifacts with an unchanged checksum keep their recorded provenance, as they were not rebuilt.
+fn write_provenance(
+    sh: &Shell,
+    artifacts_dir: &Path,
+    rustc_version: Option<&str>,
+    optimizer: Option<&str>,
+) -> Result<(), Error> {
+    let path = artifacts_dir.join(PROVENANCE_FILE);
+
+    let mut records: BTreeMap<String, Provenance> = if sh.path_exists(&path) {
+        serde_json::from_str(&sh.read_file(&path)?)?
+    } else {
+        BTreeMap::new()
+    };
+
+    let git_commit = cmd!(sh, "git rev-parse HEAD")
+        .quiet()
+        .ignore_stderr()
+        .read()
+        .ok();
+
+    let git_dirty = cmd!(sh, "git status --porcelain")
+        .quiet()
+        .ignore_stderr()
+        .read()
+        .is_ok_and(|status| !status.trim().is_empty());
+
+    for artifact in list_artifacts(sh, artifacts_dir)? {
+        let name = artifact.file_name().unwrap().to_string_lossy().into_owned();
+
+        let checksum = checksum(&artifact)?;
+
+        if records
+            .get(&name)
+            .is_some_and(|record| record.checksum == checksum)
+        {
+            continue;
+        }
+
+        records.insert(
+            name,
+            Provenance {
+                checksum,
+                git_commit: git_commit.clone(),
+                git_dirty,
+                rustc_version: rustc_version.map(ToOwned::to_owned),
+                optimizer: optimizer.map(ToOwned::to_owned),
+            },
+        );
+    }
+
+    sh.write_file(&path, serde_json::to_string_pretty(&records)?)?;
+
+    Ok(())
+}
+
+/// Verify every artifact listed in `<artifacts-dir>/checksums.txt` still has the recorded sha256 checksum,
+/// e.g. to check a build is reproducible or the artifacts were not tampered with before deploying.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Reading the checksums file or an artifact fails
+/// - An artifact's checksum does not match the recorded one
+pub fn verify_artifacts(sh: &Shell) -> Result<(), Error> {
+    let artifacts_dir = default_artifacts_dir(sh)?;
+
+    let checksums = sh.read_file(artifacts_dir.join(CHECKSUMS_FILE))?;
+
+    for line in checksums.lines().filter(|line| !line.trim().is_empty()) {
+        let Some((expected, artifact)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+
+        let artifact = artifact.trim();
+
+        let found = checksum(artifacts_dir.join(artifact))?;
+
+        if !found.eq_ignore_ascii_case(expected) {
+            return Err(Error::ArtifactChecksumMismatch {
+                artifact: artifact.to_owned(),
+                expected: expected.to_owned(),
+                found,
+            });
+        }
+
+        debug!("Verified {artifact} has checksum {found}");
+    }
+
+    Ok(())
+}
+
+/// The name of the file recording the source hash of each contract crate as of its last optimization
+pub const SOURCES_FILE: &str = "sources.txt";
+
+/// An optimized contract artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    /// The package name of the contract crate
+    pub name: String,
+    pub path: PathBuf,
+    /// The hex encoded sha256 checksum of the bytecode
+    pub checksum: String,
+}
+
+/// The options of `dist_workspace`
+#[derive(Debug, Clone)]
+pub struct DistOptions {
+    image: Option<String>,
+    tag: String,
+    artifacts_dir: Option<PathBuf>,
+    parallel: bool,
+    cache_volume: Option<String>,
+    only: Vec<String>,
+    exclude: Vec<String>,
+    features: Vec<String>,
+}
+
+impl Default for DistOptions {
+    fn default() -> Self {
+        Self {
+            image: None,
+            tag: OPTIMIZER_TAG.to_owned(),
+            artifacts_dir: None,
+            parallel: false,
+            cache_volume: None,
+            only: vec![],
+            exclude: vec![],
+            features: vec![],
+        }
+    }
+}
+
+impl DistOptions {
+    /// Use a custom workspace optimizer `image`, e.g. a fork, instead of `cosmwasm/workspace-optimizer`.
+    /// Every crate is rebuilt by the image when any have changed.
+    #[must_use]
+    pub fn image(self, image: &str) -> Self {
+        Self {
+            image: Some(image.to_owned()),
+            ..self
+        }
+    }
+
+    /// Pin the optimizer image `tag`, e.g. to use a newer optimizer release
+    #[must_use]
+    pub fn tag(self, tag: &str) -> Self {
+        Self {
+            tag: tag.to_owned(),
+            ..self
+        }
+    }
+
+    /// Place the artifacts in `dir` rather than `<workspace-root>/artifacts`
+    #[must_use]
+    pub fn artifacts_dir<P>(self, dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            artifacts_dir: Some(dir.as_ref().to_path_buf()),
+            ..self
+        }
+    }
+
+    /// Optimize the changed crates concurrently, each in its own `cosmwasm/rust-optimizer` container, see `Optimizer::run_parallel`.
+    /// Ignored when a custom image is set.
+    #[must_use]
+    pub fn parallel(self, parallel: bool) -> Self {
+        Self { parallel, ..self }
+    }
+
+    /// Prefix the target cache volumes with `prefix`, see `Optimizer::cache_volume`
+    #[must_use]
+    pub fn cache_volume(self, prefix: &str) -> Self {
+        Self {
+            cache_volume: Some(prefix.to_owned()),
+            ..self
+        }
+    }
+
+    /// Only build the contract crates with the given package names, e.g. those being released
+    #[must_use]
+    pub fn only(self, packages: &[&str]) -> Self {
+        Self {
+            only: packages.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Skip the contract crates with the given package names, e.g. test helpers
+    #[must_use]
+    pub fn exclude(self, packages: &[&str]) -> Self {
+        Self {
+            exclude: packages.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Build the selected contract crates with the Cargo `features` enabled, see `Optimizer::features`.
+    /// Crates are then built individually with `cosmwasm/rust-optimizer`, so a custom image is not used.
+    #[must_use]
+    pub fn features(self, features: &[&str]) -> Self {
+        Self {
+            features: features.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    fn includes(&self, package: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|only| only == package))
+            && !self.exclude.iter().any(|exclude| exclude == package)
+    }
+
+    fn optimizer(&self, image: &str) -> Optimizer {
+        let optimizer = Optimizer::new(image).tag(&self.tag);
+
+        let optimizer = match &self.artifacts_dir {
+            Some(dir) => optimizer.artifacts_dir(dir),
+            None => optimizer,
+        };
+
+        let optimizer = match &self.cache_volume {
+            Some(prefix) => optimizer.cache_volume(prefix),
+            None => optimizer,
+        };
+
+        let features: Vec<_> = self.features.iter().map(String::as_str).collect();
+
+        optimizer.features(&features)
+    }
+}
+
+/// Build and optimize all contract crates in `<workspace-root>/contracts` using the `cosmwasm/workspace-optimizer` docker image, responding with the artifacts.
+/// Artifacts are placed in `<workspace-root>/artifacts` by default, this can be overridden by `artifacts-dir` in `xtask.toml` or setting the `COSMWASM_ARTIFACTS_DIR` environment variable or with `DistOptions::artifacts_dir`.
+/// The `-arm64` image is used on ARM hosts, see `OptimizerArch::detect`.
+///
+/// Crates whose sources & the workspace `Cargo.lock` are unchanged since they were last optimized are skipped,
+/// if only some crates changed or are selected with `DistOptions::only` & `DistOptions::exclude` they are built individually with `cosmwasm/rust-optimizer`.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
+/// - Hashing the contract crate sources fails
 /// - Creating the artifacts directory if it does not exist fails
 /// - Running the docker command fails
-pub fn dist_workspace(sh: &Shell) -> Result<(), Error> {
-    let cwd = sh.current_dir().canonicalize()?;
+/// - Reading an artifact to compute its checksum fails
+pub fn dist_workspace(sh: &Shell, options: &DistOptions) -> Result<Vec<Artifact>, Error> {
+    let optimizer = options.optimizer(
+        options
+            .image
+            .as_deref()
+            .unwrap_or("cosmwasm/workspace-optimizer"),
+    );
 
-    let cwd_path = cwd.as_path();
+    let sources_path = optimizer.resolved_artifacts_dir(sh)?.join(SOURCES_FILE);
 
-    let cwd_name = cwd.file_stem().unwrap();
+    let recorded = if sh.path_exists(&sources_path) {
+        sh.read_file(&sources_path)?
+    } else {
+        String::new()
+    };
 
-    let artifacts_dir =
-        std::env::var("COSMWASM_ARTIFACTS_DIR").unwrap_or_else(|_| "artifacts".to_owned());
+    let lockfile = sh.current_dir().join("Cargo.lock");
 
-    if !sh.path_exists(&artifacts_dir) {
-        cmd!(sh, "mkdir {artifacts_dir}").run()?;
+    let mut lockfile = if lockfile.is_file() {
+        std::fs::read(lockfile)?
+    } else {
+        vec![]
+    };
+
+    // Hashed alongside the lockfile, so changing the features rebuilds the crates
+    lockfile.extend(options.features.join(",").as_bytes());
+
+    let all_dirs = contract_dirs(sh)?;
+
+    let mut dirs = vec![];
+
+    for dir in &all_dirs {
+        let name = package_name(sh, dir)?;
+
+        if options.includes(&name) {
+            dirs.push((dir, name));
+        }
     }
 
-    cmd!(
-        sh,
-        "docker run --rm -v {cwd_path}:/code
-          --mount type=volume,source={cwd_name}_cache,target=/code/target
-          --mount type=volume,source=registry_cache,target=/usr/local/cargo/registry
-          cosmwasm/workspace-optimizer:0.14.0"
-    )
-    .run()?;
+    let mut names = vec![];
 
-    Ok(())
+    let mut changed = vec![];
+
+    // Keep the hashes of crates which are not selected, so they are not rebuilt unnecessarily later
+    let mut sources: String = recorded
+        .lines()
+        .filter(|line| {
+            line.split_once(char::is_whitespace)
+                .is_some_and(|(_, package)| !dirs.iter().any(|(_, name)| name == package.trim()))
+        })
+        .flat_map(|line| [line, "\n"])
+        .collect();
+
+    for (dir, name) in &dirs {
+        let name = name.clone();
+
+        let hash = source_hash(sh, dir, &lockfile)?;
+
+        let unchanged = recorded.lines().any(|line| {
+            line.split_once(char::is_whitespace)
+                .is_some_and(|(recorded, package)| recorded == hash && package.trim() == name)
+        });
+
+        if !unchanged || !optimizer.artifact_path(sh, &name)?.is_file() {
+            let relative = dir.strip_prefix(sh.current_dir()).unwrap_or(dir);
+            changed.push(container_path(relative));
+        }
+
+        sources.push_str(&hash);
+        sources.push_str("  ");
+        sources.push_str(&name);
+        sources.push('\n');
+
+        names.push(name);
+    }
+
+    if changed.is_empty() && !all_dirs.is_empty() {
+        debug!("All selected contracts are unchanged, skipping optimization");
+    } else if options.parallel && (options.image.is_none() || !options.features.is_empty()) {
+        let changed: Vec<_> = changed.iter().map(String::as_str).collect();
+
+        options
+            .optimizer("cosmwasm/rust-optimizer")
+            .run_parallel(sh, &changed)?;
+    } else if options.features.is_empty()
+        && (changed.len() == all_dirs.len() || options.image.is_some())
+    {
+        optimizer.run(sh)?;
+    } else {
+        debug!("Optimizing changed contracts: {}", changed.join(", "));
+
+        let changed: Vec<_> = changed.iter().map(String::as_str).collect();
+
+        options
+            .optimizer("cosmwasm/rust-optimizer")
+            .contracts(&changed)
+            .run(sh)?;
+    }
+
+    sh.write_file(&sources_path, sources)?;
+
+    names
+        .into_iter()
+        .map(|name| {
+            let path = optimizer.artifact_path(sh, &name)?;
+
+            Ok(Artifact {
+                checksum: checksum(&path)?,
+                name,
+                path,
+            })
+        })
+        .collect()
+}
+
+/// The hex encoded sha256 hash of every source file in the crate at `dir` & the workspace `lockfile`
+fn source_hash(sh: &Shell, dir: &Path, lockfile: &[u8]) -> Result<String, Error> {
+    fn collect_files(sh: &Shell, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), Error> {
+        for path in sh.read_dir(dir)? {
+            let name = path.file_name().unwrap_or_default();
+
+            if name == "target" || name == SCHEMA_DIR {
+                continue;
+            }
+
+            if path.is_dir() {
+                collect_files(sh, &path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = vec![];
+
+    collect_files(sh, dir, &mut files)?;
+
+    files.sort();
+
+    let mut hasher = Sha256::new();
+
+    for file in files {
+        hasher.update(
+            file.strip_prefix(dir)
+                .unwrap_or(&file)
+                .to_string_lossy()
+                .as_bytes(),
+        );
+        hasher.update(std::fs::read(&file)?);
+    }
+
+    hasher.update(lockfile);
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Build and optimize the single contract crate at the workspace root using the `cosmwasm/rust-optimizer` docker image, responding with the path of the artifact.
+/// Artifacts are placed in `<workspace-root>/artifacts` by default, this can be overridden by `artifacts-dir` in `xtask.toml` or setting the `COSMWASM_ARTIFACTS_DIR` environment variable.
+/// The `-arm64` image is used on ARM hosts, see `OptimizerArch::detect`. Use `Optimizer` to pin another image or tag.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Creating the artifacts directory if it does not exist fails
+/// - Running the docker command fails
+pub fn dist_contract(sh: &Shell, package_name: &str) -> Result<PathBuf, Error> {
+    let optimizer = Optimizer::rust();
+
+    optimizer.run(sh)?;
+
+    optimizer.artifact_path(sh, package_name)
+}
+
+/// The name of the file recording artifact sizes for `size_report` to diff against
+pub const SIZES_FILE: &str = "sizes.txt";
+
+/// The default maximum size of wasm bytecode accepted by `wasmd`, in bytes
+pub const DEFAULT_MAX_WASM_SIZE: u64 = 800 * 1024;
+
+/// The size of an artifact in bytes, with its size at the time of the previous report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactSize {
+    pub name: String,
+    pub size: u64,
+    pub previous: Option<u64>,
+}
+
+impl ArtifactSize {
+    /// The change in size since the previous report, if the artifact existed then
+    #[must_use]
+    pub fn diff(&self) -> Option<i128> {
+        self.previous
+            .map(|previous| i128::from(self.size) - i128::from(previous))
+    }
+
+    /// Whether the artifact is too large to store on a chain accepting at most `max_size` bytes
+    #[must_use]
+    pub fn exceeds(&self, max_size: u64) -> bool {
+        self.size > max_size
+    }
+}
+
+impl std::fmt::Display for ArtifactSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} bytes", self.name, self.size)?;
+
+        match self.diff() {
+            Some(diff) => write!(f, " ({diff:+})"),
+            None => write!(f, " (new)"),
+        }
+    }
+}
+
+/// Report the size of every artifact, diffed against the sizes recorded by the previous report which are then replaced.
+/// Compare with `DEFAULT_MAX_WASM_SIZE` or the target chain's limit to catch bloat before storing fails.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Listing the artifacts or reading their metadata fails
+/// - Reading or writing the sizes file fails
+pub fn size_report(sh: &Shell) -> Result<Vec<ArtifactSize>, Error> {
+    let artifacts_dir = default_artifacts_dir(sh)?;
+
+    let sizes_path = artifacts_dir.join(SIZES_FILE);
+
+    let previous = if sh.path_exists(&sizes_path) {
+        sh.read_file(&sizes_path)?
+    } else {
+        String::new()
+    };
+
+    let previous_size = |name: &str| {
+        previous.lines().find_map(|line| {
+            let (size, artifact) = line.split_once(char::is_whitespace)?;
+            (artifact.trim() == name).then(|| size.parse().ok())?
+        })
+    };
+
+    let mut report = vec![];
+
+    let mut sizes = String::new();
+
+    for artifact in list_artifacts(sh, &artifacts_dir)? {
+        let name = artifact.file_name().unwrap().to_string_lossy().into_owned();
+
+        let size = std::fs::metadata(&artifact)?.len();
+
+        sizes.push_str(&size.to_string());
+        sizes.push_str("  ");
+        sizes.push_str(&name);
+        sizes.push('\n');
+
+        report.push(ArtifactSize {
+            previous: previous_size(&name),
+            name,
+            size,
+        });
+    }
+
+    sh.write_file(&sizes_path, sizes)?;
+
+    Ok(report)
+}
+
+/// The `top` largest code items of the `artifact`, as reported by `twiggy top`, to find what is bloating a contract
+///
+/// # Errors
+///
+/// This function will return an error if running `twiggy` fails, e.g. it is not installed.
+pub fn twiggy_top<P>(sh: &Shell, artifact: P, top: usize) -> Result<String, Error>
+where
+    P: AsRef<Path>,
+{
+    let artifact = artifact.as_ref();
+
+    let top = top.to_string();
+
+    cmd!(sh, "twiggy top -n {top} {artifact}")
+        .read()
+        .map_err(Error::from)
+}
+
+/// The directory of the workspace's contract crates, relative to the workspace root
+pub const CONTRACTS_DIR: &str = "contracts";
+
+/// The crate directories in `<workspace-root>/contracts`
+fn contract_dirs(sh: &Shell) -> Result<Vec<PathBuf>, Error> {
+    let mut dirs: Vec<_> = sh
+        .read_dir(CONTRACTS_DIR)?
+        .into_iter()
+        .filter(|dir| dir.join("Cargo.toml").is_file())
+        .collect();
+
+    dirs.sort();
+
+    Ok(dirs)
+}
+
+/// The package name in the manifest of the crate in `dir`
+fn package_name(sh: &Shell, dir: &Path) -> Result<String, Error> {
+    let manifest = sh.read_file(dir.join("Cargo.toml"))?;
+
+    manifest
+        .lines()
+        .skip_while(|line| line.trim() != "[package]")
+        .find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            (key.trim() == "name").then(|| value.trim().trim_matches('"').to_owned())
+        })
+        .ok_or_else(|| Error::ExpectedPackageName(dir.display().to_string()))
+}
+
+/// Build and optimize all contract crates in `<workspace-root>/contracts` without docker, responding with the paths of the artifacts.
+/// Each crate is compiled for `wasm32-unknown-unknown` with symbols stripped, then shrunk with `wasm-opt`, which must be installed.
+/// Artifacts are placed in `<workspace-root>/artifacts` by default, this can be overridden by `artifacts-dir` in `xtask.toml` or setting the `COSMWASM_ARTIFACTS_DIR` environment variable.
+///
+/// The sha256 checksum of each artifact is written to `checksums.txt` alongside them, see `verify_artifacts`.
+/// Unlike the docker optimizers the build is not reproducible, the checksums will differ between machines.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Listing the contract crates fails
+/// - Creating the artifacts directory if it does not exist fails
+/// - Running the cargo or `wasm-opt` commands fails
+pub fn build_native(sh: &Shell) -> Result<Vec<PathBuf>, Error> {
+    let artifacts_dir = default_artifacts_dir(sh)?;
+
+    if !sh.path_exists(&artifacts_dir) {
+        sh.create_dir(&artifacts_dir)?;
+    }
+
+    let target_dir = sh
+        .current_dir()
+        .join("target/wasm32-unknown-unknown/release");
+
+    let mut artifacts = vec![];
+
+    for dir in contract_dirs(sh)? {
+        let name = package_name(sh, &dir)?.replace('-', "_");
+
+        debug!("Building {name} natively");
+
+        metrics::timed(StepKind::Build, &name, || {
+            let _cd = sh.push_dir(&dir);
+
+            cmd!(
+                sh,
+                "cargo build --release --lib --target wasm32-unknown-unknown --locked"
+            )
+            .env("RUSTFLAGS", "-C link-arg=-s")
+            .logged()
+            .run()
+        })?;
+
+        let wasm = target_dir.join(format!("{name}.wasm"));
+
+        let artifact = artifacts_dir.join(format!("{name}.wasm"));
+
+        cmd!(
+            sh,
+            "wasm-opt -Os --signext-lowering --strip-debug {wasm} -o {artifact}"
+        )
+        .logged()
+        .run()?;
+
+        artifacts.push(artifact);
+    }
+
+    write_checksums(sh, &artifacts_dir)?;
+
+    let rustc_version = cmd!(sh, "rustc --version").quiet().read().ok();
+
+    let wasm_opt_version = cmd!(sh, "wasm-opt --version").quiet().read().ok();
+
+    write_provenance(
+        sh,
+        &artifacts_dir,
+        rustc_version.as_deref(),
+        wasm_opt_version.as_deref(),
+    )?;
+
+    Ok(artifacts)
+}
+
+/// The directory schemas are collected in, relative to the workspace root
+pub const SCHEMA_DIR: &str = "schema";
+
+/// Generate the JSON schemas of all contract crates in `<workspace-root>/contracts` by running their `schema` binary, i.e. `cosmwasm_schema::write_api!`,
+/// responding with the output directories. The schemas of each contract are collected in `<workspace-root>/schema/<package-name>`, replacing any stale ones.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Listing the contract crates fails
+/// - Running a schema binary fails
+/// - Copying the schema files fails
+pub fn generate_schemas(sh: &Shell) -> Result<Vec<PathBuf>, Error> {
+    let mut outputs = vec![];
+
+    for dir in contract_dirs(sh)? {
+        let name = package_name(sh, &dir)?;
+
+        debug!("Generating schema for {name}");
+
+        {
+            let _cd = sh.push_dir(&dir);
+
+            cmd!(sh, "cargo run --quiet --bin schema").logged().run()?;
+        }
+
+        let output = sh.current_dir().join(SCHEMA_DIR).join(&name);
+
+        sh.remove_path(&output)?;
+
+        copy_dir(sh, &dir.join(SCHEMA_DIR), &output)?;
+
+        outputs.push(output);
+    }
+
+    Ok(outputs)
+}
+
+/// The `@cosmwasm/ts-codegen` plugins used by `ts_codegen`
+pub const TS_CODEGEN_DEFAULT_PLUGINS: &[&str] = &["client", "message-composer"];
+
+/// Generate TypeScript clients into `out_dir` from the schemas in `<workspace-root>/schema`, see `generate_schemas`,
+/// using `@cosmwasm/ts-codegen` with the `TS_CODEGEN_DEFAULT_PLUGINS`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - `npx` is not installed
+/// - Listing the schemas fails
+/// - Running `ts-codegen` fails
+pub fn ts_codegen<P>(sh: &Shell, out_dir: P) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    ts_codegen_with_plugins(sh, out_dir, TS_CODEGEN_DEFAULT_PLUGINS)
+}
+
+/// Generate TypeScript clients into `out_dir` from the schemas in `<workspace-root>/schema` with the given ts-codegen `plugins`, e.g. `react-query`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - `npx` is not installed
+/// - Listing the schemas fails
+/// - Running `ts-codegen` fails
+pub fn ts_codegen_with_plugins<P>(sh: &Shell, out_dir: P, plugins: &[&str]) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    let out_dir = out_dir.as_ref();
+
+    cmd!(sh, "npx --version")
+        .quiet()
+        .ignore_stdout()
+        .ignore_stderr()
+        .logged()
+        .run()
+        .map_err(|_| Error::MissingTool("npx".to_owned()))?;
+
+    for schema in sh.read_dir(SCHEMA_DIR)? {
+        if !schema.is_dir() {
+            continue;
+        }
+
+        let name = pascal_case(&schema.file_name().unwrap().to_string_lossy());
+
+        debug!("Generating TypeScript client {name}");
+
+        let plugin_args = plugins.iter().flat_map(|plugin| ["--plugin", plugin]);
+
+        cmd!(
+            sh,
+            "npx --yes @cosmwasm/ts-codegen generate
+              --schema {schema}
+              --out {out_dir}
+              --name {name}
+              --no-bundle
+              {plugin_args...}"
+        )
+        .logged()
+        .run()?;
+    }
+
+    Ok(())
+}
+
+/// Convert a package name like `cw20-base` to `Cw20Base`
+fn pascal_case(name: &str) -> String {
+    name.split(['-', '_'])
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Recursively copy the contents of `from` into `to`
+fn copy_dir(sh: &Shell, from: &Path, to: &Path) -> Result<(), Error> {
+    sh.create_dir(to)?;
+
+    for path in sh.read_dir(from)? {
+        let dest = to.join(path.file_name().unwrap());
+
+        if path.is_dir() {
+            copy_dir(sh, &path, &dest)?;
+        } else {
+            sh.copy_file(&path, &dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure the `key` holds at least `min` of `denom`, responding with its balance.
+/// On localnets with a faucet the key is topped up, elsewhere this fails early rather than mid-deploy.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Querying the balance fails
+/// - The balance is below `min` & there is no faucet to top it up
+/// - Sending funds from the faucet fails
+pub fn ensure_balance(
+    sh: &Shell,
+    network: &dyn Network,
+    key: &Key,
+    min: u128,
+    denom: &str,
+) -> Result<u128, Error> {
+    let node_uri = network.node_uri(sh)?;
+
+    let balance = network
+        .cli(sh)?
+        .query(&node_uri)
+        .balance(key.address(), denom)?;
+
+    if balance >= min {
+        return Ok(balance);
+    }
+
+    let faucet = network
+        .faucet()
+        .and_then(|name| network.get(name))
+        .filter(|faucet| faucet.address() != key.address());
+
+    let Some(faucet) = faucet else {
+        return Err(Error::InsufficientBalance {
+            address: key.address().to_owned(),
+            denom: denom.to_owned(),
+            balance,
+            required: min,
+        });
+    };
+
+    let top_up = Coin::new(min - balance, denom);
+
+    debug!(
+        "Topping up {} with {top_up} from {}",
+        key.address(),
+        faucet.name()
+    );
+
+    let chain_id = network.chain_id();
+
+    let cmd = network
+        .cli(sh)?
+        .tx(faucet, &chain_id, &node_uri)
+        .bank_send(key.address(), &[top_up]);
+
+    let tx_id = execute_with_network_gas(cmd, network, gas::DEFAULT_ADJUSTMENT)?;
+
+    wait_for_tx(sh, network, &tx_id)?;
+
+    Ok(min)
+}
+
+/// The outcome of `rotate_admin` for each contract in the manifest, by name
+#[derive(Debug, Default)]
+pub struct AdminRotation {
+    /// Contracts whose admin is now the new key
+    pub rotated: Vec<String>,
+    /// Contracts not administered by the old key, left untouched
+    pub skipped: Vec<String>,
+    /// Contracts whose `update-admin` tx failed, with the error
+    pub failed: Vec<(String, Error)>,
+}
+
+impl AdminRotation {
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Move the admin of every contract in the `manifest` administered by `old_key` to `new_key`, e.g. when retiring a deployer key.
+/// A failed tx does not stop the rotation of the remaining contracts, check the returned report.
+///
+/// The manifest file is updated as each tx is confirmed, reload it to see the new admins.
+pub fn rotate_admin(
+    sh: &Shell,
+    network: &dyn Network,
+    manifest: &Manifest,
+    old_key: &Key,
+    new_key: &Key,
+) -> AdminRotation {
+    let mut report = AdminRotation::default();
+
+    for (name, record) in &manifest.contracts {
+        if record.admin.as_deref() != Some(old_key.address()) {
+            report.skipped.push(name.clone());
+            continue;
+        }
+
+        debug!(
+            "Rotating admin of {name} from {} to {}",
+            old_key.address(),
+            new_key.address()
+        );
+
+        match update_admin(&record.contract(), new_key.address()).send(sh, network, old_key) {
+            Ok(()) => report.rotated.push(name.clone()),
+            Err(err) => report.failed.push((name.clone(), err)),
+        }
+    }
+
+    report
+}
+
+/// A stored code in a `DeploymentReport`
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CodeReport {
+    pub name: String,
+    pub code_id: u64,
+    pub checksum: String,
+    pub tx_hash: String,
+    pub explorer_url: Option<String>,
+    pub gas_used: Option<u128>,
+}
+
+/// A managed contract in a `DeploymentReport`, the tx is the latest recorded, e.g. a migration
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ContractReport {
+    pub name: String,
+    pub address: String,
+    pub code_id: u64,
+    pub label: String,
+    pub admin: Option<String>,
+    pub tx_hash: String,
+    pub explorer_url: Option<String>,
+    pub gas_used: Option<u128>,
+}
+
+/// A summary of a deployment for attaching to a release or audit trail, printed in a human readable form or serialised to JSON
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct DeploymentReport {
+    pub chain_id: String,
+    pub codes: Vec<CodeReport>,
+    pub contracts: Vec<ContractReport>,
+}
+
+impl DeploymentReport {
+    /// Link each tx to a block explorer, `{tx_hash}` in `tx_url` is replaced, e.g. `https://www.mintscan.io/neutron/tx/{tx_hash}`
+    #[must_use]
+    pub fn explorer(mut self, tx_url: &str) -> Self {
+        for code in &mut self.codes {
+            code.explorer_url = Some(tx_url.replace("{tx_hash}", &code.tx_hash));
+        }
+
+        for contract in &mut self.contracts {
+            contract.explorer_url = Some(tx_url.replace("{tx_hash}", &contract.tx_hash));
+        }
+
+        self
+    }
+
+    /// Fill in the gas used by each tx by querying the `network`
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if querying a tx fails.
+    pub fn with_gas(mut self, sh: &Shell, network: &dyn Network) -> Result<Self, Error> {
+        let node_uri = network.node_uri(sh)?;
+
+        let gas_used = |tx_hash: &str| -> Result<Option<u128>, Error> {
+            Ok(network
+                .cli(sh)?
+                .query(&node_uri)
+                .tx(&TxId::from(tx_hash.to_owned()))?
+                .map(|tx_data| tx_data.meta.gas_used))
+        };
+
+        for code in &mut self.codes {
+            code.gas_used = gas_used(&code.tx_hash)?;
+        }
+
+        for contract in &mut self.contracts {
+            contract.gas_used = gas_used(&contract.tx_hash)?;
+        }
+
+        Ok(self)
+    }
+
+    /// The total gas used by the reported txs, if known
+    #[must_use]
+    pub fn total_gas_used(&self) -> Option<u128> {
+        self.codes
+            .iter()
+            .map(|code| code.gas_used)
+            .chain(self.contracts.iter().map(|contract| contract.gas_used))
+            .sum()
+    }
+
+    /// The report as pretty printed JSON
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if JSON serialisation fails.
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string_pretty(self).map_err(Error::from)
+    }
+}
+
+impl std::fmt::Display for DeploymentReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Deployment on {}", self.chain_id)?;
+
+        writeln!(f, "\nCodes:")?;
+
+        for code in &self.codes {
+            writeln!(
+                f,
+                "  {} code id {} checksum {}",
+                code.name, code.code_id, code.checksum
+            )?;
+            write_tx(
+                f,
+                &code.tx_hash,
+                code.explorer_url.as_deref(),
+                code.gas_used,
+            )?;
+        }
+
+        writeln!(f, "\nContracts:")?;
+
+        for contract in &self.contracts {
+            writeln!(
+                f,
+                "  {} at {} code id {} label {} admin {}",
+                contract.name,
+                contract.address,
+                contract.code_id,
+                contract.label,
+                contract.admin.as_deref().unwrap_or("none")
+            )?;
+            write_tx(
+                f,
+                &contract.tx_hash,
+                contract.explorer_url.as_deref(),
+                contract.gas_used,
+            )?;
+        }
+
+        if let Some(total) = self.total_gas_used() {
+            writeln!(f, "\nTotal gas used: {total}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_tx(
+    f: &mut std::fmt::Formatter<'_>,
+    tx_hash: &str,
+    explorer_url: Option<&str>,
+    gas_used: Option<u128>,
+) -> std::fmt::Result {
+    write!(f, "    tx {}", explorer_url.unwrap_or(tx_hash))?;
+
+    if let Some(gas_used) = gas_used {
+        write!(f, " gas used {gas_used}")?;
+    }
+
+    writeln!(f)
+}
+
+/// Build a report of everything recorded in the deployment `manifest`, see `DeploymentReport::explorer` & `DeploymentReport::with_gas` to enrich it
+#[must_use]
+pub fn report(manifest: &Manifest) -> DeploymentReport {
+    DeploymentReport {
+        chain_id: manifest.chain_id.clone(),
+        codes: manifest
+            .codes
+            .iter()
+            .map(|(name, record)| CodeReport {
+                name: name.clone(),
+                code_id: record.code_id,
+                checksum: record.checksum.clone(),
+                tx_hash: record.tx_hash.clone(),
+                explorer_url: None,
+                gas_used: None,
+            })
+            .collect(),
+        contracts: manifest
+            .contracts
+            .iter()
+            .map(|(name, record)| ContractReport {
+                name: name.clone(),
+                address: record.address.clone(),
+                code_id: record.code_id,
+                label: record.label.clone(),
+                admin: record.admin.clone(),
+                tx_hash: record.tx_hash.clone(),
+                explorer_url: None,
+                gas_used: None,
+            })
+            .collect(),
+    }
+}
+
+/// The outcome of a stage of `check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StageOutcome {
+    Passed,
+    /// Failed with the output of the stage
+    Failed(String),
+    /// Not run, with the reason
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageResult {
+    pub name: String,
+    pub outcome: StageOutcome,
+}
+
+/// The results of every stage of `check`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckReport {
+    pub stages: Vec<StageResult>,
+}
+
+impl CheckReport {
+    /// Whether no stage failed
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.stages
+            .iter()
+            .all(|stage| !matches!(stage.outcome, StageOutcome::Failed(_)))
+    }
+}
+
+impl std::fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for stage in &self.stages {
+            match &stage.outcome {
+                StageOutcome::Passed => writeln!(f, "PASS {}", stage.name)?,
+                StageOutcome::Failed(output) => writeln!(f, "FAIL {}\n{output}", stage.name)?,
+                StageOutcome::Skipped(reason) => writeln!(f, "SKIP {} ({reason})", stage.name)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Run a stage, capturing its output as the failure
+fn run_stage(name: &str, cmd: ShellCmd<'_>) -> Result<StageResult, Error> {
+    debug!("Running check stage {name}");
+
+    let out = cmd.quiet().ignore_status().output()?;
+
+    let outcome = if out.status.success() {
+        StageOutcome::Passed
+    } else {
+        let stdout = String::from_utf8(out.stdout)?;
+        let stderr = String::from_utf8(out.stderr)?;
+
+        StageOutcome::Failed(format!("{stdout}{stderr}").trim().to_owned())
+    };
+
+    Ok(StageResult {
+        name: name.to_owned(),
+        outcome,
+    })
+}
+
+/// Run the workspace checks as one pipeline step: `cargo fmt --check`, `cargo clippy`, the unit tests & `cosmwasm-check` of the artifacts
+/// against `DEFAULT_WASM_CAPABILITIES`. Every stage is run even if an earlier one fails, see `CheckReport::is_success`.
+///
+/// # Errors
+///
+/// This function will return an error if a stage's command cannot be run at all, e.g. `cargo` is not installed.
+pub fn check(sh: &Shell) -> Result<CheckReport, Error> {
+    let mut report = CheckReport::default();
+
+    report
+        .stages
+        .push(run_stage("fmt", cmd!(sh, "cargo fmt --all --check"))?);
+
+    report.stages.push(run_stage(
+        "clippy",
+        cmd!(sh, "cargo clippy --workspace --all-targets -- -D warnings"),
+    )?);
+
+    report
+        .stages
+        .push(run_stage("test", cmd!(sh, "cargo test --workspace --lib"))?);
+
+    let artifacts_dir = default_artifacts_dir(sh)?;
+
+    let artifacts = if sh.path_exists(&artifacts_dir) {
+        list_artifacts(sh, &artifacts_dir)?
+    } else {
+        vec![]
+    };
+
+    if artifacts.is_empty() {
+        report.stages.push(StageResult {
+            name: "cosmwasm-check".to_owned(),
+            outcome: StageOutcome::Skipped("no artifacts".to_owned()),
+        });
+    }
+
+    for artifact in artifacts {
+        let name = format!(
+            "cosmwasm-check {}",
+            artifact.file_name().unwrap().to_string_lossy()
+        );
+
+        let outcome = match check_wasm_with_capabilities(sh, DEFAULT_WASM_CAPABILITIES, &artifact) {
+            Ok(()) => StageOutcome::Passed,
+            Err(err) => StageOutcome::Failed(err.to_string()),
+        };
+
+        report.stages.push(StageResult { name, outcome });
+    }
+
+    Ok(report)
+}
+
+/// The oldest Go release the Neutron localnet services build with
+pub const MIN_GO_VERSION: (u32, u32) = (1, 20);
+
+/// The free disk space the Neutron localnet sources, Go module cache & chain state need, in GiB
+pub const MIN_FREE_DISK_GIB: u64 = 10;
+
+/// A check of `doctor`, with how to fix it when it fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub outcome: StageOutcome,
+    pub hint: Option<String>,
+}
+
+/// The results of every check of `doctor`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Whether no check failed
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| !matches!(check.outcome, StageOutcome::Failed(_)))
+    }
+
+    fn push(&mut self, name: impl Into<String>, outcome: StageOutcome, hint: &str) {
+        let hint = matches!(outcome, StageOutcome::Failed(_)).then(|| hint.to_owned());
+
+        self.checks.push(DoctorCheck {
+            name: name.into(),
+            outcome,
+            hint,
+        });
+    }
+}
+
+impl std::fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for check in &self.checks {
+            match &check.outcome {
+                StageOutcome::Passed => writeln!(f, "PASS {}", check.name)?,
+                StageOutcome::Failed(problem) => writeln!(f, "FAIL {}: {problem}", check.name)?,
+                StageOutcome::Skipped(reason) => writeln!(f, "SKIP {} ({reason})", check.name)?,
+            }
+
+            if let Some(hint) = &check.hint {
+                writeln!(f, "     {hint}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The first line `cmd` prints, if it runs successfully
+fn tool_output(cmd: ShellCmd<'_>) -> Option<String> {
+    let out = cmd.quiet().ignore_status().output().ok()?;
+
+    if !out.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+
+    Some(stdout.lines().next().unwrap_or_default().trim().to_owned())
+}
+
+/// Passed if a tool printed its version, i.e. it is installed
+fn presence(version: Option<&str>) -> StageOutcome {
+    match version {
+        Some(_) => StageOutcome::Passed,
+        None => StageOutcome::Failed("not found".to_owned()),
+    }
+}
+
+/// The `(major, minor)` version in `go version` output, e.g. `go version go1.21.5 linux/amd64`
+fn go_version(output: &str) -> Option<(u32, u32)> {
+    let version = output.split_whitespace().find_map(|word| {
+        word.strip_prefix("go")
+            .filter(|v| v.starts_with(char::is_numeric))
+    })?;
+
+    let mut parts = version.split('.');
+
+    let major = parts.next()?.parse().ok()?;
+
+    let minor = parts
+        .next()?
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .ok()?;
+
+    Some((major, minor))
+}
+
+/// The free disk space in the filesystem holding `dir`, in KiB
+fn free_disk_kib(sh: &Shell, dir: &Path) -> Option<u64> {
+    let df = cmd!(sh, "df -Pk {dir}")
+        .quiet()
+        .ignore_stderr()
+        .read()
+        .ok()?;
+
+    // The header is followed by `<filesystem> <blocks> <used> <available> <capacity> <mountpoint>`
+    df.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()
+}
+
+/// Check the Go toolchain & disk space the Neutron localnet services are built with
+fn check_build_env(sh: &Shell, report: &mut DoctorReport) {
+    let go = tool_output(cmd!(sh, "go version"));
+
+    let (min_major, min_minor) = MIN_GO_VERSION;
+
+    let outcome = match go.as_deref().map(go_version) {
+        None => StageOutcome::Failed("not found".to_owned()),
+        Some(Some(version)) if version >= MIN_GO_VERSION => StageOutcome::Passed,
+        Some(Some((major, minor))) => StageOutcome::Failed(format!(
+            "found {major}.{minor} but at least {min_major}.{min_minor} is required"
+        )),
+        Some(None) => StageOutcome::Failed("unrecognised version".to_owned()),
+    };
+
+    report.push(
+        go.as_deref().unwrap_or("go"),
+        outcome,
+        &format!("install Go {min_major}.{min_minor} or later from https://go.dev/dl"),
+    );
+
+    let make = tool_output(cmd!(sh, "make --version"));
+
+    report.push(
+        make.as_deref().unwrap_or("make"),
+        presence(make.as_deref()),
+        "install make, e.g. `xcode-select --install` on macOS or `apt install build-essential` on Debian",
+    );
+
+    let outcome = match free_disk_kib(sh, &sh.current_dir()) {
+        Some(kib) if kib / (1024 * 1024) >= MIN_FREE_DISK_GIB => StageOutcome::Passed,
+        Some(kib) => StageOutcome::Failed(format!(
+            "{} GiB free but {MIN_FREE_DISK_GIB} GiB is needed",
+            kib / (1024 * 1024)
+        )),
+        None => StageOutcome::Skipped("df is not available".to_owned()),
+    };
+
+    report.push(
+        "free disk space",
+        outcome,
+        "free up space or run `clean-all` for networks no longer in use",
+    );
+}
+
+/// Check the host can run the default network in `xtask.toml`, or any of the networks if there is none: the required tools & their versions,
+/// free disk space & whether the ports the localnet listens on are free. Each failed check carries a hint how to fix it, print the report
+/// before a first `initialize` rather than deciphering a failed build.
+///
+/// # Errors
+///
+/// This function will return an error if loading the config fails.
+pub fn doctor(sh: &Shell) -> Result<DoctorReport, Error> {
+    let network = Config::load(sh)?.network;
+
+    Ok(doctor_network(sh, network.as_deref()))
+}
+
+/// Check the host can run the `network` named as on the command line, e.g. `neutron-local`, or any of the networks if `None`, see `doctor`
+#[must_use]
+pub fn doctor_network(sh: &Shell, network: Option<&str>) -> DoctorReport {
+    use crate::network::neutron::local::{
+        EXPLORER_ENV, EXPLORER_PORT, GAIA_GRPC_PORT, GAIA_GRPC_WEB_PORT, GAIA_P2P_PORT,
+        GAIA_REST_PORT, GAIA_ROSETTA_PORT, GAIA_RPC_PORT, NTRN_GRPC_PORT, NTRN_GRPC_WEB_PORT,
+        NTRN_P2P_PORT, NTRN_REST_PORT, NTRN_ROSETTA_PORT, NTRN_RPC_PORT,
+    };
+
+    let archway_local = matches!(network, None | Some("archway-local"));
+
+    let neutron_local = matches!(network, None | Some("neutron-local"));
+
+    let explorer = neutron_local && std::env::var_os(EXPLORER_ENV).is_some();
+
+    let mut report = DoctorReport::default();
+
+    match network {
+        Some(network) => report.push(format!("network {network}"), StageOutcome::Passed, ""),
+        None => report.push(
+            "network",
+            StageOutcome::Skipped("no default network in xtask.toml, checking all".to_owned()),
+            "",
+        ),
+    }
+
+    let git = tool_output(cmd!(sh, "git --version"));
+
+    report.push(
+        git.as_deref().unwrap_or("git"),
+        presence(git.as_deref()),
+        "install git from https://git-scm.com/downloads",
+    );
+
+    let cargo = tool_output(cmd!(sh, "cargo --version"));
+
+    report.push(
+        cargo.as_deref().unwrap_or("cargo"),
+        presence(cargo.as_deref()),
+        "install Rust with rustup from https://rustup.rs",
+    );
+
+    let curl = tool_output(cmd!(sh, "curl --version"));
+
+    report.push(
+        curl.as_deref().unwrap_or("curl"),
+        presence(curl.as_deref()),
+        "install curl, `Tx::simulate` uses it to query the node",
+    );
+
+    if archway_local || explorer {
+        // Asking for the server version also checks the daemon is running
+        let docker = tool_output(cmd!(sh, "docker version --format").arg("{{.Server.Version}}"));
+
+        let outcome = match (&docker, tool_output(cmd!(sh, "docker --version"))) {
+            (Some(_), _) => StageOutcome::Passed,
+            (None, Some(_)) => StageOutcome::Failed("the docker daemon is not running".to_owned()),
+            (None, None) => StageOutcome::Failed("not found".to_owned()),
+        };
+
+        report.push(
+            docker.map_or_else(
+                || "docker".to_owned(),
+                |version| format!("docker {version}"),
+            ),
+            outcome,
+            "install Docker from https://docs.docker.com/get-docker & start it",
+        );
+    }
+
+    if neutron_local {
+        check_build_env(sh, &mut report);
+    }
+
+    let mut ports = vec![];
+
+    if archway_local {
+        ports.extend([9090, 26657]);
+    }
+
+    if neutron_local {
+        ports.extend([
+            NTRN_P2P_PORT,
+            NTRN_RPC_PORT,
+            NTRN_REST_PORT,
+            NTRN_GRPC_PORT,
+            NTRN_GRPC_WEB_PORT,
+            NTRN_ROSETTA_PORT,
+            GAIA_P2P_PORT,
+            GAIA_RPC_PORT,
+            GAIA_REST_PORT,
+            GAIA_GRPC_PORT,
+            GAIA_GRPC_WEB_PORT,
+            GAIA_ROSETTA_PORT,
+        ]);
+    }
+
+    if explorer {
+        ports.push(EXPLORER_PORT);
+    }
+
+    // The Archway & Gaia gRPC ports are both 9090, they are not run together
+    ports.sort_unstable();
+    ports.dedup();
+
+    for port in ports {
+        let outcome = if std::net::TcpListener::bind(("127.0.0.1", port)).is_ok() {
+            StageOutcome::Passed
+        } else {
+            StageOutcome::Failed("in use".to_owned())
+        };
+
+        report.push(
+            format!("port {port}"),
+            outcome,
+            "stop the localnet already running or whatever else is listening on it",
+        );
+    }
+
+    report
+}
+
+/// The directory `verify_onchain` places its rebuilt artifacts in, relative to the workspace root, so existing artifacts are left untouched
+pub const VERIFY_ARTIFACTS_DIR: &str = "target/verify";
+
+/// The result of rebuilding a contract & comparing it against the code stored on-chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerificationReport {
+    pub code_id: u64,
+    pub package: String,
+    /// The optimizer image the contract was rebuilt with
+    pub optimizer: String,
+    /// The hex encoded sha256 checksum of the rebuilt artifact
+    pub local: String,
+    /// The `data_hash` of the stored code
+    pub on_chain: String,
+}
+
+impl VerificationReport {
+    /// Whether the rebuilt artifact matches the stored code
+    #[must_use]
+    pub fn is_verified(&self) -> bool {
+        self.local.eq_ignore_ascii_case(&self.on_chain)
+    }
+}
+
+impl std::fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = if self.is_verified() {
+            "VERIFIED"
+        } else {
+            "MISMATCH"
+        };
+
+        writeln!(f, "{status} code {} ({})", self.code_id, self.package)?;
+        writeln!(f, "  optimizer: {}", self.optimizer)?;
+        writeln!(f, "  local:     {}", self.local)?;
+        write!(f, "  on-chain:  {}", self.on_chain)
+    }
+}
+
+/// Rebuild `package` with the pinned `cosmwasm/rust-optimizer` & compare its checksum against the `data_hash` of `code_id` on the `network`.
+/// The `amd64` image is always used as the `arm64` images do not produce the same bytecode. The rebuilt artifact is placed in `VERIFY_ARTIFACTS_DIR`.
+///
+/// A mismatch is reported rather than returned as an error, see `VerificationReport::is_verified`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Reading the contract crate manifests fails
+/// - Running the optimizer fails
+/// - Reading the rebuilt artifact fails
+/// - Querying the code info fails
+pub fn verify_onchain(
+    sh: &Shell,
+    network: &dyn Network,
+    code_id: CodeId,
+    package: &str,
+) -> Result<VerificationReport, Error> {
+    let mut optimizer = Optimizer::rust()
+        .arch(OptimizerArch::Amd64)
+        .artifacts_dir(VERIFY_ARTIFACTS_DIR);
+
+    // Build the crate in `<workspace-root>/contracts` if it is a member, otherwise the single contract at the root
+    if sh.path_exists(CONTRACTS_DIR) {
+        let root = sh.current_dir();
+
+        for dir in contract_dirs(sh)? {
+            if package_name(sh, &dir)? != package {
+                continue;
+            }
+
+            let relative = dir.strip_prefix(&root).unwrap_or(&dir);
+
+            optimizer = optimizer.contracts(&[&container_path(relative)]);
+
+            break;
+        }
+    }
+
+    optimizer.run(sh)?;
+
+    let local = checksum(optimizer.artifact_path(sh, package)?)?;
+
+    let node_uri = network.node_uri(sh)?;
+
+    let on_chain = network
+        .cli(sh)?
+        .query(&node_uri)
+        .code_info(code_id)?
+        .data_hash;
+
+    let report = VerificationReport {
+        code_id: code_id.u64(),
+        package: package.to_owned(),
+        optimizer: optimizer.image_ref(),
+        local,
+        on_chain,
+    };
+
+    debug!("{report}");
+
+    Ok(report)
+}
+
+/// The outcome of a `load_test`
+#[derive(Debug, Clone, Default)]
+pub struct LoadTestReport {
+    /// Txs accepted by the node for inclusion
+    pub sent: usize,
+    /// Broadcast & execution errors, plus txs not included before the timeout
+    pub failures: Vec<String>,
+    /// From the first broadcast until the last tx was confirmed or timed out
+    pub elapsed: Duration,
+    /// The time from broadcast to confirmation of each included tx, fastest first
+    pub latencies: Vec<Duration>,
+}
+
+impl LoadTestReport {
+    #[must_use]
+    pub fn confirmed(&self) -> usize {
+        self.latencies.len()
+    }
+
+    /// Confirmed txs per second over the whole run
+    #[must_use]
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+
+        if secs == 0.0 {
+            return 0.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let confirmed = self.confirmed() as f64;
+
+        confirmed / secs
+    }
+
+    /// The latency below which `percentile`% of the confirmed txs were included, e.g. `percentile(99)` for the p99
+    #[must_use]
+    pub fn percentile(&self, percentile: u8) -> Option<Duration> {
+        let last = self.latencies.len().checked_sub(1)?;
+
+        let idx = last * usize::from(percentile.min(100)) / 100;
+
+        self.latencies.get(idx).copied()
+    }
+}
+
+impl std::fmt::Display for LoadTestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} txs sent, {} confirmed, {} failed in {:.1?} ({:.1} tx/s)",
+            self.sent,
+            self.confirmed(),
+            self.failures.len(),
+            self.elapsed,
+            self.throughput()
+        )?;
+
+        if let (Some(p50), Some(p90), Some(p99)) = (
+            self.percentile(50),
+            self.percentile(90),
+            self.percentile(99),
+        ) {
+            writeln!(f, "  latency p50 {p50:.1?}, p90 {p90:.1?}, p99 {p99:.1?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A broadcast load test tx waiting to be included in a block
+struct InFlight {
+    tx_id: TxId,
+    sent: Instant,
+}
+
+/// A key sending load test txs & the sequence of its next tx
+struct Sender<'a> {
+    key: &'a Key,
+    account_number: u64,
+    sequence: u64,
+}
+
+/// Execute `msg` on the `contract` at a target rate of `tps` txs per second for `duration`, round robin from the network's demo keys,
+/// then wait for the txs in flight & report the throughput, latency percentiles & failures.
+///
+/// Txs are signed with explicit sequence numbers so each key can have several in the mempool at once.
+/// The gas limit is simulated once up front, so `msg` should cost about the same every time it is executed.
+/// The achieved rate is capped by how quickly the chain CLI can sign & broadcast, compare `throughput` with `tps`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The network has no demo keys
+/// - JSON serialisation of `msg` fails
+/// - Simulating the gas limit fails
+/// - Querying the accounts of the keys fails
+pub fn load_test<Msg>(
+    sh: &Shell,
+    network: &dyn Network,
+    contract: &Contract,
+    msg: &Msg,
+    tps: u32,
+    duration: Duration,
+) -> Result<LoadTestReport, Error>
+where
+    Msg: Serialize,
+{
+    let keys = network.keys_with_role(KeyRole::Demo);
+
+    let Some(first) = keys.first() else {
+        return Err(Error::NoDemoKeys);
+    };
+
+    let chain_id = network.chain_id();
+
+    let node_uri = network.node_uri(sh)?;
+
+    let msg = serde_json::to_string(msg)?;
+
+    let gas_price = network.medium_gas_price();
+
+    let gas_units = if network.supports_gas_simulation() {
+        network
+            .cli(sh)?
+            .tx(first, &chain_id, &node_uri)
+            .wasm_exec(contract, &msg)
+            .simulate(&gas_price, gas::DEFAULT_ADJUSTMENT)?
+    } else {
+        DEFAULT_GAS_UNITS
+    };
+
+    let mut senders = keys
+        .into_iter()
+        .map(|key| {
+            let account = network.cli(sh)?.query(&node_uri).account(key.address())?;
+
+            Ok(Sender {
+                key,
+                account_number: account.account_number,
+                sequence: account.sequence,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    debug!(
+        "Load testing {contract} at {tps} tx/s for {duration:?} from {} keys with {gas_units} gas per tx",
+        senders.len()
+    );
+
+    let interval = Duration::from_secs(1) / tps.max(1);
+
+    let mut report = LoadTestReport::default();
+
+    let mut in_flight = VecDeque::new();
+
+    let started = Instant::now();
+
+    let mut next_send = started;
+
+    let mut round = 0;
+
+    while started.elapsed() < duration {
+        if Instant::now() >= next_send {
+            let idx = round % senders.len();
+
+            let sender = &mut senders[idx];
+
+            round += 1;
+
+            next_send += interval;
+
+            let sent = Instant::now();
+
+            let res = network
+                .cli(sh)?
+                .tx(sender.key, &chain_id, &node_uri)
+                .wasm_exec(contract, &msg)
+                .sequence(sender.account_number, sender.sequence)
+                .execute(&gas_price.clone().units(gas_units));
+
+            match res {
+                Ok(tx_id) => {
+                    sender.sequence += 1;
+                    report.sent += 1;
+                    in_flight.push_back(InFlight { tx_id, sent });
+                }
+                Err(err) => {
+                    report.failures.push(err.to_string());
+
+                    // The sequence is unused if the tx was rejected before entering the mempool
+                    sender.sequence = network
+                        .cli(sh)?
+                        .query(&node_uri)
+                        .account(sender.key.address())?
+                        .sequence;
+                }
+            }
+
+            continue;
+        }
+
+        if !confirm_oldest(sh, network, &mut in_flight, &mut report)? {
+            std::thread::sleep(next_send.saturating_duration_since(Instant::now()));
+        }
+    }
+
+    let deadline = Instant::now() + DEFAULT_TX_TIMEOUT;
+
+    while !in_flight.is_empty() && Instant::now() < deadline {
+        if !confirm_oldest(sh, network, &mut in_flight, &mut report)? {
+            std::thread::sleep(poll_interval());
+        }
+    }
+
+    report.failures.extend(
+        in_flight
+            .into_iter()
+            .map(|tx| Error::TxTimeout(tx.tx_id).to_string()),
+    );
+
+    report.elapsed = started.elapsed();
+
+    report.latencies.sort();
+
+    Ok(report)
+}
+
+/// Query the oldest tx in flight, recording its latency or failure once it is included. Responds with whether it was.
+fn confirm_oldest(
+    sh: &Shell,
+    network: &dyn Network,
+    in_flight: &mut VecDeque<InFlight>,
+    report: &mut LoadTestReport,
+) -> Result<bool, Error> {
+    let Some(oldest) = in_flight.front() else {
+        return Ok(false);
+    };
+
+    let node_uri = network.node_uri(sh)?;
+
+    match network.cli(sh)?.query(&node_uri).tx(&oldest.tx_id) {
+        Ok(None) => return Ok(false),
+        Ok(Some(_)) => report.latencies.push(oldest.sent.elapsed()),
+        Err(err) => report.failures.push(err.to_string()),
+    }
+
+    in_flight.pop_front();
+
+    Ok(true)
 }
@@ -0,0 +1,94 @@
+//! Machine readable output of high level operations, for scripting & CI
+
+use std::{
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use serde::Serialize;
+
+use crate::{
+    cli::{CodeId, Contract, CwExecuteResponse, TxResult},
+    Error,
+};
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable JSON output, e.g. by the `--json` flag of `cli_app`
+pub fn set_json(enabled: bool) {
+    JSON.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether results should be emitted as JSON on stdout rather than logged
+#[must_use]
+pub fn json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Print `value` to stdout as a single line of JSON
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - JSON serialisation fails
+/// - Writing to stdout fails
+pub fn emit<T: Serialize>(value: &T) -> Result<(), Error> {
+    let mut stdout = std::io::stdout().lock();
+
+    serde_json::to_writer(&mut stdout, value)?;
+
+    writeln!(stdout)?;
+
+    Ok(())
+}
+
+/// A summary of a tx for scripts, fields which don't apply to the tx are omitted
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct TxOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub tx_hash: String,
+    pub gas_used: u128,
+}
+
+/// The fields of a tx response included in a `TxOutput`
+pub trait OutputFields {
+    fn code_id(&self) -> Option<u64> {
+        None
+    }
+
+    fn address(&self) -> Option<String> {
+        None
+    }
+}
+
+impl OutputFields for () {}
+
+impl OutputFields for CwExecuteResponse {}
+
+impl OutputFields for CodeId {
+    fn code_id(&self) -> Option<u64> {
+        Some(self.u64())
+    }
+}
+
+impl OutputFields for Contract {
+    fn address(&self) -> Option<String> {
+        Some(self.as_str().to_owned())
+    }
+}
+
+impl<Response: OutputFields> TxResult<Response> {
+    /// Summarise the tx for machine readable output, see `emit`
+    #[must_use]
+    pub fn output(&self) -> TxOutput {
+        TxOutput {
+            code_id: self.data.code_id(),
+            address: self.data.address(),
+            tx_hash: self.tx_hash.to_string(),
+            gas_used: self.gas_used,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Feedback for long running steps, e.g. cloning & building a chain binary, which would otherwise appear hung for minutes.
+//! With the `indicatif` feature each phase shows a spinner with its elapsed time, otherwise phases are logged.
+
+use std::time::Instant;
+
+use log::info;
+use xshell::Cmd as ShellCmd;
+
+#[cfg(not(feature = "indicatif"))]
+use crate::cli::ShellCmdExt;
+use crate::Error;
+
+/// Run the `phase` of work done by `f`, reporting its progress & elapsed time
+pub(crate) fn phase<T, F>(phase: &str, f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error>,
+{
+    let started = Instant::now();
+
+    #[cfg(feature = "indicatif")]
+    let spinner = {
+        let spinner = indicatif::ProgressBar::new_spinner().with_message(phase.to_owned());
+
+        spinner.set_style(
+            indicatif::ProgressStyle::with_template("{spinner} {msg} [{elapsed}]")
+                .expect("valid template"),
+        );
+
+        spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+
+        spinner
+    };
+
+    #[cfg(not(feature = "indicatif"))]
+    info!("{phase}...");
+
+    let res = f();
+
+    #[cfg(feature = "indicatif")]
+    match &res {
+        Ok(_) => spinner.finish_with_message(format!("{phase} done")),
+        Err(_) => spinner.abandon_with_message(format!("{phase} failed")),
+    }
+
+    match &res {
+        Ok(_) => info!("{phase} done in {:.1?}", started.elapsed()),
+        Err(_) => info!("{phase} failed after {:.1?}", started.elapsed()),
+    }
+
+    res
+}
+
+/// Run `cmd` within a `phase`. With the `indicatif` feature its output is captured so it doesn't garble the spinner,
+/// & only included in the error if it fails.
+pub(crate) fn run(cmd: ShellCmd) -> Result<(), Error> {
+    #[cfg(feature = "indicatif")]
+    {
+        let cmd = cmd.quiet().ignore_status();
+
+        log::debug!("$ {}", crate::cli::redact(&cmd.to_string()));
+
+        let out = cmd.output()?;
+
+        if !out.status.success() {
+            let stdout = String::from_utf8_lossy(&out.stdout);
+            let stderr = String::from_utf8_lossy(&out.stderr);
+
+            return Err(Error::CmdExecute(crate::cli::redact(
+                format!("{stdout}{stderr}").trim(),
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "indicatif"))]
+    cmd.logged().run().map_err(Error::from)
+}
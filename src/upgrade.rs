@@ -0,0 +1,219 @@
+//! Testing contract state compatibility across chain upgrades on a localnet.
+//!
+//! ```ignore
+//! let plan = upgrade::software_upgrade("v2.0.0", Coin::new(10_000_000, "stake"))
+//!     .vote_with(&validators)
+//!     .propose(&sh, &network, &validators[0])?;
+//!
+//! let handle = upgrade::restart(&sh, &network, handle, &plan, |sh| {
+//!     network.swap_image(sh, "ghcr.io/archway-network/archwayd:v2.0.0")
+//! })?;
+//! ```
+
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+use xshell::Shell;
+
+use crate::{
+    cli::{latest_height, wait_for_height, BlockHeight, Coin},
+    contract::{execute_with_network_gas, pass_proposal, DEFAULT_PROPOSAL_TIMEOUT},
+    key::Key,
+    network::{gas, Network, StartLocal},
+    Error,
+};
+
+/// How many blocks after the proposal is submitted the upgrade is scheduled by default, enough for the voting period to end
+pub const DEFAULT_UPGRADE_DELAY_BLOCKS: u64 = 50;
+
+pub struct SoftwareUpgrade {
+    name: String,
+    title: Option<String>,
+    summary: Option<String>,
+    deposit: Coin,
+    delay_blocks: u64,
+    voters: Vec<Key>,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+/// Construct a software upgrade proposal for the upgrade handler `name` of the new chain binary
+#[must_use]
+pub fn software_upgrade(name: &str, deposit: Coin) -> SoftwareUpgrade {
+    SoftwareUpgrade {
+        name: name.to_owned(),
+        title: None,
+        summary: None,
+        deposit,
+        delay_blocks: DEFAULT_UPGRADE_DELAY_BLOCKS,
+        voters: vec![],
+        poll_interval: Duration::from_secs(1),
+        timeout: DEFAULT_PROPOSAL_TIMEOUT,
+    }
+}
+
+impl SoftwareUpgrade {
+    /// Title the proposal, defaults to `Upgrade to <name>`
+    #[must_use]
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_owned());
+        self
+    }
+
+    /// Describe the proposal with `summary`, defaults to the title
+    #[must_use]
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_owned());
+        self
+    }
+
+    /// Schedule the upgrade `blocks` after the latest block, it must pass before then or the upgrade never happens
+    #[must_use]
+    pub fn delay_blocks(mut self, blocks: u64) -> Self {
+        self.delay_blocks = blocks;
+        self
+    }
+
+    /// Vote yes on the proposal with each of the `voters`, e.g. the validator keys of a localnet
+    #[must_use]
+    pub fn vote_with(mut self, voters: &[Key]) -> Self {
+        self.voters = voters.to_vec();
+        self
+    }
+
+    /// How often to query the proposal status while waiting for it to pass
+    #[must_use]
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Give up with `Error::ProposalTimeout` if the proposal has not passed within `timeout` of being submitted
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Submit the proposal from the `proposer`, vote on it & wait for it to pass, responding with the planned upgrade
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Command execution fails
+    /// - The proposal ID is missing from the submission tx events
+    /// - The proposal is rejected or fails
+    /// - The proposal has not passed before the timeout
+    pub fn propose(
+        self,
+        sh: &Shell,
+        network: &dyn Network,
+        proposer: &Key,
+    ) -> Result<UpgradePlan, Error> {
+        let chain_id = network.chain_id();
+
+        let node_uri = network.node_uri(sh)?;
+
+        let latest_height = latest_height(sh, network)?;
+
+        let height = latest_height.u64() + self.delay_blocks;
+
+        let title = self
+            .title
+            .unwrap_or_else(|| format!("Upgrade to {}", self.name));
+
+        debug!(
+            "Proposing software upgrade {} at height {height}",
+            self.name
+        );
+
+        let cmd = network
+            .cli(sh)?
+            .tx(proposer, &chain_id, &node_uri)
+            .software_upgrade_proposal(
+                &self.name,
+                height,
+                &title,
+                self.summary.as_deref().unwrap_or(&title),
+                &self.deposit,
+            );
+
+        let tx_id = execute_with_network_gas(cmd, network, gas::DEFAULT_ADJUSTMENT)?;
+
+        let deadline = Instant::now() + self.timeout;
+
+        let proposal_id = pass_proposal(
+            sh,
+            network,
+            &tx_id,
+            &self.voters,
+            self.poll_interval,
+            deadline,
+        )?;
+
+        info!(
+            "Software upgrade proposal {proposal_id} passed, {} is scheduled at height {height}",
+            self.name
+        );
+
+        Ok(UpgradePlan {
+            name: self.name,
+            height,
+            proposal_id,
+        })
+    }
+}
+
+/// A software upgrade which passed governance, the chain halts at `height` until restarted with the new binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpgradePlan {
+    pub name: String,
+    pub height: u64,
+    pub proposal_id: u64,
+}
+
+impl UpgradePlan {
+    /// Wait for the last block before the upgrade height, after which the chain halts
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if querying the node status fails.
+    pub fn wait_for_halt(&self, sh: &Shell, network: &dyn Network) -> Result<BlockHeight, Error> {
+        wait_for_height(sh, network, self.height.saturating_sub(1))
+    }
+}
+
+/// Wait for the localnet to halt for the upgrade `plan`, stop it by dropping its `handle`, swap in the new binary with `swap`,
+/// then start it again & wait for it to produce the upgrade height, responding with the new handle
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Querying the node status fails
+/// - `swap` fails
+/// - Starting the node fails
+pub fn restart<'shell, N, F>(
+    sh: &'shell Shell,
+    network: &N,
+    handle: N::Handle<'shell>,
+    plan: &UpgradePlan,
+    swap: F,
+) -> Result<N::Handle<'shell>, Error>
+where
+    N: Network + StartLocal,
+    F: FnOnce(&Shell) -> Result<(), Error>,
+{
+    plan.wait_for_halt(sh, network)?;
+
+    info!("Chain halted for upgrade {}, restarting", plan.name);
+
+    drop(handle);
+
+    swap(sh)?;
+
+    let handle = network.start_local(sh)?;
+
+    wait_for_height(sh, network, plan.height)?;
+
+    Ok(handle)
+}
@@ -0,0 +1,382 @@
+//! An interactive deploy, enabled by the `wizard` feature, walking through choosing the network, key, artifact & instantiate msg
+//! & confirming the fees before each tx is broadcast.
+
+use std::{
+    io::{BufRead, Write},
+    path::Path,
+};
+
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use xshell::Shell;
+
+use crate::{
+    cli::GasReport,
+    cli_app::NetworkOption,
+    config::Config,
+    contract::{instantiate, store, DEFAULT_GAS_UNITS},
+    key::Key,
+    network::Network,
+    ops::{list_artifacts, SCHEMA_DIR},
+    ArchwayLocalnet, Error, Initialize, NeutronLocalnet, NeutronTestnet,
+};
+
+/// The primitive type of a JSON schema, following a single `$ref` into the `definitions`, if it is not an object or array
+fn primitive_type<'a>(
+    schema: &'a Value,
+    definitions: Option<&'a Map<String, Value>>,
+) -> Option<&'a str> {
+    let schema = match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => definitions?.get(reference.strip_prefix("#/definitions/")?)?,
+        None => schema,
+    };
+
+    let kind = match schema.get("type")? {
+        Value::String(kind) => kind.as_str(),
+        // e.g. `["string", "null"]` for an `Option<String>`
+        Value::Array(kinds) => kinds
+            .iter()
+            .find_map(|kind| kind.as_str().filter(|kind| *kind != "null"))?,
+        _ => return None,
+    };
+
+    matches!(kind, "string" | "integer" | "number" | "boolean").then_some(kind)
+}
+
+/// The instantiate msg JSON schema generated for the contract of `artifact` by `ops::generate_schemas`, if there is one
+fn instantiate_schema(sh: &Shell, artifact: &Path) -> Option<Value> {
+    let stem = artifact.file_stem()?.to_str()?;
+
+    // Artifacts are named after the package with `-` replaced by `_`, optionally with an architecture suffix
+    let package = stem.trim_end_matches("-aarch64");
+
+    [package.to_owned(), package.replace('_', "-")]
+        .into_iter()
+        .find_map(|name| {
+            let path = sh
+                .current_dir()
+                .join(SCHEMA_DIR)
+                .join(&name)
+                .join(format!("{name}.json"));
+
+            let json = sh.read_file(path).ok()?;
+
+            let mut schema: Value = serde_json::from_str(&json).ok()?;
+
+            schema.get_mut("instantiate").map(Value::take)
+        })
+}
+
+/// Line based prompts on stdin & stdout
+struct Prompt<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompt<R, W> {
+    /// Ask `question`, responding with the trimmed answer or `default` if it is empty
+    fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String, Error> {
+        match default {
+            Some(default) => write!(self.output, "{question} [{default}]: ")?,
+            None => write!(self.output, "{question}: ")?,
+        }
+
+        self.output.flush()?;
+
+        let mut answer = String::new();
+
+        if self.input.read_line(&mut answer)? == 0 {
+            return Err(Error::WizardAborted);
+        }
+
+        let answer = answer.trim();
+
+        Ok(match default {
+            Some(default) if answer.is_empty() => default.to_owned(),
+            _ => answer.to_owned(),
+        })
+    }
+
+    /// Ask to choose one of `options` by number, responding with its index
+    fn select(
+        &mut self,
+        question: &str,
+        options: &[String],
+        default: usize,
+    ) -> Result<usize, Error> {
+        for (n, option) in options.iter().enumerate() {
+            writeln!(self.output, "  {}) {option}", n + 1)?;
+        }
+
+        loop {
+            let answer = self.ask(question, Some(&(default + 1).to_string()))?;
+
+            match answer.parse::<usize>() {
+                Ok(n) if (1..=options.len()).contains(&n) => return Ok(n - 1),
+                _ => writeln!(self.output, "Enter a number from 1 to {}", options.len())?,
+            }
+        }
+    }
+
+    fn confirm(&mut self, question: &str) -> Result<bool, Error> {
+        let answer = self.ask(&format!("{question} (y/n)"), Some("n"))?;
+
+        Ok(matches!(answer.as_str(), "y" | "Y" | "yes"))
+    }
+
+    /// Ask for the value of a JSON schema property, responding with `None` if an optional property is left empty
+    fn value(
+        &mut self,
+        name: &str,
+        schema: &Value,
+        definitions: Option<&Map<String, Value>>,
+        required: bool,
+    ) -> Result<Option<Value>, Error> {
+        let kind = primitive_type(schema, definitions);
+
+        let mut question = format!("{name} ({})", kind.unwrap_or("json"));
+
+        if let Some(description) = schema.get("description").and_then(Value::as_str) {
+            question.push_str(" - ");
+            question.push_str(description);
+        }
+
+        loop {
+            let answer = self.ask(&question, None)?;
+
+            if answer.is_empty() && !required {
+                return Ok(None);
+            }
+
+            let value = match kind {
+                Some("string") => Ok(Value::String(answer)),
+                Some("boolean") => Ok(Value::Bool(matches!(answer.as_str(), "y" | "yes" | "true"))),
+                _ => serde_json::from_str(&answer),
+            };
+
+            match value {
+                Ok(value) => return Ok(Some(value)),
+                Err(err) => writeln!(self.output, "Invalid value: {err}")?,
+            }
+        }
+    }
+
+    /// Build the instantiate msg by prompting for each property of the `schema`, or as raw JSON without one
+    fn instantiate_msg(&mut self, schema: Option<&Value>) -> Result<Value, Error> {
+        let Some(properties) = schema
+            .and_then(|schema| schema.get("properties"))
+            .and_then(Value::as_object)
+        else {
+            return self
+                .value("instantiate msg", &Value::Null, None, true)
+                .map(Option::unwrap_or_default);
+        };
+
+        let schema = schema.unwrap();
+
+        let definitions = schema.get("definitions").and_then(Value::as_object);
+
+        let required: Vec<_> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+
+        let mut msg = Map::new();
+
+        for (name, property) in properties {
+            if let Some(value) = self.value(
+                name,
+                property,
+                definitions,
+                required.contains(&name.as_str()),
+            )? {
+                msg.insert(name.clone(), value);
+            }
+        }
+
+        Ok(Value::Object(msg))
+    }
+
+    /// Show the gas `report` of the next tx & ask to broadcast it
+    fn confirm_fees(
+        &mut self,
+        network: &dyn Network,
+        action: &str,
+        report: Option<&GasReport>,
+    ) -> Result<(), Error> {
+        let price = network.medium_gas_price();
+
+        match report {
+            Some(GasReport { estimate, limit }) => writeln!(
+                self.output,
+                "{action} is estimated to use {estimate} gas, with a limit of {limit} gas at {price}"
+            )?,
+            None => writeln!(self.output, "{action} will use a fixed limit of {DEFAULT_GAS_UNITS} gas at {price}")?,
+        }
+
+        if self.confirm("Broadcast?")? {
+            Ok(())
+        } else {
+            Err(Error::WizardAborted)
+        }
+    }
+
+    /// Choose a network, defaulting to the one in `xtask.toml`
+    fn network(&mut self, config: &Config) -> Result<NetworkOption, Error> {
+        let variants = NetworkOption::value_variants();
+
+        let options: Vec<_> = variants
+            .iter()
+            .filter_map(ValueEnum::to_possible_value)
+            .map(|value| value.get_name().to_owned())
+            .collect();
+
+        let default = config
+            .network
+            .as_deref()
+            .and_then(|network| options.iter().position(|option| option == network))
+            .unwrap_or_default();
+
+        Ok(variants[self.select("Network", &options, default)?])
+    }
+
+    fn deploy(&mut self, sh: &Shell, config: &Config, network: &dyn Network) -> Result<(), Error> {
+        let keys = network.keys();
+
+        if keys.is_empty() {
+            return Err(Error::KeyNotFound("deployer".to_owned()));
+        }
+
+        let default_key = config
+            .deployer(network)
+            .ok()
+            .and_then(|deployer| {
+                keys.iter()
+                    .position(|key| key.address() == deployer.address())
+            })
+            .unwrap_or_default();
+
+        let options: Vec<_> = keys
+            .iter()
+            .map(|key| format!("{} ({})", key.name(), key.address()))
+            .collect();
+
+        let key: &Key = &keys[self.select("Deploy with key", &options, default_key)?];
+
+        let artifacts = list_artifacts(sh, &config.artifacts_dir(sh))?;
+
+        if artifacts.is_empty() {
+            writeln!(self.output, "No artifacts to deploy, build them first")?;
+
+            return Err(Error::WizardAborted);
+        }
+
+        let options: Vec<_> = artifacts
+            .iter()
+            .map(|artifact| artifact.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+
+        let artifact = &artifacts[self.select("Artifact", &options, 0)?];
+
+        let default_label = artifact.file_stem().unwrap().to_string_lossy().into_owned();
+
+        let label = self.ask("Label", Some(&default_label))?;
+
+        let admin = self.ask("Admin, or 'none'", Some(key.address()))?;
+
+        let msg = self.instantiate_msg(instantiate_schema(sh, artifact).as_ref())?;
+
+        writeln!(
+            self.output,
+            "Instantiate msg: {}",
+            serde_json::to_string_pretty(&msg)?
+        )?;
+
+        let simulate = network.supports_gas_simulation();
+
+        let report = if simulate {
+            Some(
+                store(artifact)
+                    .gas_adjustment(config.gas.adjustment)
+                    .simulate(sh, network, key)?
+                    .gas,
+            )
+        } else {
+            None
+        };
+
+        self.confirm_fees(network, "Storing the code", report.as_ref())?;
+
+        let code_id = store(artifact)
+            .gas_adjustment(config.gas.adjustment)
+            .send(sh, network, key)?;
+
+        writeln!(self.output, "Stored code id {code_id}")?;
+
+        let tx = || {
+            let tx =
+                instantiate(code_id, &label, msg.clone()).gas_adjustment(config.gas.adjustment);
+
+            if admin == "none" {
+                tx
+            } else {
+                tx.admin(&admin)
+            }
+        };
+
+        let report = if simulate {
+            Some(tx().simulate(sh, network, key)?.gas)
+        } else {
+            None
+        };
+
+        self.confirm_fees(network, "Instantiating", report.as_ref())?;
+
+        let contract = tx().send(sh, network, key)?;
+
+        writeln!(self.output, "Instantiated {label} at {contract}")?;
+
+        Ok(())
+    }
+}
+
+/// Interactively deploy a contract to `network`, or a network chosen at the prompt, defaulting to the one in `xtask.toml`
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - Loading `xtask.toml` fails
+/// - Initializing the network fails
+/// - The network has no keys or there are no artifacts
+/// - Reading input fails or the user declines to broadcast a tx
+/// - Simulating or sending a tx fails
+pub fn deploy(sh: &Shell, network: Option<NetworkOption>) -> Result<(), Error> {
+    let config = Config::load(sh)?;
+
+    let stdin = std::io::stdin();
+
+    let mut prompt = Prompt {
+        input: stdin.lock(),
+        output: std::io::stdout(),
+    };
+
+    let network = match network {
+        Some(network) => network,
+        None => prompt.network(&config)?,
+    };
+
+    match network {
+        NetworkOption::ArchwayLocal => {
+            prompt.deploy(sh, &config, &ArchwayLocalnet::initialize(sh)?)
+        }
+        NetworkOption::NeutronLocal => {
+            prompt.deploy(sh, &config, &NeutronLocalnet::initialize(sh)?)
+        }
+        NetworkOption::NeutronTestnet => {
+            prompt.deploy(sh, &config, &NeutronTestnet::initialize(sh)?)
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use cosmwasm_xtask::{contract::required_capabilities, Error};
+
+const HEADER: &[u8] = b"\0asm\x01\0\0\0";
+
+const CUSTOM_SECTION: u8 = 0;
+const EXPORT_SECTION: u8 = 7;
+
+fn section(id: u8, contents: &[u8]) -> Vec<u8> {
+    let len = u8::try_from(contents.len()).unwrap();
+
+    assert!(len < 0x80, "test sections use a single byte length");
+
+    [&[id, len], contents].concat()
+}
+
+fn function_exports(names: &[&str]) -> Vec<u8> {
+    let mut contents = vec![u8::try_from(names.len()).unwrap()];
+
+    for (index, name) in names.iter().enumerate() {
+        contents.push(u8::try_from(name.len()).unwrap());
+        contents.extend_from_slice(name.as_bytes());
+        // Function export kind & index
+        contents.push(0);
+        contents.push(u8::try_from(index).unwrap());
+    }
+
+    contents
+}
+
+/// Write `bytecode` to a file unique to the test, as `required_capabilities` reads from a path
+fn wasm_file(test: &str, bytecode: &[u8]) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("cosmwasm_xtask_{test}_{}.wasm", std::process::id()));
+
+    std::fs::write(&path, bytecode).unwrap();
+
+    path
+}
+
+fn capabilities(test: &str, bytecode: &[u8]) -> Result<Vec<String>, Error> {
+    let path = wasm_file(test, bytecode);
+
+    let capabilities = required_capabilities(&path);
+
+    std::fs::remove_file(path).unwrap();
+
+    capabilities
+}
+
+fn assert_invalid(result: Result<Vec<String>, Error>) {
+    assert!(
+        matches!(result, Err(Error::InvalidWasm(_))),
+        "expected invalid wasm, got {result:?}"
+    );
+}
+
+#[test]
+fn required_exports_are_listed() {
+    let bytecode = [
+        HEADER,
+        &section(CUSTOM_SECTION, b"\x04name"),
+        &section(
+            EXPORT_SECTION,
+            &function_exports(&["requires_staking", "instantiate", "requires_iterator"]),
+        ),
+    ]
+    .concat();
+
+    assert_eq!(
+        capabilities("required_exports", &bytecode).unwrap(),
+        vec!["iterator".to_owned(), "staking".to_owned()]
+    );
+}
+
+#[test]
+fn module_without_exports_requires_nothing() {
+    let bytecode = [HEADER, &section(CUSTOM_SECTION, b"\x04name")].concat();
+
+    assert!(capabilities("without_exports", &bytecode)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn exports_without_requirements_require_nothing() {
+    let bytecode = [
+        HEADER,
+        &section(
+            EXPORT_SECTION,
+            &function_exports(&["instantiate", "execute"]),
+        ),
+    ]
+    .concat();
+
+    assert!(capabilities("without_requirements", &bytecode)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn missing_header_is_invalid() {
+    assert_invalid(capabilities("missing_header", b"not wasm"));
+}
+
+#[test]
+fn truncated_section_length_is_invalid() {
+    // The continuation bit is set on the last byte of the file
+    let bytecode = [HEADER, &[EXPORT_SECTION, 0x80]].concat();
+
+    assert_invalid(capabilities("truncated_length", &bytecode));
+}
+
+#[test]
+fn overlong_section_length_is_invalid() {
+    // Every byte sets the continuation bit, beyond the 5 bytes a u32 can take
+    let bytecode = [HEADER, &[EXPORT_SECTION], &[0xff; 6], &[0; 4]].concat();
+
+    assert_invalid(capabilities("overlong_length", &bytecode));
+}
+
+#[test]
+fn section_longer_than_the_file_is_invalid() {
+    let bytecode = [HEADER, &[EXPORT_SECTION, 0x7f, 1]].concat();
+
+    assert_invalid(capabilities("section_too_long", &bytecode));
+}
+
+#[test]
+fn truncated_export_name_is_invalid() {
+    // One export whose name claims 16 bytes but has 3
+    let bytecode = [HEADER, &section(EXPORT_SECTION, b"\x01\x10req")].concat();
+
+    assert_invalid(capabilities("truncated_name", &bytecode));
+}
+
+#[test]
+fn truncated_export_index_is_invalid() {
+    // One export missing its index after the kind
+    let bytecode = [HEADER, &section(EXPORT_SECTION, b"\x01\x03abc\x00")].concat();
+
+    assert_invalid(capabilities("truncated_index", &bytecode));
+}
@@ -0,0 +1,137 @@
+use cosmwasm_xtask::{cli::ChainError, Error};
+
+#[test]
+fn unauthorized_code() {
+    assert_eq!(
+        ChainError::from_code("sdk", 4, "signature verification failed"),
+        ChainError::Unauthorized
+    );
+}
+
+#[test]
+fn insufficient_funds_code() {
+    assert_eq!(
+        ChainError::from_code("sdk", 5, "0untrn is smaller than 100untrn"),
+        ChainError::InsufficientFunds
+    );
+}
+
+#[test]
+fn out_of_gas_code() {
+    assert_eq!(
+        ChainError::from_code("sdk", 11, "gasWanted: 200000, gasUsed: 200417"),
+        ChainError::OutOfGas
+    );
+}
+
+#[test]
+fn account_sequence_mismatch_code() {
+    assert_eq!(
+        ChainError::from_code("sdk", 32, "expected 5, got 4"),
+        ChainError::AccountSequenceMismatch
+    );
+}
+
+#[test]
+fn code_not_found_code() {
+    assert_eq!(
+        ChainError::from_code("wasm", 28, "no such code"),
+        ChainError::CodeNotFound
+    );
+}
+
+#[test]
+fn unspecific_code_is_classified_from_the_log() {
+    assert_eq!(
+        ChainError::from_code("sdk", 13, "insufficient fee: got 1untrn required 500untrn"),
+        ChainError::InsufficientFunds
+    );
+}
+
+#[test]
+fn unknown_code_is_classified_from_the_log_or_custom() {
+    assert_eq!(
+        ChainError::from_code(
+            "wasm",
+            5,
+            "execute wasm contract failed: Unauthorized: only the owner"
+        ),
+        ChainError::Unauthorized
+    );
+
+    assert_eq!(
+        ChainError::from_code(
+            "wasm",
+            5,
+            "execute wasm contract failed: Generic error: paused"
+        ),
+        ChainError::Custom {
+            codespace: "wasm".to_owned(),
+            code: 5,
+        }
+    );
+}
+
+#[test]
+fn same_code_in_another_codespace_is_custom() {
+    assert_eq!(
+        ChainError::from_code("wasm", 11, "invalid msg"),
+        ChainError::Custom {
+            codespace: "wasm".to_owned(),
+            code: 11,
+        }
+    );
+}
+
+#[test]
+fn logs_are_classified() {
+    let cases = [
+        ("out of gas in location: WriteFlat", ChainError::OutOfGas),
+        (
+            "spendable balance 0untrn is smaller than 1untrn: insufficient funds",
+            ChainError::InsufficientFunds,
+        ),
+        (
+            "insufficient fees; got: 0untrn",
+            ChainError::InsufficientFunds,
+        ),
+        (
+            "account sequence mismatch, expected 5, got 4: incorrect account sequence",
+            ChainError::AccountSequenceMismatch,
+        ),
+        (
+            "Error: rpc error: code = Unknown desc = no such code: 42",
+            ChainError::CodeNotFound,
+        ),
+        ("code not found", ChainError::CodeNotFound),
+        (
+            "Unauthorized: only the admin can migrate",
+            ChainError::Unauthorized,
+        ),
+    ];
+
+    for (log, expected) in cases {
+        assert_eq!(ChainError::from_log(log), expected, "{log}");
+    }
+}
+
+#[test]
+fn unknown_log_is_unknown() {
+    assert_eq!(
+        ChainError::from_log("post failed: connection refused"),
+        ChainError::Unknown
+    );
+}
+
+#[test]
+fn failed_commands_are_classified_from_their_output() {
+    assert_eq!(
+        Error::CmdExecute("out of gas in location: ReadFlat".to_owned()).chain_error(),
+        Some(ChainError::OutOfGas)
+    );
+
+    assert_eq!(
+        Error::CmdExecute("post failed: connection refused".to_owned()).chain_error(),
+        None
+    );
+}
@@ -0,0 +1,70 @@
+use base64::Engine;
+use prost::Message;
+
+use cosmwasm_xtask::cli::{
+    decode_tx, decode_tx_as, MsgSend, ProtobufAny, ProtobufCoin, TxBody, TxEncoding, TxMsg, TxRaw,
+};
+
+fn msg_send() -> MsgSend {
+    MsgSend {
+        from_address: "neutron1from".to_owned(),
+        to_address: "neutron1to".to_owned(),
+        amount: vec![ProtobufCoin {
+            denom: "untrn".to_owned(),
+            amount: "100".to_owned(),
+        }],
+    }
+}
+
+fn tx_bytes() -> Vec<u8> {
+    let body = TxBody {
+        messages: vec![ProtobufAny {
+            type_url: "/cosmos.bank.v1beta1.MsgSend".to_owned(),
+            value: msg_send().encode_to_vec(),
+        }],
+        memo: "memo".to_owned(),
+        timeout_height: 42,
+    };
+
+    TxRaw {
+        body_bytes: body.encode_to_vec(),
+        auth_info_bytes: vec![],
+        signatures: vec![vec![1, 2, 3]],
+    }
+    .encode_to_vec()
+}
+
+#[test]
+fn hex_tx_is_decoded() {
+    let tx = decode_tx(&hex::encode(tx_bytes())).unwrap();
+
+    assert_eq!(tx.messages, vec![TxMsg::Send(msg_send())]);
+    assert_eq!(tx.memo, "memo");
+    assert_eq!(tx.timeout_height, 42);
+    assert_eq!(tx.signatures, vec![vec![1, 2, 3]]);
+}
+
+#[test]
+fn base64_tx_is_decoded() {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(tx_bytes());
+
+    assert_eq!(
+        decode_tx(&encoded).unwrap(),
+        decode_tx_as(&encoded, TxEncoding::Base64).unwrap()
+    );
+    assert_eq!(
+        decode_tx(&encoded).unwrap().messages,
+        vec![TxMsg::Send(msg_send())]
+    );
+}
+
+#[test]
+fn hex_digits_without_a_hex_tx_are_decoded_as_base64() {
+    // Valid hex & valid base64, but as hex it is not a tx with messages
+    let encoded = "deadbeef";
+
+    assert_eq!(
+        decode_tx(encoded).map_err(|err| err.to_string()),
+        decode_tx_as(encoded, TxEncoding::Base64).map_err(|err| err.to_string()),
+    );
+}
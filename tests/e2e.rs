@@ -1,17 +1,14 @@
 use anyhow::Result;
-use serial_test::serial;
 use xshell::Shell;
 
 use cosmwasm_xtask::{
-    cli::wait_for_blocks, execute, instantiate, query, store, ArchwayLocalnet, Initialize, Network,
-    NeutronLocalnet, StartLocal,
+    execute, harness::localnet, instantiate, query, store, ArchwayLocalnet, Isolate, Network,
+    NeutronLocalnet, TestIsolation,
 };
 
 fn deploy(sh: &Shell, network: &dyn Network) -> Result<()> {
     let demo_account = network.keys().first().expect("at least one account");
 
-    wait_for_blocks(sh, network)?;
-
     let code_id = store("examples/cw20_base.wasm").send(sh, network, demo_account)?;
 
     let contract = instantiate(
@@ -55,25 +52,33 @@ fn deploy(sh: &Shell, network: &dyn Network) -> Result<()> {
 }
 
 #[test]
-#[serial]
 fn archway_localnet() -> Result<()> {
+    localnet::<ArchwayLocalnet>().run_isolated(deploy)
+}
+
+#[test]
+fn archway_clean_isolated() -> Result<()> {
     let sh = Shell::new()?;
 
-    let network = ArchwayLocalnet::initialize(&sh)?;
+    let isolation = TestIsolation::unique();
 
-    let _handle = network.start_local(&sh)?;
+    ArchwayLocalnet::initialize_isolated(&sh, &isolation)?;
 
-    deploy(&sh, &network)
-}
+    let home_path = sh
+        .current_dir()
+        .join("target/cosmwasm_xtask/network/archway")
+        .join(format!("data_{}", isolation.namespace()));
 
-#[test]
-#[serial]
-fn neutron_localnet() -> Result<()> {
-    let sh = Shell::new()?;
+    assert!(sh.path_exists(&home_path));
+
+    ArchwayLocalnet::clean_isolated(&sh, &isolation)?;
 
-    let network = NeutronLocalnet::initialize(&sh)?;
+    assert!(!sh.path_exists(&home_path));
 
-    let _handle = network.start_local(&sh)?;
+    Ok(())
+}
 
-    deploy(&sh, &network)
+#[test]
+fn neutron_localnet() -> Result<()> {
+    localnet::<NeutronLocalnet>().run(deploy)
 }
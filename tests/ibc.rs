@@ -0,0 +1,50 @@
+use sha2::{Digest, Sha256};
+
+use cosmwasm_xtask::ibc::received_denom;
+
+fn ibc_denom(trace: &str) -> String {
+    format!(
+        "ibc/{}",
+        hex::encode_upper(Sha256::digest(trace.as_bytes()))
+    )
+}
+
+#[test]
+fn native_denom_gains_a_hop() {
+    assert_eq!(
+        received_denom("untrn", "channel-0", "channel-1"),
+        ibc_denom("transfer/channel-1/untrn")
+    );
+}
+
+#[test]
+fn returning_voucher_is_unwound() {
+    assert_eq!(
+        received_denom("transfer/channel-0/uatom", "channel-0", "channel-1"),
+        "uatom"
+    );
+}
+
+#[test]
+fn tokenfactory_denom_gains_a_hop() {
+    let denom = "factory/neutron1m9l358xunhhwds0568za49mzhvuxx9ux8xafx2/sub";
+
+    assert_eq!(
+        received_denom(denom, "channel-0", "channel-1"),
+        ibc_denom(&format!("transfer/channel-1/{denom}"))
+    );
+}
+
+#[test]
+fn returning_tokenfactory_voucher_is_unwound() {
+    let denom = "factory/neutron1m9l358xunhhwds0568za49mzhvuxx9ux8xafx2/sub";
+
+    assert_eq!(
+        received_denom(
+            &format!("transfer/channel-0/{denom}"),
+            "channel-0",
+            "channel-1"
+        ),
+        denom
+    );
+}